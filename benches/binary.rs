@@ -0,0 +1,63 @@
+//! Regression benchmark for decoding random binary data, in which
+//! most bytes are malformed UTF-8; the time per byte should not grow
+//! with the amount of data, either for a [Reader] or for a [Decoder]
+//! fed all of the data at once
+//!
+//! Run with `cargo bench --bench binary`
+use std::time::Instant;
+use utf8_read::{Char, Decoder, Reader};
+
+/// Generate pseudo-random bytes with a xorshift generator
+fn random_bytes(n:usize) -> Vec<u8> {
+    let mut x : u64 = 0x9e3779b97f4a7c15;
+    (0..n).map(|_| {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        x as u8
+    }).collect()
+}
+
+/// Time a function over the data, printing the time per byte
+fn time<F:Fn(&[u8]) -> usize>(name:&str, data:&[u8], f:F) {
+    let start = Instant::now();
+    let n = f(data);
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(n, data.len());
+    println!("{:24} {:10} bytes {:8.3}s {:8.2} ns/byte", name, data.len(), elapsed, elapsed * 1.0e9 / (data.len() as f64));
+}
+
+fn by_reader(mut bytes:&[u8]) -> usize {
+    let mut reader = Reader::new(&mut bytes);
+    loop {
+        match reader.next_char() {
+            Ok(Char::Eof) => { break; }
+            Ok(Char::NoData) => { panic!("Unexpected NoData"); }
+            _ => (),
+        }
+    }
+    reader.borrow_pos().byte()
+}
+
+fn by_decoder(bytes:&[u8]) -> usize {
+    let mut decoder = Decoder::new();
+    let mut feed = decoder.feed(bytes);
+    loop {
+        match feed.next_char() {
+            Ok(Char::NoData) => { break; }
+            Ok(Char::Eof) => { panic!("Unexpected Eof"); }
+            _ => (),
+        }
+    }
+    drop(feed);
+    let _ = decoder.finish();
+    decoder.borrow_pos().byte()
+}
+
+fn main() {
+    let data = random_bytes(64 * 1024 * 1024);
+    for size in [1024 * 1024, 8 * 1024 * 1024, 64 * 1024 * 1024] {
+        time("Reader::next_char", &data[..size], by_reader);
+        time("Decoder::next_char", &data[..size], by_decoder);
+    }
+}
@@ -0,0 +1,73 @@
+//! Throughput of the character-at-a-time and bulk reading methods of
+//! the [Reader] over a large generated ASCII log, and over a log with
+//! some multi-byte characters
+//!
+//! Run with `cargo bench --bench throughput`
+use std::time::Instant;
+use utf8_read::{Char, Reader};
+
+/// Size of the generated logs in bytes (approximately)
+const LOG_SIZE : usize = 64 * 1024 * 1024;
+
+/// Generate a log, with every `unicode_every` line containing
+/// multi-byte characters (never, if 0)
+fn generate_log(unicode_every:usize) -> String {
+    let mut log = String::with_capacity(LOG_SIZE + 256);
+    let mut i = 0;
+    while log.len() < LOG_SIZE {
+        log += &format!("2024-01-01T00:00:{:02} INFO request {} completed in {}ms status=200\n", i % 60, i, i % 997);
+        if unicode_every > 0 && i % unicode_every == 0 {
+            log += "caf\u{e9} \u{2764} \u{1f600}\n";
+        }
+        i += 1;
+    }
+    log
+}
+
+/// Time a function over the log, printing the throughput
+fn time<F:Fn(&[u8]) -> usize>(name:&str, log:&str, f:F) {
+    let start = Instant::now();
+    let n = f(log.as_bytes());
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(n, log.len());
+    println!("{:32} {:8.3}s {:8.3} GB/s", name, elapsed, (log.len() as f64) / elapsed / 1.0e9);
+}
+
+fn by_next_char(mut bytes:&[u8]) -> usize {
+    let mut reader = Reader::new(&mut bytes);
+    let mut n = 0;
+    while let Char::Char(ch) = reader.next_char().unwrap() {
+        n += ch.len_utf8();
+    }
+    n
+}
+
+fn by_read_to_string(mut bytes:&[u8]) -> usize {
+    let mut reader = Reader::new(&mut bytes);
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap()
+}
+
+fn by_read_line(mut bytes:&[u8]) -> usize {
+    let mut reader = Reader::new(&mut bytes);
+    let mut s = String::new();
+    let mut n = 0;
+    loop {
+        s.clear();
+        match reader.read_line(&mut s).unwrap() {
+            0 => { break; }
+            l => { n += l; }
+        }
+    }
+    n
+}
+
+fn main() {
+    for (name, unicode_every) in [("ASCII log", 0), ("log with some unicode", 10)] {
+        let log = generate_log(unicode_every);
+        println!("{} ({} bytes)", name, log.len());
+        time("  next_char", &log, by_next_char);
+        time("  read_to_string", &log, by_read_to_string);
+        time("  read_line", &log, by_read_line);
+    }
+}
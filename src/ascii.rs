@@ -0,0 +1,56 @@
+//a Documentation
+/*!
+Word-at-a-time helpers for the fast paths of the bulk-read methods of
+the [Reader](crate::Reader), which operate on mostly-ASCII data many
+bytes at a time rather than one character at a time.
+!*/
+
+//a Imports
+use core::convert::TryInto;
+
+//a Constants
+/// Number of bytes handled at a time
+const WORD_BYTES : usize = core::mem::size_of::<usize>();
+
+/// A word with the top bit of every byte set
+const HIGH_BITS : usize = usize::MAX / 255 * 0x80;
+
+//a Functions
+//fp ascii_prefix_len
+/// Return the number of bytes at the start of `bytes` that are ASCII
+#[inline]
+pub(crate) fn ascii_prefix_len(bytes:&[u8]) -> usize {
+    let mut n = 0;
+    let mut words = bytes.chunks_exact(WORD_BYTES);
+    for w in &mut words {
+        let w = usize::from_ne_bytes(w.try_into().unwrap());
+        if w & HIGH_BITS != 0 {
+            break;
+        }
+        n += WORD_BYTES;
+    }
+    n + bytes[n..].iter().take_while(|b| b.is_ascii()).count()
+}
+
+//fp count_chars
+/// Count the characters in a valid UTF-8 byte sequence, which is the
+/// number of bytes that are not continuation bytes (0x80 to 0xBF)
+#[inline]
+pub(crate) fn count_chars(bytes:&[u8]) -> usize {
+    let mut continuations = 0;
+    let mut words = bytes.chunks_exact(WORD_BYTES);
+    for w in &mut words {
+        let w = usize::from_ne_bytes(w.try_into().unwrap());
+        // A continuation byte has its top bit set and the next bit clear
+        continuations += (w & !(w << 1) & HIGH_BITS).count_ones() as usize;
+    }
+    continuations += words.remainder().iter().filter(|b| (**b & 0xc0) == 0x80).count();
+    bytes.len() - continuations
+}
+
+//fp count_newlines
+/// Count the newline characters in a byte sequence
+#[inline]
+pub(crate) fn count_newlines(bytes:&[u8]) -> usize {
+    memchr::memchr_iter(b'\n', bytes).count()
+}
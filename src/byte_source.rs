@@ -0,0 +1,210 @@
+//a Imports
+#[cfg(not(feature = "std"))]
+use core::fmt;
+
+//a IoError and IoErrorKind
+//tp IoError
+/// The error type of a [ByteSource]; with the `std` feature this is
+/// [std::io::Error]
+#[cfg(feature = "std")]
+pub type IoError = std::io::Error;
+
+//tp IoErrorKind
+/// The kind of an [IoError]; with the `std` feature this is
+/// [std::io::ErrorKind]
+#[cfg(feature = "std")]
+pub type IoErrorKind = std::io::ErrorKind;
+
+//tp IoErrorKind
+/// The kind of an [IoError]; without the `std` feature this has the
+/// commonly used kinds of [std::io::ErrorKind] (including all those
+/// of an embedded-io stream), and [Other](IoErrorKind::Other) for any
+/// other error
+///
+/// As with [std::io::ErrorKind] the enumeration is non-exhaustive, so
+/// that code written without the `std` feature also builds with it.
+#[cfg(not(feature = "std"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IoErrorKind {
+    /// The read was interrupted, and should be retried
+    Interrupted,
+    /// The source has no data at the moment, but is not at its end
+    WouldBlock,
+    /// The source timed out without data, but is not at its end
+    TimedOut,
+    /// An entity was not found
+    NotFound,
+    /// The operation lacked the necessary privileges
+    PermissionDenied,
+    /// The connection was refused by the remote peer
+    ConnectionRefused,
+    /// The connection was reset by the remote peer
+    ConnectionReset,
+    /// The connection was aborted by the remote peer
+    ConnectionAborted,
+    /// The operation failed as the source is not connected
+    NotConnected,
+    /// An address was already in use
+    AddrInUse,
+    /// An address was not available
+    AddrNotAvailable,
+    /// The operation failed as a pipe was closed
+    BrokenPipe,
+    /// An entity already exists
+    AlreadyExists,
+    /// A parameter was incorrect
+    InvalidInput,
+    /// The data was not valid for the operation
+    InvalidData,
+    /// The operation is not supported
+    Unsupported,
+    /// The operation could not allocate the memory it needed
+    OutOfMemory,
+    /// A write returned zero bytes written
+    WriteZero,
+    /// Any other error, which is passed to the client
+    Other,
+}
+
+//tp IoError
+/// The error type of a [ByteSource]; without the `std` feature this
+/// is just an [IoErrorKind]
+///
+/// As with [std::io::Error], an [IoError] may be created from an
+/// [IoErrorKind] using [From], and its kind is returned by
+/// [kind](IoError::kind); it provides no more than this (it is not
+/// [Clone] or [PartialEq], for example), so that code written without
+/// the `std` feature also builds with it.
+#[cfg(not(feature = "std"))]
+#[derive(Debug)]
+pub struct IoError {
+    /// The kind of the error
+    kind : IoErrorKind,
+}
+
+//ip IoError
+#[cfg(not(feature = "std"))]
+impl IoError {
+    //mp kind
+    /// Get the kind of the error
+    pub fn kind(&self) -> IoErrorKind {
+        self.kind
+    }
+}
+
+//ip From<IoErrorKind> for IoError
+#[cfg(not(feature = "std"))]
+impl From<IoErrorKind> for IoError {
+    fn from(kind:IoErrorKind) -> Self {
+        Self { kind }
+    }
+}
+
+//ip Display for IoError
+#[cfg(not(feature = "std"))]
+impl fmt::Display for IoError {
+    //mp fmt - format for humans
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            IoErrorKind::Interrupted       => write!(f, "operation interrupted"),
+            IoErrorKind::WouldBlock        => write!(f, "operation would block"),
+            IoErrorKind::TimedOut          => write!(f, "timed out"),
+            IoErrorKind::NotFound          => write!(f, "entity not found"),
+            IoErrorKind::PermissionDenied  => write!(f, "permission denied"),
+            IoErrorKind::ConnectionRefused => write!(f, "connection refused"),
+            IoErrorKind::ConnectionReset   => write!(f, "connection reset"),
+            IoErrorKind::ConnectionAborted => write!(f, "connection aborted"),
+            IoErrorKind::NotConnected      => write!(f, "not connected"),
+            IoErrorKind::AddrInUse         => write!(f, "address in use"),
+            IoErrorKind::AddrNotAvailable  => write!(f, "address not available"),
+            IoErrorKind::BrokenPipe        => write!(f, "broken pipe"),
+            IoErrorKind::AlreadyExists     => write!(f, "entity already exists"),
+            IoErrorKind::InvalidInput      => write!(f, "invalid input parameter"),
+            IoErrorKind::InvalidData       => write!(f, "invalid data"),
+            IoErrorKind::Unsupported       => write!(f, "unsupported"),
+            IoErrorKind::OutOfMemory       => write!(f, "out of memory"),
+            IoErrorKind::WriteZero         => write!(f, "write zero"),
+            IoErrorKind::Other             => write!(f, "other error"),
+        }
+    }
+}
+
+//a ByteSource
+//tp ByteSource
+/// A [ByteSource] supplies the bytes that a [Reader](crate::Reader)
+/// decodes, in the manner of [std::io::Read]; with the `std` feature
+/// every [std::io::Read] is a [ByteSource].
+///
+/// Without the `std` feature (for example on a microcontroller) the
+/// client implements [ByteSource] for its device, such as a UART
+/// receive buffer.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{ByteSource, Char, IoError, IoErrorKind, Reader};
+///     /// A UART receive FIFO
+///     struct Uart { fifo : Vec<u8> }
+///     impl ByteSource for Uart {
+///         fn read(&mut self, buf:&mut [u8]) -> Result<usize, IoError> {
+///             if self.fifo.is_empty() {
+///                 return Err(IoErrorKind::WouldBlock.into());
+///             }
+///             let n = buf.len().min(self.fifo.len());
+///             buf[..n].copy_from_slice(&self.fifo[..n]);
+///             self.fifo.drain(..n);
+///             Ok(n)
+///         }
+///     }
+///     let mut reader = Reader::new(Uart { fifo:b"ok\xe2\x9c".to_vec() });
+///     assert_eq!(reader.next_char().unwrap(), Char::Char('o'));
+///     assert_eq!(reader.next_char().unwrap(), Char::Char('k'));
+///     assert_eq!(reader.next_char().unwrap(), Char::NoData);
+///     reader.borrow_mut().fifo.push(0x93);
+///     assert_eq!(reader.next_char().unwrap(), Char::Char('\u{2713}'));
+/// ```
+pub trait ByteSource {
+    /// Read bytes into `buf`, returning the number of bytes read; as
+    /// for [std::io::Read::read], a result of zero indicates the end
+    /// of the source (unless the [Reader](crate::Reader) has been
+    /// configured otherwise with
+    /// [set_eof_on_no_data](crate::Reader::set_eof_on_no_data)).
+    ///
+    /// # Errors
+    ///
+    /// An error of kind [WouldBlock](IoErrorKind::WouldBlock) or
+    /// [TimedOut](IoErrorKind::TimedOut) indicates that there is no
+    /// data at the moment; one of kind
+    /// [Interrupted](IoErrorKind::Interrupted) causes the read to be
+    /// retried. Any other error is returned to the client of the
+    /// [Reader](crate::Reader).
+    fn read(&mut self, buf:&mut [u8]) -> core::result::Result<usize, IoError>;
+}
+
+//ip ByteSource for std::io::Read
+#[cfg(feature = "std")]
+impl <R:std::io::Read> ByteSource for R {
+    fn read(&mut self, buf:&mut [u8]) -> core::result::Result<usize, IoError> {
+        std::io::Read::read(self, buf)
+    }
+}
+
+//ip ByteSource for &[u8]
+#[cfg(not(feature = "std"))]
+impl ByteSource for &[u8] {
+    fn read(&mut self, buf:&mut [u8]) -> core::result::Result<usize, IoError> {
+        let n = buf.len().min(self.len());
+        buf[..n].copy_from_slice(&self[..n]);
+        *self = &self[n..];
+        Ok(n)
+    }
+}
+
+//ip ByteSource for &mut ByteSource
+#[cfg(not(feature = "std"))]
+impl <B:ByteSource + ?Sized> ByteSource for &mut B {
+    fn read(&mut self, buf:&mut [u8]) -> core::result::Result<usize, IoError> {
+        (**self).read(buf)
+    }
+}
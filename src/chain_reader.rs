@@ -0,0 +1,218 @@
+//a Imports
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use crate::{ByteSource, Char, Error, Reader, Result, SourcePosition, StreamPosition};
+
+//a ChainReader
+//tp ChainReader
+/// The [ChainReader] provides a single stream of characters from a
+/// sequence of named sources, such as a list of files, reading each
+/// source in turn with a [Reader].
+///
+/// Each source is decoded independently, with its own
+/// [StreamPosition] that starts at line 1 character 1; a UTF-8
+/// encoding is never decoded across the boundary between two
+/// sources, and a source that ends part way through an encoding is
+/// reported as a [MalformedUtf8](Error::MalformedUtf8) error of the
+/// kind given by [classify_at_eof](crate::Utf8ErrorKind::classify_at_eof).
+///
+/// Errors are returned as [Error::InSource], naming the source in
+/// which they occurred.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::ChainReader;
+///     let a : &[u8] = b"first\n";
+///     let b : &[u8] = b"second\n";
+///     let mut reader = ChainReader::new()
+///         .with_source("a.txt", a)
+///         .with_source("b.txt", b);
+///     let mut text = String::new();
+///     for x in reader.into_iter() {
+///         text.push(x.unwrap());
+///     }
+///     assert_eq!(text, "first\nsecond\n");
+///     assert_eq!(format!("{}", reader.source_position()), "b.txt line 2 char 1");
+/// ```
+pub struct ChainReader<R:ByteSource> {
+    /// `eof_on_no_data` is used for the [Reader] of every source
+    eof_on_no_data : bool,
+    /// Sources that have not yet been started, with their names
+    pending        : VecDeque<(Arc<str>, R)>,
+    /// The source currently being read, its index and its name
+    current        : Option<(usize, Arc<str>, Reader<R>)>,
+    /// Number of sources that have been started
+    num_started    : usize,
+    /// Position after the end of the last source to be completed
+    last_pos       : Option<SourcePosition>,
+    /// Position within the current source of the character most
+    /// recently returned
+    char_pos       : StreamPosition,
+}
+
+//ip Default for ChainReader
+impl <R:ByteSource> Default for ChainReader<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//ip ChainReader
+impl <R:ByteSource> ChainReader<R> {
+    //fp new
+    /// Returns a new [ChainReader] with no sources
+    pub fn new() -> Self {
+        Self {
+            eof_on_no_data : true,
+            pending        : VecDeque::new(),
+            current        : None,
+            num_started    : 0,
+            last_pos       : None,
+            char_pos       : StreamPosition::new(),
+        }
+    }
+
+    //cp set_eof_on_no_data
+    /// Build pattern function to set the `eof_on_no_data` for the
+    /// [Reader] of every source; see
+    /// [Reader::set_eof_on_no_data]
+    pub fn set_eof_on_no_data(mut self, eof_on_no_data:bool) -> Self {
+        self.eof_on_no_data = eof_on_no_data;
+        self
+    }
+
+    //cp with_source
+    /// Build pattern function to add a named source to the end of the
+    /// chain
+    pub fn with_source(mut self, name:&str, stream:R) -> Self {
+        self.add_source(name, stream);
+        self
+    }
+
+    //mp add_source
+    /// Add a named source to the end of the chain; this may be used
+    /// while the chain is being read
+    pub fn add_source(&mut self, name:&str, stream:R) {
+        self.pending.push_back((name.into(), stream));
+    }
+
+    //mp set_eof
+    /// Set the eof indicator of the source currently being read; see
+    /// [Reader::set_eof]. Once the buffered data of the source has
+    /// been consumed the next source is started.
+    pub fn set_eof(&mut self, eof:bool) {
+        if let Some((_, _, reader)) = &mut self.current {
+            reader.set_eof(eof);
+        }
+    }
+
+    //mp source_index
+    /// Get the index of the source currently being read (or that
+    /// will be read next), starting at 0 for the first source added
+    pub fn source_index(&self) -> usize {
+        match &self.current {
+            Some((index, _, _)) => *index,
+            None => self.num_started,
+        }
+    }
+
+    //mp source_position
+    /// Get the [SourcePosition] of the next character to be returned
+    ///
+    /// The end of a source is only detected when a character is
+    /// requested, so between sources this is the position after the
+    /// end of the previous source; use
+    /// [char_position](ChainReader::char_position) after
+    /// [next_char](ChainReader::next_char) to get the position of the
+    /// character that was returned. When all the sources have been
+    /// read this is the position after the end of the last source.
+    pub fn source_position(&self) -> SourcePosition {
+        if let Some((_, name, reader)) = &self.current {
+            SourcePosition::new(name.clone(), *reader.borrow_pos())
+        } else if let Some((name, _)) = self.pending.front() {
+            SourcePosition::new(name.clone(), StreamPosition::new())
+        } else if let Some(pos) = &self.last_pos {
+            pos.clone()
+        } else {
+            SourcePosition::new("".into(), StreamPosition::new())
+        }
+    }
+
+    //mp char_position
+    /// Get the [SourcePosition] of the character most recently
+    /// returned by [next_char](ChainReader::next_char)
+    pub fn char_position(&self) -> SourcePosition {
+        match &self.current {
+            Some((_, name, _)) => SourcePosition::new(name.clone(), self.char_pos),
+            None => self.source_position(),
+        }
+    }
+
+    //mp borrow_reader
+    /// Borrow the [Reader] of the source currently being read, if there is one
+    pub fn borrow_reader(&self) -> Option<&Reader<R>> {
+        self.current.as_ref().map(|(_, _, reader)| reader)
+    }
+
+    //mp borrow_reader_mut
+    /// Mutably borrow the [Reader] of the source currently being read, if there is one
+    pub fn borrow_reader_mut(&mut self) -> Option<&mut Reader<R>> {
+        self.current.as_mut().map(|(_, _, reader)| reader)
+    }
+
+    //mp next_char
+    /// Return the next character from the chain of sources, if one is
+    /// available, or [EOF](Char::Eof) once every source has been read.
+    ///
+    /// # Errors
+    ///
+    /// Any error from the [Reader] of a source is returned wrapped in
+    /// an [Error::InSource] with the name of the source.
+    pub fn next_char(&mut self) -> Result<Char> {
+        loop {
+            if self.current.is_none() {
+                match self.pending.pop_front() {
+                    None => { return Ok(Char::Eof); }
+                    Some((name, stream)) => {
+                        let reader = Reader::new(stream)
+                            .set_eof_on_no_data(self.eof_on_no_data)
+                            .set_source_name(&name);
+                        self.current = Some((self.num_started, name, reader));
+                        self.num_started += 1;
+                    }
+                }
+            }
+            let (_, name, reader) = self.current.as_mut().unwrap();
+            self.char_pos = *reader.borrow_pos();
+            match reader.next_char() {
+                Ok(Char::Eof) => {
+                    self.last_pos = Some(SourcePosition::new(name.clone(), *reader.borrow_pos()));
+                    self.current = None;
+                }
+                Ok(c) => { return Ok(c); }
+                Err(e) => { return Err(Error::InSource(name.clone(), Box::new(e))); }
+            }
+        }
+    }
+
+    //zz All done
+}
+
+//ip Iterator for ChainReader - iterate over characters
+#[allow(missing_doc_code_examples)]
+impl <R:ByteSource> Iterator for &mut ChainReader<R> {
+    type Item = Result<char>;
+
+    //mp next - return next character or None if end of all sources
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Ok(Char::Char(ch)) => Some(Ok(ch)),
+            Ok(_)              => None,
+            Err(x)             => Some(Err(x)),
+        }
+    }
+
+    //zz All done
+}
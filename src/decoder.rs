@@ -0,0 +1,489 @@
+//a Imports
+use alloc::vec::Vec;
+use crate::{Char, Error, Result, StreamPosition, Utf8ErrorKind};
+use crate::ascii::ascii_prefix_len;
+
+//a Decoding core
+//tp Decoded
+/// The result of decoding the start of a byte slice as UTF-8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Decoded {
+    /// A character was decoded from the given number of bytes
+    Char(char, usize),
+    /// The given number of bytes are malformed UTF-8
+    Malformed(Utf8ErrorKind, usize),
+    /// The slice is empty, or holds only the start of a UTF-8 encoding
+    Incomplete,
+}
+
+//fp validate
+/// Find the length of the valid UTF-8 at the start of `bytes`, which
+/// is the unconsumed data of a stream; runs of ASCII are validated a
+/// word at a time.
+///
+/// If there is no valid UTF-8 at the start then the bytes are either
+/// [Incomplete](Decoded::Incomplete) or
+/// [Malformed](Decoded::Malformed), and that is returned as an error.
+pub(crate) fn validate(bytes:&[u8]) -> core::result::Result<usize, Decoded> {
+    let ascii = ascii_prefix_len(bytes);
+    if ascii == bytes.len() {
+        return Ok(ascii);
+    }
+    match core::str::from_utf8(&bytes[ascii..]) {
+        Ok(_) => Ok(bytes.len()),
+        Err(e) => {
+            let n = ascii + e.valid_up_to();
+            if n > 0 {
+                Ok(n)
+            } else { // no valid data - check it is just incomplete, or an actual error
+                match e.error_len() {
+                    None    => Err(Decoded::Incomplete),
+                    Some(n) => Err(Decoded::Malformed(Utf8ErrorKind::classify(bytes), n)),
+                }
+            }
+        }
+    }
+}
+
+//fp decode
+/// Decode the first character of `bytes`, which is the unconsumed
+/// data of a stream.
+///
+/// `valid` is the number of bytes at the start of `bytes` that are
+/// already known to be valid UTF-8; it is updated as validation is
+/// performed and as characters are decoded, and the caller must drop
+/// the bytes of a decoded character or of a malformed encoding from
+/// the front of the data before decoding again.
+///
+/// Each byte is examined a bounded number of times whatever the
+/// density of malformed UTF-8: a run of the data is only validated
+/// when it starts with an ASCII byte, and validation stops at the
+/// first malformed encoding, so the bytes it examines become the
+/// valid prefix and are not validated again; other characters and
+/// malformed encodings are decoded one encoding at a time.
+pub(crate) fn decode(bytes:&[u8], valid:&mut usize) -> Decoded {
+    if *valid == 0 {
+        match bytes.first() {
+            None => { return Decoded::Incomplete; }
+            Some(b) if *b >= 0x80 => { return decode_sequence(bytes); }
+            _ => (),
+        }
+        match validate(bytes) {
+            Ok(0)  => { return Decoded::Incomplete; }
+            Ok(n)  => { *valid = n; }
+            Err(d) => { return d; }
+        }
+    }
+    let s = {
+        // core::str::from_utf8(&bytes[..*valid]).unwrap()
+        unsafe {
+            core::str::from_utf8_unchecked(&bytes[..*valid])
+        }
+    };
+    let ch = s.chars().next().unwrap();
+    let n = ch.len_utf8();
+    *valid -= n;
+    Decoded::Char(ch, n)
+}
+
+//fi decode_sequence
+/// Decode the single UTF-8 encoding at the start of `bytes`, which
+/// must be non-empty, examining at most four bytes.
+///
+/// A malformed encoding has the same length as given by
+/// [core::str::Utf8Error::error_len] - the lead byte and any
+/// continuation bytes that could start a valid encoding with it.
+fn decode_sequence(bytes:&[u8]) -> Decoded {
+    let b0 = bytes[0];
+    let (len, b1_range) = {
+        match b0 {
+            0x00..=0x7f => { return Decoded::Char(b0 as char, 1); }
+            0xc2..=0xdf => (2, 0x80..=0xbf),
+            0xe0        => (3, 0xa0..=0xbf),
+            0xed        => (3, 0x80..=0x9f),
+            0xe1..=0xef => (3, 0x80..=0xbf),
+            0xf0        => (4, 0x90..=0xbf),
+            0xf1..=0xf3 => (4, 0x80..=0xbf),
+            0xf4        => (4, 0x80..=0x8f),
+            _ => { return Decoded::Malformed(Utf8ErrorKind::classify(bytes), 1); }
+        }
+    };
+    let mut ch = (b0 & (0x7f >> len)) as u32;
+    for i in 1..len {
+        let b = {
+            match bytes.get(i) {
+                None => { return Decoded::Incomplete; }
+                Some(b) => *b,
+            }
+        };
+        let in_range = if i == 1 { b1_range.contains(&b) } else { (0x80..=0xbf).contains(&b) };
+        if !in_range {
+            let kind = if i == 1 { Utf8ErrorKind::classify(bytes) } else { Utf8ErrorKind::Incomplete };
+            return Decoded::Malformed(kind, i);
+        }
+        ch = (ch << 6) | ((b & 0x3f) as u32);
+    }
+    // The ranges above exclude surrogates and values beyond 0x10ffff
+    Decoded::Char(char::from_u32(ch).unwrap(), len)
+}
+
+//a Decoder
+//tp Decoder
+/// The [Decoder] provides push-style UTF-8 decoding, for bytes that
+/// are received in callbacks (such as websocket frames or buffers
+/// from foreign code) rather than from a [std::io::Read] stream. It
+/// uses the same decoding as the [Reader](crate::Reader), and tracks
+/// the [StreamPosition] of the characters in the same way.
+///
+/// Bytes are supplied with [feed](Decoder::feed), which returns a
+/// [Feed] that decodes characters directly from the caller's bytes
+/// with [next_char](Feed::next_char) until it returns
+/// [NoData](Char::NoData). When the stream is complete,
+/// [finish](Decoder::finish) reports any UTF-8 encoding that was left
+/// incomplete.
+///
+/// The bytes fed are not copied: the [Decoder] carries only the start
+/// of a partial UTF-8 encoding (at most three bytes) from one feed to
+/// the next. Bytes that are not decoded before the [Feed] is dropped
+/// are copied into the [Decoder], and are returned by its own
+/// [next_char](Decoder::next_char) (and by the next [Feed]).
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{Char, Decoder};
+///     let mut decoder = Decoder::new();
+///     let mut text = String::new();
+///     for frame in [&b"ab\xf0\x9f"[..], &b"\x98\x80c"[..]] {
+///         let mut feed = decoder.feed(frame);
+///         while let Char::Char(ch) = feed.next_char().unwrap() {
+///             text.push(ch);
+///         }
+///     }
+///     decoder.finish().unwrap();
+///     assert_eq!(text, "ab\u{1f600}c");
+///     assert_eq!(decoder.borrow_pos().byte(), 7);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Decoder {
+    /// Bytes carried from previous feeds; those before `start` have been consumed
+    pending    : Vec<u8>,
+    /// Offset of the first unconsumed byte in `pending`
+    start      : usize,
+    /// Number of bytes from `start` known to be valid UTF-8
+    valid      : usize,
+    /// Set by [finish](Decoder::finish) when no more data will be fed
+    eof        : bool,
+    /// Position of the next character
+    stream_pos : StreamPosition,
+}
+
+//ip Decoder
+impl Decoder {
+    //fp new
+    /// Returns a new [Decoder], with a stream position set to the
+    /// normal start of a stream - byte 0, line 1, character 1
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //cp set_position
+    /// Build pattern function to set the initial stream position
+    pub fn set_position(mut self, stream_pos:StreamPosition) -> Self {
+        self.stream_pos = stream_pos;
+        self
+    }
+
+    //mp feed
+    /// Supply more bytes of the stream to the [Decoder], returning a
+    /// [Feed] that decodes them in place
+    ///
+    /// Bytes fed after [finish](Decoder::finish) are ignored.
+    pub fn feed<'a>(&'a mut self, data:&'a [u8]) -> Feed<'a> {
+        let data = if self.eof { &data[..0] } else { data };
+        Feed { decoder:self, data, ofs:0, valid:0 }
+    }
+
+    //mp borrow_pos
+    /// Borrow the stream position of the next character to be returned
+    pub fn borrow_pos(&self) -> &StreamPosition {
+        &self.stream_pos
+    }
+
+    //mp borrow_buffer
+    /// Borrow the bytes that have been fed but not yet decoded
+    pub fn borrow_buffer(&self) -> &[u8] {
+        &self.pending[self.start..]
+    }
+
+    //mp eof
+    /// Returns true if [finish](Decoder::finish) has been invoked
+    pub fn eof(&self) -> bool {
+        self.eof
+    }
+
+    //mp next_char
+    /// Return the next character from the bytes that have been fed
+    /// but not decoded by a [Feed], if one is available, or
+    /// [NoData](Char::NoData) if more bytes are required. Once
+    /// [finish](Decoder::finish) has been invoked and all of the data
+    /// consumed, [Eof](Char::Eof) is returned.
+    ///
+    /// # Errors
+    ///
+    /// May return [Error::MalformedUtf8] if the next bytes in the
+    /// stream do not make a well-formed UTF8 character; the bytes are
+    /// consumed, so that decoding may continue after the error.
+    pub fn next_char(&mut self) -> Result<Char> {
+        let bytes = &self.pending[self.start..];
+        match decode(bytes, &mut self.valid) {
+            Decoded::Char(ch, n) => {
+                self.consume(n);
+                self.stream_pos.move_by(n, ch);
+                Ok(Char::Char(ch))
+            }
+            Decoded::Malformed(kind, n) => {
+                self.malformed(kind, n)
+            }
+            Decoded::Incomplete => {
+                if bytes.is_empty() {
+                    if self.eof { Ok(Char::Eof) } else { Ok(Char::NoData) }
+                } else if self.eof {
+                    self.malformed(Utf8ErrorKind::classify_at_eof(bytes), bytes.len())
+                } else {
+                    Ok(Char::NoData)
+                }
+            }
+        }
+    }
+
+    //mp finish
+    /// Indicate that no more bytes will be fed to the [Decoder].
+    ///
+    /// Characters that have been fed but not yet decoded are still
+    /// returned by [next_char](Decoder::next_char), after which it
+    /// returns [Eof](Char::Eof).
+    ///
+    /// # Errors
+    ///
+    /// Returns [Error::MalformedUtf8] with the kind given by
+    /// [classify_at_eof](Utf8ErrorKind::classify_at_eof) if the next
+    /// bytes to be decoded are an incomplete UTF-8 encoding; these
+    /// bytes are consumed.
+    pub fn finish(&mut self) -> Result<()> {
+        self.eof = true;
+        let bytes = &self.pending[self.start..];
+        let mut valid = self.valid;
+        if !bytes.is_empty() && decode(bytes, &mut valid) == Decoded::Incomplete {
+            self.malformed(Utf8ErrorKind::classify_at_eof(bytes), bytes.len())?;
+        }
+        Ok(())
+    }
+
+    //mi next_carried
+    /// Return the next character of the carried bytes followed by
+    /// `data` from offset `ofs`, updating `ofs` by the bytes of
+    /// `data` that are consumed
+    ///
+    /// If the carried bytes are the start of an encoding then at most
+    /// four bytes are combined to decode it; otherwise the carried
+    /// bytes are decoded on their own.
+    fn next_carried(&mut self, data:&[u8], ofs:&mut usize) -> Result<Char> {
+        let carried = &self.pending[self.start..];
+        let mut valid = self.valid;
+        if decode(carried, &mut valid) != Decoded::Incomplete {
+            return self.next_char();
+        }
+        let c = carried.len();
+        let k = (4 - c).min(data.len() - *ofs);
+        let mut seq = [0u8; 4];
+        seq[..c].copy_from_slice(carried);
+        seq[c..c+k].copy_from_slice(&data[*ofs..*ofs+k]);
+        let mut valid = 0;
+        match decode(&seq[..c+k], &mut valid) {
+            Decoded::Char(ch, n) => {
+                self.consume_carried(n, ofs);
+                self.stream_pos.move_by(n, ch);
+                Ok(Char::Char(ch))
+            }
+            Decoded::Malformed(kind, n) => {
+                let r = Error::malformed_utf8(self.stream_pos, kind, &seq[..n]);
+                self.stream_pos.move_on_bytes(n);
+                self.consume_carried(n, ofs);
+                r
+            }
+            Decoded::Incomplete => {
+                self.pending.extend_from_slice(&data[*ofs..*ofs+k]);
+                *ofs += k;
+                Ok(Char::NoData)
+            }
+        }
+    }
+
+    //mi consume_carried
+    /// Consume `n` bytes of the carried bytes followed by the data of
+    /// a [Feed] from offset `ofs`
+    fn consume_carried(&mut self, n:usize, ofs:&mut usize) {
+        let c = self.pending.len() - self.start;
+        if n < c {
+            self.consume(n);
+        } else {
+            self.consume(c);
+            *ofs += n - c;
+        }
+        self.valid = 0;
+    }
+
+    //mi consume
+    /// Consume `n` carried bytes, releasing the carried bytes when
+    /// all have been consumed
+    fn consume(&mut self, n:usize) {
+        self.start += n;
+        if self.start == self.pending.len() {
+            self.pending.clear();
+            self.pending.shrink_to(4);
+            self.start = 0;
+        }
+    }
+
+    //mi carry
+    /// Carry the bytes of a [Feed] that were not decoded, which have
+    /// `valid` bytes of valid UTF-8 at their start
+    fn carry(&mut self, data:&[u8], valid:usize) {
+        if self.start == self.pending.len() {
+            self.valid = valid;
+        }
+        self.pending.drain(..self.start);
+        self.start = 0;
+        self.pending.extend_from_slice(data);
+    }
+
+    //mi malformed
+    /// Consume `n` bytes of malformed UTF-8, returning an error
+    /// describing them
+    fn malformed(&mut self, kind:Utf8ErrorKind, n:usize) -> Result<Char> {
+        let r = Error::malformed_utf8(self.stream_pos, kind, &self.pending[self.start..self.start+n]);
+        self.stream_pos.move_on_bytes(n);
+        self.consume(n);
+        r
+    }
+
+    //zz All done
+}
+
+//a Feed
+//tp Feed
+/// A [Feed] decodes the bytes supplied to a [Decoder] by
+/// [feed](Decoder::feed) directly from the caller's slice, following
+/// any partial UTF-8 encoding carried by the [Decoder] from previous
+/// feeds.
+///
+/// When the [Feed] is dropped, the start of a partial encoding at
+/// the end of the bytes is carried by the [Decoder]; any other bytes
+/// that have not been decoded are copied into the [Decoder].
+#[derive(Debug)]
+pub struct Feed<'a> {
+    /// The decoder, which carries bytes between feeds and holds the position
+    decoder : &'a mut Decoder,
+    /// The bytes fed
+    data    : &'a [u8],
+    /// Offset of the first byte of `data` that has not been decoded
+    ofs     : usize,
+    /// Number of bytes from `ofs` known to be valid UTF-8
+    valid   : usize,
+}
+
+//ip Feed
+impl Feed<'_> {
+    //mp borrow_pos
+    /// Borrow the stream position of the next character to be returned
+    pub fn borrow_pos(&self) -> &StreamPosition {
+        &self.decoder.stream_pos
+    }
+
+    //mp next_char
+    /// Return the next character of the stream, if one is available,
+    /// or [NoData](Char::NoData) if more bytes are required; after
+    /// [finish](Decoder::finish) this is as
+    /// [Decoder::next_char](Decoder::next_char).
+    ///
+    /// # Errors
+    ///
+    /// May return [Error::MalformedUtf8] if the next bytes in the
+    /// stream do not make a well-formed UTF8 character; the bytes are
+    /// consumed, so that decoding may continue after the error.
+    pub fn next_char(&mut self) -> Result<Char> {
+        if self.decoder.eof {
+            return self.decoder.next_char();
+        }
+        if self.decoder.start < self.decoder.pending.len() {
+            self.valid = 0;
+            return self.decoder.next_carried(self.data, &mut self.ofs);
+        }
+        let bytes = &self.data[self.ofs..];
+        match decode(bytes, &mut self.valid) {
+            Decoded::Char(ch, n) => {
+                self.ofs += n;
+                self.decoder.stream_pos.move_by(n, ch);
+                Ok(Char::Char(ch))
+            }
+            Decoded::Malformed(kind, n) => {
+                let r = Error::malformed_utf8(self.decoder.stream_pos, kind, &bytes[..n]);
+                self.decoder.stream_pos.move_on_bytes(n);
+                self.ofs += n;
+                r
+            }
+            Decoded::Incomplete => {
+                self.decoder.carry(bytes, 0);
+                self.ofs = self.data.len();
+                Ok(Char::NoData)
+            }
+        }
+    }
+
+    //zz All done
+}
+
+//ip Drop for Feed
+impl Drop for Feed<'_> {
+    //mp drop - carry the bytes that have not been decoded
+    fn drop(&mut self) {
+        if self.ofs < self.data.len() {
+            self.decoder.carry(&self.data[self.ofs..], self.valid);
+        }
+    }
+}
+
+//ip Iterator for Feed - iterate over characters
+#[allow(missing_doc_code_examples)]
+impl Iterator for Feed<'_> {
+    type Item = Result<char>;
+
+    //mp next - return next character or None if more data is required
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Ok(Char::Char(ch)) => Some(Ok(ch)),
+            Ok(_)              => None,
+            Err(x)             => Some(Err(x)),
+        }
+    }
+
+    //zz All done
+}
+
+//ip Iterator for Decoder - iterate over characters
+#[allow(missing_doc_code_examples)]
+impl Iterator for &mut Decoder {
+    type Item = Result<char>;
+
+    //mp next - return next character or None if more data is required
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Ok(Char::Char(ch)) => Some(Ok(ch)),
+            Ok(_)              => None,
+            Err(x)             => Some(Err(x)),
+        }
+    }
+
+    //zz All done
+}
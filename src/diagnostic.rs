@@ -0,0 +1,262 @@
+//a Imports
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::StreamPosition;
+
+//a Line
+//tp Line
+/// The text of a line held by a [LineHistory], in which each
+/// malformed UTF-8 sequence is shown as U+FFFD
+#[derive(Debug, Clone, Default)]
+struct Line {
+    /// Text of the line
+    text      : String,
+    /// Number of characters in `text`
+    chars     : usize,
+    /// The character index in `text` and the stream byte offset of
+    /// each malformed UTF-8 sequence; these do not advance the
+    /// character position of the stream
+    malformed : Vec<(usize, usize)>,
+}
+
+//ip Line
+impl Line {
+    //mp push
+    /// Add a character to the line
+    fn push(&mut self, ch:char) {
+        self.text.push(ch);
+        self.chars += 1;
+    }
+
+    //mp push_malformed
+    /// Add a U+FFFD for a malformed UTF-8 sequence at stream byte
+    /// offset `byte`
+    fn push_malformed(&mut self, byte:usize) {
+        self.malformed.push((self.chars, byte));
+        self.push(char::REPLACEMENT_CHARACTER);
+    }
+
+    //mp push_bytes
+    /// Add the characters of `bytes`, which start at stream byte
+    /// offset `byte`, up to the first newline
+    fn push_bytes(&mut self, mut bytes:&[u8], mut byte:usize) {
+        loop {
+            let (valid, error_len) = {
+                match core::str::from_utf8(bytes) {
+                    Ok(text) => (text, None),
+                    Err(e)   => (core::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap(), Some(e.error_len())),
+                }
+            };
+            for ch in valid.chars() {
+                if ch == '\n' {
+                    return;
+                }
+                self.push(ch);
+            }
+            match error_len {
+                None => { return; }
+                Some(n) => {
+                    byte += valid.len();
+                    self.push_malformed(byte);
+                    let n = n.unwrap_or(bytes.len() - valid.len());
+                    bytes = &bytes[valid.len() + n..];
+                    byte += n;
+                }
+            }
+        }
+    }
+
+    //mp trim_cr
+    /// Remove a trailing carriage return from the line
+    fn trim_cr(mut self) -> Self {
+        if self.text.ends_with('\r') {
+            self.text.pop();
+            self.chars -= 1;
+        }
+        self
+    }
+
+    //zz All done
+}
+
+//a LineHistory
+//tp LineHistory
+/// The [LineHistory] keeps the text of the line currently being read
+/// by a [Reader](crate::Reader), and optionally some previous lines,
+/// so that diagnostics can show the source text for an error
+#[derive(Debug, Clone)]
+pub(crate) struct LineHistory {
+    /// Maximum number of previous lines to keep
+    max_previous : usize,
+    /// Previous lines, oldest first, with their line numbers; these
+    /// exclude the newline characters
+    previous     : VecDeque<(usize, Line)>,
+    /// Line number of the current line
+    line_num     : usize,
+    /// Text of the current line read so far
+    current      : Line,
+}
+
+//ip LineHistory
+impl LineHistory {
+    //fp new
+    /// Create a new [LineHistory] keeping up to `max_previous` lines
+    /// before the current line
+    pub(crate) fn new(max_previous:usize) -> Self {
+        Self {
+            max_previous,
+            previous : VecDeque::new(),
+            line_num : 0,
+            current  : Line::default(),
+        }
+    }
+
+    //mi start_line
+    /// Start recording at line `line_num`, if it is not the current line
+    fn start_line(&mut self, line_num:usize) {
+        if line_num != self.line_num {
+            self.current = Line::default();
+            self.line_num = line_num;
+        }
+    }
+
+    //mp push
+    /// Record the character `ch` which was read at line `line_num`
+    pub(crate) fn push(&mut self, line_num:usize, ch:char) {
+        self.start_line(line_num);
+        if ch == '\n' {
+            let line = core::mem::take(&mut self.current);
+            if self.max_previous > 0 {
+                if self.previous.len() == self.max_previous {
+                    self.previous.pop_front();
+                }
+                self.previous.push_back((line_num, line));
+            }
+            self.line_num += 1;
+        } else {
+            self.current.push(ch);
+        }
+    }
+
+    //mp push_malformed
+    /// Record a malformed UTF-8 sequence which was read at `pos`
+    pub(crate) fn push_malformed(&mut self, pos:&StreamPosition) {
+        self.start_line(pos.line_position().0);
+        self.current.push_malformed(pos.byte());
+    }
+
+    //mp push_str
+    /// Record the characters of `s`, the first of which was read at
+    /// line `line_num`
+    pub(crate) fn push_str(&mut self, mut line_num:usize, s:&str) {
+        for ch in s.chars() {
+            self.push(line_num, ch);
+            if ch == '\n' {
+                line_num += 1;
+            }
+        }
+    }
+
+    //mp line
+    /// Get a line, if it is held; for the current line this is the
+    /// text read so far followed by `rest`, which should be the bytes
+    /// that have been buffered but not yet read, starting at stream
+    /// byte offset `rest_byte`
+    fn line(&self, line_num:usize, rest:&[u8], rest_byte:usize) -> Option<Line> {
+        if line_num == self.line_num {
+            let mut line = self.current.clone();
+            line.push_bytes(rest, rest_byte);
+            Some(line.trim_cr())
+        } else {
+            self.previous
+                .iter()
+                .find(|(n,_)| *n == line_num)
+                .map(|(_,line)| line.clone().trim_cr())
+        }
+    }
+
+    //zz All done
+}
+
+//fi trim_cr
+/// Remove a trailing carriage return from a line of text
+fn trim_cr(text:&str) -> &str {
+    text.strip_suffix('\r').unwrap_or(text)
+}
+
+//a Rendering
+//fp render
+/// Render a diagnostic message in the style of rustc, with the
+/// position and (if it is held in `lines`) the source text of the
+/// line with a caret under the character at the position.
+///
+/// Previous lines held in `lines` are shown before the line of the
+/// position. Malformed UTF-8 is shown as U+FFFD; as it does not
+/// advance the character position, the caret is only placed under
+/// it if the position is that of the malformed bytes.
+pub(crate) fn render(name:Option<&str>,
+                     lines:Option<&LineHistory>,
+                     rest:&[u8],
+                     rest_byte:usize,
+                     pos:Option<StreamPosition>,
+                     message:&str) -> String {
+    let mut r = format!("error: {}\n", message);
+    let pos = {
+        match pos {
+            None => { return r; }
+            Some(pos) => pos,
+        }
+    };
+    let (line_num, char_ofs) = pos.line_position();
+    let name = name.unwrap_or("<input>");
+    let line = lines.and_then(|l| l.line(line_num, rest, rest_byte));
+    let line = {
+        match line {
+            None => {
+                r += &format!(" --> {}:{}:{}\n", name, line_num, char_ofs);
+                return r;
+            }
+            Some(line) => line,
+        }
+    };
+    let lines = lines.unwrap();
+    let width = format!("{}", line_num).len();
+    r += &format!("{:w$}--> {}:{}:{}\n", "", name, line_num, char_ofs, w=width);
+    r += &format!("{:w$} |\n", "", w=width);
+    for (n, prev) in lines.previous.iter() {
+        if *n < line_num {
+            r += &format!("{:>w$} | {}\n", n, trim_cr(&prev.text), w=width);
+        }
+    }
+    r += &format!("{:>w$} | {}\n", line_num, line.text, w=width);
+    // Indent the caret with the whitespace of the line so that tabs
+    // align, passing the malformed UTF-8 before the position
+    let mut indent = String::new();
+    let mut column = 1;
+    let mut malformed = line.malformed.iter().peekable();
+    for (i, ch) in line.text.chars().enumerate() {
+        match malformed.peek() {
+            Some((m, byte)) if *m == i => {
+                if column == char_ofs && *byte == pos.byte() {
+                    break;
+                }
+                malformed.next();
+            }
+            _ => {
+                if column >= char_ofs {
+                    break;
+                }
+                column += 1;
+            }
+        }
+        indent.push(if ch == '\t' {'\t'} else {' '});
+    }
+    while column < char_ofs {
+        indent.push(' ');
+        column += 1;
+    }
+    r += &format!("{:w$} | {}^\n", "", indent, w=width);
+    r
+}
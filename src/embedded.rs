@@ -0,0 +1,226 @@
+//a Imports
+use crate::{ByteSource, IoError, IoErrorKind};
+#[cfg(feature = "embedded-io-async")]
+use crate::{Char, Observer, Reader, Result};
+
+//a Error mapping
+//fi io_error
+/// Map an error from an embedded-io stream to an [IoError] of the
+/// equivalent [IoErrorKind]
+fn io_error<E:embedded_io::Error>(e:E) -> IoError {
+    use embedded_io::ErrorKind as K;
+    let kind = {
+        match e.kind() {
+            K::NotFound          => IoErrorKind::NotFound,
+            K::PermissionDenied  => IoErrorKind::PermissionDenied,
+            K::ConnectionRefused => IoErrorKind::ConnectionRefused,
+            K::ConnectionReset   => IoErrorKind::ConnectionReset,
+            K::ConnectionAborted => IoErrorKind::ConnectionAborted,
+            K::NotConnected      => IoErrorKind::NotConnected,
+            K::AddrInUse         => IoErrorKind::AddrInUse,
+            K::AddrNotAvailable  => IoErrorKind::AddrNotAvailable,
+            K::BrokenPipe        => IoErrorKind::BrokenPipe,
+            K::AlreadyExists     => IoErrorKind::AlreadyExists,
+            K::InvalidInput      => IoErrorKind::InvalidInput,
+            K::InvalidData       => IoErrorKind::InvalidData,
+            K::TimedOut          => IoErrorKind::TimedOut,
+            K::Interrupted       => IoErrorKind::Interrupted,
+            K::Unsupported       => IoErrorKind::Unsupported,
+            K::OutOfMemory       => IoErrorKind::OutOfMemory,
+            K::WriteZero         => IoErrorKind::WriteZero,
+            _                    => IoErrorKind::Other,
+        }
+    };
+    kind.into()
+}
+
+//a EmbeddedIo
+//tp EmbeddedIo
+/// An adapter that makes an [embedded_io::Read] stream (such as a
+/// serial port of a microcontroller HAL) a [ByteSource] for a
+/// [Reader](crate::Reader).
+///
+/// Errors from the stream are returned by the
+/// [Reader](crate::Reader) as an [Error::IoError](crate::Error::IoError)
+/// with the [IoErrorKind] mapped from the [embedded_io::ErrorKind];
+/// interrupted reads are retried, and a read that times out gives
+/// [NoData](crate::Char::NoData).
+///
+/// A serial port that returns zero bytes when it has no data should
+/// be read with a [Reader](crate::Reader) configured with
+/// [set_eof_on_no_data(false)](crate::Reader::set_eof_on_no_data),
+/// so that a zero-byte read gives [NoData](crate::Char::NoData)
+/// rather than [Eof](crate::Char::Eof).
+///
+/// Requires the `embedded-io` feature.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{Char, EmbeddedIo, Reader};
+///     let port : &[u8] = b"AT+OK\r\n";
+///     let mut reader = Reader::new(EmbeddedIo::new(port)).set_eof_on_no_data(false);
+///     let mut line = String::new();
+///     while let Char::Char(ch) = reader.next_char().unwrap() {
+///         line.push(ch);
+///     }
+///     assert_eq!(line, "AT+OK\r\n");
+/// ```
+#[derive(Debug)]
+pub struct EmbeddedIo<T> {
+    /// The embedded-io stream
+    inner : T,
+}
+
+//ip EmbeddedIo
+impl <T> EmbeddedIo<T> {
+    //fp new
+    /// Wrap an embedded-io stream
+    pub fn new(inner:T) -> Self {
+        Self { inner }
+    }
+
+    //mp borrow
+    /// Borrow the embedded-io stream
+    #[allow(clippy::should_implement_trait)]
+    pub fn borrow(&self) -> &T {
+        &self.inner
+    }
+
+    //mp borrow_mut
+    /// Borrow the embedded-io stream mutably
+    #[allow(clippy::should_implement_trait)]
+    pub fn borrow_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    //mp into_inner
+    /// Get the embedded-io stream back
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+//ip ByteSource for EmbeddedIo
+impl <T:embedded_io::Read> ByteSource for EmbeddedIo<T> {
+    fn read(&mut self, buf:&mut [u8]) -> core::result::Result<usize, IoError> {
+        self.inner.read(buf).map_err(io_error)
+    }
+}
+
+//a EmbeddedIoAsync
+//tp EmbeddedIoAsync
+/// An adapter that makes an [embedded_io_async::Read] stream the
+/// source of a [Reader](crate::Reader), which is then read with
+/// [next_char_async](Reader::next_char_async).
+///
+/// The synchronous [next_char](Reader::next_char) of such a
+/// [Reader](crate::Reader) only decodes the data that has already
+/// been read, returning [NoData](Char::NoData) when it needs more.
+///
+/// Requires the `embedded-io-async` feature.
+#[cfg(feature = "embedded-io-async")]
+#[derive(Debug)]
+pub struct EmbeddedIoAsync<T> {
+    /// The embedded-io-async stream
+    inner : T,
+}
+
+//ip EmbeddedIoAsync
+#[cfg(feature = "embedded-io-async")]
+impl <T> EmbeddedIoAsync<T> {
+    //fp new
+    /// Wrap an embedded-io-async stream
+    pub fn new(inner:T) -> Self {
+        Self { inner }
+    }
+
+    //mp borrow
+    /// Borrow the embedded-io-async stream
+    #[allow(clippy::should_implement_trait)]
+    pub fn borrow(&self) -> &T {
+        &self.inner
+    }
+
+    //mp borrow_mut
+    /// Borrow the embedded-io-async stream mutably
+    #[allow(clippy::should_implement_trait)]
+    pub fn borrow_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    //mp into_inner
+    /// Get the embedded-io-async stream back
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+//ip ByteSource for EmbeddedIoAsync
+#[cfg(feature = "embedded-io-async")]
+impl <T> ByteSource for EmbeddedIoAsync<T> {
+    /// A synchronous read of an asynchronous stream never has data
+    fn read(&mut self, _buf:&mut [u8]) -> core::result::Result<usize, IoError> {
+        Err(IoErrorKind::WouldBlock.into())
+    }
+}
+
+//ip Reader for EmbeddedIoAsync
+#[cfg(feature = "embedded-io-async")]
+impl <T:embedded_io_async::Read, O:Observer> Reader<EmbeddedIoAsync<T>, O> {
+    //mp next_char_async
+    /// Return the next character from the stream, awaiting a read of
+    /// the stream if more data is required, or [EOF](Char::Eof).
+    ///
+    /// A read of zero bytes is EOF unless the [Reader] has been
+    /// configured with
+    /// [set_eof_on_no_data(false)](Reader::set_eof_on_no_data), in
+    /// which case [NoData](Char::NoData) is returned.
+    ///
+    /// # Errors
+    ///
+    /// As for [next_char](Reader::next_char); errors from the stream
+    /// are mapped as for [EmbeddedIo].
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     # use utf8_read::{Char, EmbeddedIoAsync, Reader};
+    ///     async fn read_line(port:&[u8]) -> String {
+    ///         let mut reader = Reader::new(EmbeddedIoAsync::new(port));
+    ///         let mut line = String::new();
+    ///         while let Char::Char(ch) = reader.next_char_async().await.unwrap() {
+    ///             line.push(ch);
+    ///             if ch == '\n' { break; }
+    ///         }
+    ///         line
+    ///     }
+    /// ```
+    pub async fn next_char_async(&mut self) -> Result<Char> {
+        loop {
+            match self.next_char()? {
+                Char::NoData if !self.eof() => {
+                    let (stream, buffer) = self.buffer_to_fill();
+                    let n = {
+                        match stream.inner.read(buffer).await {
+                            Ok(n) => n,
+                            Err(e) => {
+                                let e = io_error(e);
+                                match e.kind() {
+                                    IoErrorKind::Interrupted => { continue; }
+                                    IoErrorKind::TimedOut    => { return Ok(Char::NoData); }
+                                    _ => { return Err(e.into()); }
+                                }
+                            }
+                        }
+                    };
+                    self.buffer_filled(n);
+                    if n == 0 && !self.eof() {
+                        return Ok(Char::NoData);
+                    }
+                }
+                c => { return Ok(c); }
+            }
+        }
+    }
+}
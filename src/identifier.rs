@@ -0,0 +1,218 @@
+//a Imports
+use alloc::string::String;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use unicode_script::{Script, ScriptExtension, UnicodeScript};
+use crate::reader::Peeked;
+use crate::{ByteSource, Char, Error, Observer, Reader, Result, StreamSpan};
+pub use unicode_ident::{is_xid_continue, is_xid_start};
+
+//a Pattern properties
+//fp is_pattern_white_space
+/// Returns true if the character has the Unicode `Pattern_White_Space`
+/// property, which (as this property is immutable) is exactly the
+/// characters that a syntax that follows UAX #31 treats as whitespace
+///
+/// Requires the `unicode-ident` feature.
+pub fn is_pattern_white_space(ch:char) -> bool {
+    matches!(ch, '\u{9}'..='\u{d}' | ' ' | '\u{85}' | '\u{200e}' | '\u{200f}' | '\u{2028}' | '\u{2029}')
+}
+
+//fp is_pattern_syntax
+/// Returns true if the character has the Unicode `Pattern_Syntax`
+/// property, which (as this property is immutable) is exactly the
+/// characters that a syntax that follows UAX #31 may use as operators
+/// and punctuation, now or in the future
+///
+/// Requires the `unicode-ident` feature.
+pub fn is_pattern_syntax(ch:char) -> bool {
+    matches!(ch,
+             '\u{21}'..='\u{2f}' | '\u{3a}'..='\u{40}' | '\u{5b}'..='\u{5e}' | '\u{60}' |
+             '\u{7b}'..='\u{7e}' | '\u{a1}'..='\u{a7}' | '\u{a9}' | '\u{ab}' | '\u{ac}' |
+             '\u{ae}' | '\u{b0}' | '\u{b1}' | '\u{b6}' | '\u{bb}' | '\u{bf}' | '\u{d7}' |
+             '\u{f7}' | '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{203e}' |
+             '\u{2041}'..='\u{2053}' | '\u{2055}'..='\u{205e}' | '\u{2190}'..='\u{245f}' |
+             '\u{2500}'..='\u{2775}' | '\u{2794}'..='\u{2bff}' | '\u{2e00}'..='\u{2e7f}' |
+             '\u{3001}'..='\u{3003}' | '\u{3008}'..='\u{3020}' | '\u{3030}' |
+             '\u{fd3e}' | '\u{fd3f}' | '\u{fe45}' | '\u{fe46}')
+}
+
+//a Script mixing
+//tp ScriptSet
+/// The augmented script set of a character, or the intersection of
+/// those of a string, as in UAX #39; the script extensions are
+/// augmented with the writing systems that combine Han with other
+/// scripts
+#[derive(Debug, Clone, Copy)]
+struct ScriptSet {
+    /// The scripts
+    scripts : ScriptExtension,
+    /// Japanese - Han, Hiragana and Katakana
+    jpan    : bool,
+    /// Korean - Han and Hangul
+    kore    : bool,
+    /// Han with Bopomofo
+    hanb    : bool,
+}
+
+//ip ScriptSet
+impl ScriptSet {
+    //fp of_char
+    /// Get the augmented script set of a character
+    fn of_char(ch:char) -> Self {
+        let scripts = ch.script_extension();
+        if scripts.is_common() || scripts.is_inherited() {
+            return Self { scripts, jpan:true, kore:true, hanb:true };
+        }
+        let han = scripts.contains_script(Script::Han);
+        Self {
+            scripts,
+            jpan : han || scripts.contains_script(Script::Hiragana) || scripts.contains_script(Script::Katakana),
+            kore : han || scripts.contains_script(Script::Hangul),
+            hanb : han || scripts.contains_script(Script::Bopomofo),
+        }
+    }
+
+    //mp intersection
+    /// Get the intersection with another set
+    fn intersection(self, other:Self) -> Self {
+        Self {
+            scripts : self.scripts.intersection(other.scripts),
+            jpan    : self.jpan && other.jpan,
+            kore    : self.kore && other.kore,
+            hanb    : self.hanb && other.hanb,
+        }
+    }
+
+    //mp is_empty
+    /// Returns true if the set is empty
+    fn is_empty(&self) -> bool {
+        self.scripts.is_empty() && !self.jpan && !self.kore && !self.hanb
+    }
+}
+
+//fi is_mixed_script
+/// Returns true if the text is not single-script under the UAX #39
+/// definition: characters that are Common or Inherited are ignored,
+/// and the scripts of the other characters (allowing for those that
+/// are used with more than one script) have no script in common
+fn is_mixed_script(text:&str) -> bool {
+    let mut chars = text.chars().map(ScriptSet::of_char);
+    match chars.next() {
+        None => false,
+        Some(first) => chars.fold(first, ScriptSet::intersection).is_empty(),
+    }
+}
+
+//a Identifier
+//tp Identifier
+/// An identifier read by [read_identifier](Reader::read_identifier)
+///
+/// Requires the `unicode-ident` feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identifier {
+    /// The text of the identifier, as it is in the stream
+    text         : String,
+    /// The span of the identifier in the stream
+    span         : StreamSpan,
+    /// The NFC form of the text, if it differs from the text
+    nfc          : Option<String>,
+    /// True if the identifier mixes scripts
+    mixed_script : bool,
+}
+
+//ip Identifier
+impl Identifier {
+    //mp text
+    /// Borrow the text of the identifier, as it is in the stream
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    //mp span
+    /// Get the [StreamSpan] of the identifier
+    pub fn span(&self) -> StreamSpan {
+        self.span
+    }
+
+    //mp nfc
+    /// Borrow the NFC-normalized form of the identifier, if this
+    /// differs from the text in the stream
+    pub fn nfc(&self) -> Option<&str> {
+        self.nfc.as_deref()
+    }
+
+    //mp normalized
+    /// Borrow the NFC-normalized form of the identifier, which should
+    /// be used to compare identifiers
+    pub fn normalized(&self) -> &str {
+        self.nfc.as_deref().unwrap_or(&self.text)
+    }
+
+    //mp is_mixed_script
+    /// Returns true if the identifier mixes scripts (such as a Latin
+    /// identifier with a Cyrillic letter), which UAX #39 recommends
+    /// should be rejected or flagged as possibly confusable
+    ///
+    /// Characters common to all scripts (such as digits and `_`) are
+    /// ignored, and Han may be mixed with Hiragana and Katakana (as
+    /// in Japanese), with Hangul (as in Korean) or with Bopomofo.
+    pub fn is_mixed_script(&self) -> bool {
+        self.mixed_script
+    }
+}
+
+//ip Reader - identifiers
+impl <R:ByteSource, O:Observer> Reader<R, O> {
+    //mp read_identifier
+    /// Read a Unicode identifier, as defined by UAX #31 and used by
+    /// Rust and Python - an `XID_Start` character or `_` followed by
+    /// `XID_Continue` characters; `Ok(None)` is returned (and nothing
+    /// consumed) if the stream has no data before the end of the
+    /// identifier, as for the [scanning methods](Reader::skip_while).
+    ///
+    /// The identifier is returned with its span, its NFC-normalized
+    /// form if that differs, and whether it mixes scripts; a client
+    /// that should reject mixed-script identifiers can do so with
+    /// [is_mixed_script](Identifier::is_mixed_script).
+    ///
+    /// Requires the `unicode-ident` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [Error::Expected] if the stream does not start with
+    /// an identifier, or a malformed UTF-8 or IO error found during
+    /// the scan
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::Reader;
+    ///     let mut reader = Reader::new("cafe\u{301} = \u{441}ount".as_bytes());
+    ///     let ident = reader.read_identifier().unwrap().unwrap();
+    ///     assert_eq!(ident.text(), "cafe\u{301}");
+    ///     assert_eq!(ident.nfc(), Some("caf\u{e9}"));
+    ///     assert!(!ident.is_mixed_script());
+    ///     reader.skip_while(|c| !c.is_alphabetic()).unwrap();
+    ///     let ident = reader.read_identifier().unwrap().unwrap();
+    ///     assert!(ident.is_mixed_script());
+    /// ```
+    pub fn read_identifier(&mut self) -> Result<Option<Identifier>> {
+        match self.peek_nth(0) {
+            Peeked::Char(_, ch) if ch == '_' || is_xid_start(ch) => (),
+            Peeked::Char(pos, ch) => { return Err(Error::Expected(pos, "identifier".into(), Char::Char(ch))); }
+            Peeked::Eof(pos) => { return Err(Error::Expected(pos, "identifier".into(), Char::Eof)); }
+            Peeked::NoData   => { return Ok(None); }
+            Peeked::Error(_) => { return Err(self.take_peeked_error()); }
+        }
+        let n = {
+            match self.scan_while(is_xid_continue)? {
+                None    => { return Ok(None); }
+                Some(n) => n,
+            }
+        };
+        let (text, span) = self.take(n);
+        let nfc = if is_nfc(&text) { None } else { Some(text.nfc().collect()) };
+        let mixed_script = is_mixed_script(&text);
+        Ok(Some(Identifier { text, span, nfc, mixed_script }))
+    }
+}
@@ -0,0 +1,246 @@
+//a Imports
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use crate::{ByteSource, Char, Error, Reader, Result, SourcePosition, StreamPosition};
+
+//a Constants
+/// Default value for the maximum depth of nested includes
+const DEFAULT_MAX_DEPTH : usize = 16;
+
+//a IncludeReader
+//tp IncludeReader
+/// The [IncludeReader] provides a stream of characters from a root
+/// source into which other sources may be included, as for an
+/// `include "other.cfg"` directive in a configuration language.
+///
+/// When the client reads an include directive it pushes the new
+/// source with [push_source](IncludeReader::push_source); characters
+/// are then read from that source until its end, after which reading
+/// continues with the parent source from the character after the
+/// directive. Each source is read with its own [Reader], so positions
+/// are within the source that a character comes from.
+///
+/// Errors are returned as [Error::InSource] for the source in which
+/// they occurred, wrapped in an [Error::IncludedFrom] for each parent
+/// source, so that they display as (for example) "in b.cfg: ... at
+/// line 4 char 1, included from a.cfg line 10 char 17".
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{Char, IncludeReader};
+///     let a : &[u8] = b"x<y";
+///     let b : &[u8] = b"inner";
+///     let mut reader = IncludeReader::new("a.cfg", a);
+///     let mut text = String::new();
+///     while let Char::Char(ch) = reader.next_char().unwrap() {
+///         if ch == '<' {
+///             reader.push_source("b.cfg", b).unwrap();
+///         } else {
+///             text.push(ch);
+///         }
+///     }
+///     assert_eq!(text, "xinnery");
+/// ```
+pub struct IncludeReader<R:ByteSource> {
+    /// `eof_on_no_data` is used for the [Reader] of every source
+    eof_on_no_data : bool,
+    /// Maximum number of sources that may be included within each
+    /// other below the root source
+    max_depth      : usize,
+    /// Stack of sources, with the root source first; this is never empty
+    stack          : Vec<(Arc<str>, Reader<R>)>,
+    /// Position within the top source of the character most recently
+    /// returned
+    char_pos       : StreamPosition,
+}
+
+//ip IncludeReader
+impl <R:ByteSource> IncludeReader<R> {
+    //fp new
+    /// Returns a new [IncludeReader] for a named root source, with
+    /// the default maximum include depth of 16
+    pub fn new(name:&str, stream:R) -> Self {
+        let mut r = Self {
+            eof_on_no_data : true,
+            max_depth      : DEFAULT_MAX_DEPTH,
+            stack          : Vec::new(),
+            char_pos       : StreamPosition::new(),
+        };
+        r.stack.push((name.into(), r.new_reader(name, stream)));
+        r
+    }
+
+    //cp set_eof_on_no_data
+    /// Build pattern function to set the `eof_on_no_data` for the
+    /// [Reader] of every source; see [Reader::set_eof_on_no_data]
+    pub fn set_eof_on_no_data(mut self, eof_on_no_data:bool) -> Self {
+        self.eof_on_no_data = eof_on_no_data;
+        self.stack = self.stack
+            .drain(..)
+            .map(|(name, reader)| (name, reader.set_eof_on_no_data(eof_on_no_data)))
+            .collect();
+        self
+    }
+
+    //cp set_max_depth
+    /// Build pattern function to set the maximum depth of nested
+    /// includes; a depth of 0 prevents any source being included
+    pub fn set_max_depth(mut self, max_depth:usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    //mi new_reader
+    /// Create a [Reader] for a source
+    fn new_reader(&self, name:&str, stream:R) -> Reader<R> {
+        Reader::new(stream)
+            .set_eof_on_no_data(self.eof_on_no_data)
+            .set_source_name(name)
+    }
+
+    //mp push_source
+    /// Include a named source at the current position; the next
+    /// character will be the first character of the new source
+    ///
+    /// # Errors
+    ///
+    /// Returns [Error::IncludeDepth] (wrapped as for other errors) if
+    /// the maximum include depth would be exceeded; the new source is
+    /// not included.
+    pub fn push_source(&mut self, name:&str, stream:R) -> Result<()> {
+        if self.depth() >= self.max_depth {
+            let pos = *self.top().borrow_pos();
+            return Err(self.wrap_error(Error::IncludeDepth(pos, self.max_depth)));
+        }
+        let reader = self.new_reader(name, stream);
+        self.stack.push((name.into(), reader));
+        Ok(())
+    }
+
+    //mp depth
+    /// Get the current include depth; this is 0 when reading the root source
+    pub fn depth(&self) -> usize {
+        self.stack.len() - 1
+    }
+
+    //mp set_eof
+    /// Set the eof indicator of the source currently being read; see
+    /// [Reader::set_eof]
+    pub fn set_eof(&mut self, eof:bool) {
+        self.top_mut().set_eof(eof);
+    }
+
+    //mp source_position
+    /// Get the [SourcePosition] of the next character in the source
+    /// currently being read
+    ///
+    /// The end of an included source is only detected when a
+    /// character is requested, so this may be the position after the
+    /// end of an included source.
+    pub fn source_position(&self) -> SourcePosition {
+        let (name, reader) = self.stack.last().unwrap();
+        SourcePosition::new(name.clone(), *reader.borrow_pos())
+    }
+
+    //mp char_position
+    /// Get the [SourcePosition] of the character most recently
+    /// returned by [next_char](IncludeReader::next_char)
+    pub fn char_position(&self) -> SourcePosition {
+        let (name, _) = self.stack.last().unwrap();
+        SourcePosition::new(name.clone(), self.char_pos)
+    }
+
+    //mp include_chain
+    /// Get the chain of positions from the next character in the
+    /// source currently being read, followed by the position in each
+    /// parent source after the directive that included its child,
+    /// ending with the root source
+    pub fn include_chain(&self) -> Vec<SourcePosition> {
+        self.stack
+            .iter()
+            .rev()
+            .map(|(name, reader)| SourcePosition::new(name.clone(), *reader.borrow_pos()))
+            .collect()
+    }
+
+    //mp borrow_reader
+    /// Borrow the [Reader] of the source currently being read
+    pub fn borrow_reader(&self) -> &Reader<R> {
+        self.top()
+    }
+
+    //mp borrow_reader_mut
+    /// Mutably borrow the [Reader] of the source currently being read
+    pub fn borrow_reader_mut(&mut self) -> &mut Reader<R> {
+        self.top_mut()
+    }
+
+    //mi top
+    fn top(&self) -> &Reader<R> {
+        &self.stack.last().unwrap().1
+    }
+
+    //mi top_mut
+    fn top_mut(&mut self) -> &mut Reader<R> {
+        &mut self.stack.last_mut().unwrap().1
+    }
+
+    //mi wrap_error
+    /// Wrap an error from the source currently being read with the
+    /// name of that source and the positions of its includes
+    fn wrap_error(&self, e:Error) -> Error {
+        let mut sources = self.stack.iter().rev();
+        let (name, _) = sources.next().unwrap();
+        let mut e = Error::InSource(name.clone(), Box::new(e));
+        for (name, reader) in sources {
+            let pos = SourcePosition::new(name.clone(), *reader.borrow_pos());
+            e = Error::IncludedFrom(pos, Box::new(e));
+        }
+        e
+    }
+
+    //mp next_char
+    /// Return the next character from the source currently being
+    /// read; at the end of an included source reading continues with
+    /// its parent. [EOF](Char::Eof) is returned at the end of the root
+    /// source.
+    ///
+    /// # Errors
+    ///
+    /// Any error from the [Reader] of a source is returned wrapped in
+    /// an [Error::InSource] with the name of the source, and then an
+    /// [Error::IncludedFrom] for each parent source.
+    pub fn next_char(&mut self) -> Result<Char> {
+        loop {
+            self.char_pos = *self.top().borrow_pos();
+            match self.top_mut().next_char() {
+                Ok(Char::Eof) if self.stack.len() > 1 => {
+                    self.stack.pop();
+                }
+                Ok(c) => { return Ok(c); }
+                Err(e) => { return Err(self.wrap_error(e)); }
+            }
+        }
+    }
+
+    //zz All done
+}
+
+//ip Iterator for IncludeReader - iterate over characters
+#[allow(missing_doc_code_examples)]
+impl <R:ByteSource> Iterator for &mut IncludeReader<R> {
+    type Item = Result<char>;
+
+    //mp next - return next character or None if end of the root source
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Ok(Char::Char(ch)) => Some(Ok(ch)),
+            Ok(_)              => None,
+            Err(x)             => Some(Err(x)),
+        }
+    }
+
+    //zz All done
+}
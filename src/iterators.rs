@@ -0,0 +1,76 @@
+//a Imports
+use crate::{ByteSource, Char, NoObserver, Observer, Reader, Result, StreamPosition};
+
+//a Positions
+//tp Positions
+/// An iterator over the characters of a [Reader] with their
+/// [StreamPosition]s, in the manner of [str::char_indices]
+///
+/// This is returned by the [positions](Reader::positions) method. As
+/// with iterating over the [Reader] itself, the iterator ends when the
+/// [Reader] returns [Eof](Char::Eof) or [NoData](Char::NoData).
+pub struct Positions<'a, R:ByteSource, O:Observer = NoObserver> {
+    /// The reader being iterated over
+    reader : &'a mut Reader<R, O>,
+}
+
+//ip Positions
+impl <'a, R:ByteSource, O:Observer> Positions<'a, R, O> {
+    //fp new
+    /// Create a new [Positions] iterator
+    pub(crate) fn new(reader:&'a mut Reader<R, O>) -> Self {
+        Self { reader }
+    }
+}
+
+//ip Iterator for Positions
+impl <R:ByteSource, O:Observer> Iterator for Positions<'_, R, O> {
+    type Item = Result<(StreamPosition, char)>;
+
+    //mp next - return next character and its position, or None
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = *self.reader.borrow_pos();
+        match self.reader.next_char() {
+            Ok(Char::Char(ch)) => Some(Ok((pos, ch))),
+            Ok(_)              => None,
+            Err(x)             => Some(Err(x)),
+        }
+    }
+}
+
+//a CharsRaw
+//tp CharsRaw
+/// An iterator over the results of [next_char](Reader::next_char) on
+/// a [Reader], which ends after [Eof](Char::Eof) is returned
+///
+/// This is returned by the [chars_raw](Reader::chars_raw) method.
+/// Unlike iterating over the [Reader] itself,
+/// [NoData](Char::NoData) is yielded to the caller and does not end
+/// the iteration; the caller should wait for its stream to have more
+/// data before continuing to iterate.
+pub struct CharsRaw<'a, R:ByteSource, O:Observer = NoObserver> {
+    /// The reader being iterated over
+    reader : &'a mut Reader<R, O>,
+}
+
+//ip CharsRaw
+impl <'a, R:ByteSource, O:Observer> CharsRaw<'a, R, O> {
+    //fp new
+    /// Create a new [CharsRaw] iterator
+    pub(crate) fn new(reader:&'a mut Reader<R, O>) -> Self {
+        Self { reader }
+    }
+}
+
+//ip Iterator for CharsRaw
+impl <R:ByteSource, O:Observer> Iterator for CharsRaw<'_, R, O> {
+    type Item = Result<Char>;
+
+    //mp next - return next result, or None once EOF has been reached
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.next_char() {
+            Ok(Char::Eof) => None,
+            x             => Some(x),
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//a Imports
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use aho_corasick::automaton::{Automaton, StateID};
+use aho_corasick::dfa::DFA;
+use aho_corasick::{Anchored, BuildError, PatternID};
+use crate::{ByteSource, Char, NoObserver, Observer, Reader, Result, StreamPosition, StreamSpan};
+
+//a Case folding
+//fp simple_fold
+/// Fold a character for case-insensitive matching, such that two
+/// characters fold to the same character if and only if they are
+/// equal under Unicode simple case folding
+///
+/// This is the single-character lowercase mapping of the character,
+/// except for the characters (such as final sigma and the long s)
+/// that are already lowercase but that fold to another lowercase
+/// character, including the Greek letters with oxia and the long s t
+/// ligature that fold to their tonos and st equivalents.
+fn simple_fold(ch:char) -> char {
+    match ch {
+        '\u{b5}'   => '\u{3bc}', // micro sign to mu
+        '\u{17f}'  => 's',       // long s
+        '\u{345}'  => '\u{3b9}', // combining ypogegrammeni to iota
+        '\u{3c2}'  => '\u{3c3}', // final sigma
+        '\u{3d0}'  => '\u{3b2}', // beta symbol
+        '\u{3d1}'  => '\u{3b8}', // theta symbol
+        '\u{3d5}'  => '\u{3c6}', // phi symbol
+        '\u{3d6}'  => '\u{3c0}', // pi symbol
+        '\u{3f0}'  => '\u{3ba}', // kappa symbol
+        '\u{3f1}'  => '\u{3c1}', // rho symbol
+        '\u{3f5}'  => '\u{3b5}', // lunate epsilon symbol
+        '\u{1c80}' => '\u{432}', // Cyrillic small letters with variant forms
+        '\u{1c81}' => '\u{434}',
+        '\u{1c82}' => '\u{43e}',
+        '\u{1c83}' => '\u{441}',
+        '\u{1c84}' | '\u{1c85}' => '\u{442}',
+        '\u{1c86}' => '\u{44a}',
+        '\u{1c87}' => '\u{463}',
+        '\u{1c88}' => '\u{a64b}',
+        '\u{1e9b}' => '\u{1e61}', // long s with dot above
+        '\u{1fbe}' => '\u{3b9}', // Greek prosgegrammeni to iota
+        '\u{1fd3}' => '\u{390}', // iota with dialytika and oxia to tonos
+        '\u{1fe3}' => '\u{3b0}', // upsilon with dialytika and oxia to tonos
+        '\u{fb05}' => '\u{fb06}', // ligature long s t to st
+        _ => {
+            let mut lower = ch.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) => l,
+                _ => ch,
+            }
+        }
+    }
+}
+
+//a KeywordSet
+//tp KeywordSet
+/// A set of literal keywords compiled to an Aho-Corasick automaton
+/// (from the `aho-corasick` crate), to find all of their occurrences
+/// in the characters read from a [Reader]
+///
+/// The keywords are identified by their index in the list that the
+/// set is built from. A case-insensitive set matches characters that
+/// are equal under Unicode simple case folding (so, for example,
+/// `"STRASSE"` does not match `"straße"`, but the Kelvin sign matches
+/// `k`).
+///
+/// An empty keyword never matches.
+///
+/// Requires the `aho-corasick` feature.
+#[derive(Debug, Clone)]
+pub struct KeywordSet {
+    /// The automaton, built from the (folded, if case-insensitive)
+    /// keywords
+    dfa              : DFA,
+    /// True if characters are folded before matching
+    case_insensitive : bool,
+}
+
+//ip KeywordSet
+impl KeywordSet {
+    //fp new
+    /// Build a [KeywordSet] that matches the keywords exactly
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `aho-corasick` if the automaton would
+    /// be too large
+    pub fn new<I, P>(keywords:I) -> core::result::Result<Self, BuildError>
+    where I:IntoIterator<Item = P>, P:AsRef<str> {
+        let dfa = DFA::new(keywords.into_iter().map(|k| String::from(k.as_ref())))?;
+        Ok(Self { dfa, case_insensitive:false })
+    }
+
+    //fp case_insensitive
+    /// Build a [KeywordSet] that matches the keywords under Unicode
+    /// simple case folding
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `aho-corasick` if the automaton would
+    /// be too large
+    pub fn case_insensitive<I, P>(keywords:I) -> core::result::Result<Self, BuildError>
+    where I:IntoIterator<Item = P>, P:AsRef<str> {
+        let folded = keywords.into_iter().map(|k| k.as_ref().chars().map(simple_fold).collect::<String>());
+        let dfa = DFA::new(folded)?;
+        Ok(Self { dfa, case_insensitive:true })
+    }
+
+    //mp len
+    /// Get the number of keywords in the set
+    pub fn len(&self) -> usize {
+        self.dfa.patterns_len()
+    }
+
+    //mp is_empty
+    /// Returns true if the set has no keywords
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    //mi start
+    /// Get the start state of the automaton
+    fn start(&self) -> StateID {
+        // The DFA is built to support unanchored searches
+        self.dfa.start_state(Anchored::No).unwrap()
+    }
+
+    //zz All done
+}
+
+//a KeywordMatcher
+//tp KeywordMatcher
+/// The state of a search for the keywords of a [KeywordSet] in the
+/// characters read from a [Reader]
+///
+/// Every occurrence of every keyword is found, including those that
+/// overlap, in order of where they end; occurrences that end at the
+/// same character are found longest first. Each is returned with the
+/// index of its keyword and the [StreamSpan] that it covers.
+///
+/// The characters are fed to the automaton as they are read, so an
+/// occurrence may straddle any number of refills of the [Reader]'s
+/// buffer; the [KeywordMatcher] holds only the automaton state and
+/// the positions of as many characters as are in the longest keyword.
+///
+/// If the [Reader] returns [NoData](Char::NoData) then
+/// [next_match](KeywordMatcher::next_match) returns `Ok(None)`, and
+/// the search may be continued when the stream has more data; an
+/// occurrence that spans the stall is still found.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{KeywordMatcher, KeywordSet, Reader};
+///     let keywords = KeywordSet::case_insensitive(["error", "warn"]).unwrap();
+///     let mut reader = Reader::new("ok\nWARN: fan\nERROR: disk\n".as_bytes());
+///     let mut matcher = KeywordMatcher::new(&keywords);
+///     let (id, span) = matcher.next_match(&mut reader).unwrap().unwrap();
+///     assert_eq!((id, span.start().line_position()), (1, (2, 1)));
+///     let (id, span) = matcher.next_match(&mut reader).unwrap().unwrap();
+///     assert_eq!((id, span.start().line_position()), (0, (3, 1)));
+///     assert!(matcher.next_match(&mut reader).unwrap().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeywordMatcher<'a> {
+    /// The keywords being searched for
+    keywords  : &'a KeywordSet,
+    /// State of the automaton after the characters read
+    state     : StateID,
+    /// Number of bytes fed to the automaton since it was started
+    fed       : usize,
+    /// Offsets in the bytes fed, and stream positions, of the most
+    /// recent characters, as far back as the longest keyword
+    positions : VecDeque<(usize, StreamPosition)>,
+    /// Occurrences found but not yet returned
+    pending   : VecDeque<(usize, StreamSpan)>,
+}
+
+//ip KeywordMatcher
+impl <'a> KeywordMatcher<'a> {
+    //fp new
+    /// Create a new [KeywordMatcher] to find the keywords of `keywords`
+    pub fn new(keywords:&'a KeywordSet) -> Self {
+        Self {
+            keywords,
+            state     : keywords.start(),
+            fed       : 0,
+            positions : VecDeque::new(),
+            pending   : VecDeque::new(),
+        }
+    }
+
+    //mp next_match
+    /// Find the next occurrence of a keyword in the stream, returning
+    /// the index of the keyword and the [StreamSpan] that it covers;
+    /// `Ok(None)` is returned if the [Reader] returns
+    /// [NoData](Char::NoData) or [Eof](Char::Eof) first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a malformed UTF-8 or IO error from the [Reader]; no
+    /// occurrence spans the malformed bytes, and the search may be
+    /// continued after the error.
+    pub fn next_match<R:ByteSource, O:Observer>(&mut self, reader:&mut Reader<R, O>) -> Result<Option<(usize, StreamSpan)>> {
+        loop {
+            if let Some(m) = self.pending.pop_front() {
+                return Ok(Some(m));
+            }
+            let pos = *reader.borrow_pos();
+            let ch = {
+                match reader.next_char() {
+                    Ok(Char::Char(ch)) => ch,
+                    Ok(_) => { return Ok(None); }
+                    Err(e) => {
+                        self.state = self.keywords.start();
+                        self.positions.clear();
+                        return Err(e);
+                    }
+                }
+            };
+            let ch = if self.keywords.case_insensitive { simple_fold(ch) } else { ch };
+            self.positions.push_back((self.fed, pos));
+            let mut utf8 = [0; 4];
+            for b in ch.encode_utf8(&mut utf8).bytes() {
+                self.state = self.keywords.dfa.next_state(Anchored::No, self.state, b);
+            }
+            self.fed += ch.len_utf8();
+            let max_len = self.keywords.dfa.max_pattern_len();
+            while self.positions.front().is_some_and(|(ofs, _)| ofs + max_len < self.fed) {
+                self.positions.pop_front();
+            }
+            if self.keywords.dfa.is_match(self.state) {
+                self.found(*reader.borrow_pos());
+            }
+        }
+    }
+
+    //mi found
+    /// Add the occurrences that end at the character just read, which
+    /// ends at `end`, to the pending occurrences
+    fn found(&mut self, end:StreamPosition) {
+        let dfa = &self.keywords.dfa;
+        let mut found : Vec<(usize, usize)> = (0..dfa.match_len(self.state))
+            .map(|i| dfa.match_pattern(self.state, i))
+            .map(|pid:PatternID| (dfa.pattern_len(pid), pid.as_usize()))
+            .filter(|(len, _)| *len > 0)
+            .collect();
+        found.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for (len, id) in found {
+            let start_ofs = self.fed - len;
+            let start = self.positions
+                .iter()
+                .find(|(ofs, _)| *ofs == start_ofs)
+                .map(|(_, pos)| *pos);
+            // Keywords are whole characters, so the start is always held
+            if let Some(start) = start {
+                self.pending.push_back((id, StreamSpan::new(start, end)));
+            }
+        }
+    }
+
+    //zz All done
+}
+
+//a Keywords
+//tp Keywords
+/// An iterator over the occurrences of the keywords of a [KeywordSet]
+/// in a [Reader], yielding the index of the keyword and the
+/// [StreamSpan] of each occurrence
+///
+/// This is returned by the [find_keywords](Reader::find_keywords)
+/// method. As with iterating over the [Reader] itself, the iterator
+/// ends when the [Reader] returns [Eof](Char::Eof) or
+/// [NoData](Char::NoData); to continue a search after the stream
+/// stalls, use a [KeywordMatcher] directly.
+pub struct Keywords<'a, 'k, R:ByteSource, O:Observer = NoObserver> {
+    /// The reader being searched
+    reader  : &'a mut Reader<R, O>,
+    /// The state of the search
+    matcher : KeywordMatcher<'k>,
+}
+
+//ip Iterator for Keywords
+impl <R:ByteSource, O:Observer> Iterator for Keywords<'_, '_, R, O> {
+    type Item = Result<(usize, StreamSpan)>;
+
+    //mp next - return the next occurrence, or None
+    fn next(&mut self) -> Option<Self::Item> {
+        self.matcher.next_match(self.reader).transpose()
+    }
+}
+
+//ip Reader - keyword search
+impl <R:ByteSource, O:Observer> Reader<R, O> {
+    //mp find_keywords
+    /// Iterate over the occurrences of the keywords of `keywords` in
+    /// the characters of the stream, yielding the index of the
+    /// keyword and the [StreamSpan] of each occurrence, until the
+    /// [Reader] returns [Eof](Char::Eof) or [NoData](Char::NoData)
+    ///
+    /// See [KeywordMatcher] for details, and for searching a stream
+    /// that may stall.
+    ///
+    /// Requires the `aho-corasick` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{KeywordSet, Reader};
+    ///     let keywords = KeywordSet::new(["he", "she", "hers"]).unwrap();
+    ///     let mut reader = Reader::new("ushers".as_bytes());
+    ///     let found : Vec<_> = reader.find_keywords(&keywords)
+    ///         .map(|m| m.unwrap())
+    ///         .map(|(id, span)| (id, span.start().byte(), span.end().byte()))
+    ///         .collect();
+    ///     assert_eq!(found, [(1, 1, 4), (0, 2, 4), (2, 2, 6)]);
+    /// ```
+    pub fn find_keywords<'a, 'k>(&'a mut self, keywords:&'k KeywordSet) -> Keywords<'a, 'k, R, O> {
+        Keywords { reader:self, matcher:KeywordMatcher::new(keywords) }
+    }
+}
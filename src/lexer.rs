@@ -0,0 +1,599 @@
+//a Imports
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::reader::Peeked;
+use crate::{ByteSource, Char, Error, Observer, Reader, Result, StreamPosition, StreamSpan};
+
+//a Rules
+//tp Escapes
+/// The escape rules of a quoted string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escapes {
+    /// No escapes; the string ends at the first closing quote
+    None,
+    /// C-style escapes with a backslash: `\n`, `\r`, `\t`, `\0`,
+    /// `\\`, `\'`, `\"` and `\u{...}` with one to six hexadecimal
+    /// digits
+    Backslash,
+    /// A doubled quote is a quote within the string, as in SQL
+    DoubledQuote,
+}
+
+//tp StringRules
+/// The rules for a quoted string [Recognizer]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringRules {
+    /// The character that opens and closes the string
+    quote     : char,
+    /// The escape rules within the string
+    escapes   : Escapes,
+    /// True if the string may contain newlines
+    multiline : bool,
+}
+
+//ip StringRules
+impl StringRules {
+    //fp new
+    /// Create rules for a string quoted with `quote`, which may not
+    /// contain newlines
+    pub fn new(quote:char, escapes:Escapes) -> Self {
+        Self { quote, escapes, multiline:false }
+    }
+
+    //cp set_multiline
+    /// Build pattern function to allow newlines within the string
+    pub fn set_multiline(mut self, multiline:bool) -> Self {
+        self.multiline = multiline;
+        self
+    }
+}
+
+//a Token
+//tp Token
+/// A token recognized by a [Recognizer]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// A run of whitespace
+    Whitespace,
+    /// A decimal integer
+    Decimal(u64),
+    /// A hexadecimal integer, written with a `0x` or `0X` prefix
+    Hex(u64),
+    /// A decimal floating point number, with a fraction or an
+    /// exponent (or both)
+    Float(f64),
+    /// An identifier
+    Identifier(String),
+    /// A quoted string, with its escapes decoded
+    Str(String),
+    /// A line comment, without its introducer or the newline
+    LineComment(String),
+    /// A block comment, without its delimiters
+    BlockComment(String),
+}
+
+//tp Lexed
+/// The result of recognizing a token
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lexed {
+    /// A token, with the [StreamSpan] of its text
+    Token(Token, StreamSpan),
+    /// The stream has no data before the token is complete
+    NoData,
+    /// The stream is at its end
+    Eof,
+}
+
+//a Scanning
+//tp Stop
+/// The reason that a scan for a token stopped without a result
+pub(crate) enum Stop {
+    /// The stream has no data; nothing is consumed
+    NoData,
+    /// The token is invalid; the given number of characters are
+    /// consumed, and the error returned
+    Invalid(usize, Error),
+    /// Malformed UTF-8 or an IO error at the front of the stream;
+    /// nothing is consumed
+    Failed(Error),
+}
+
+//tp Scanned
+/// The result of a scan for a token: the number of characters and the
+/// token, if it is recognized
+type Scanned = core::result::Result<Option<(usize, Token)>, Stop>;
+
+//fp peek
+/// Peek at character `n` ahead in the stream, returning its position
+/// and the character (or `None` at EOF)
+///
+/// An error at the front of the stream stops the scan; an error
+/// after `n` > 0 characters is held by the [Reader] until the
+/// characters before it have been consumed, and until then it ends
+/// the characters that may be peeked (with `None`, as EOF does; see
+/// [error_at]).
+pub(crate) fn peek<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, n:usize) -> core::result::Result<(StreamPosition, Option<char>), Stop> {
+    match reader.peek_nth(n) {
+        Peeked::Char(pos, ch) => Ok((pos, Some(ch))),
+        Peeked::Eof(pos)      => Ok((pos, None)),
+        Peeked::NoData        => Err(Stop::NoData),
+        Peeked::Error(_) if n == 0 => Err(Stop::Failed(reader.take_peeked_error())),
+        Peeked::Error(pos)    => Ok((pos, None)),
+    }
+}
+
+//fp error_at
+/// Describe the error at `n` ahead in the stream, if it has been
+/// peeked as `None` because the stream cannot be decoded there
+/// (rather than at EOF)
+pub(crate) fn error_at<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, n:usize) -> Option<&'static str> {
+    match reader.peek_nth(n) {
+        Peeked::Error(_) => {
+            match reader.borrow_peeked_error() {
+                Some(Error::MalformedUtf8(..)) => Some("malformed UTF-8"),
+                _ => Some("a stream error"),
+            }
+        }
+        _ => None,
+    }
+}
+
+//fp found
+/// Convert a peeked character to the [Char] found, for an
+/// [Error::Expected]
+pub(crate) fn found(ch:Option<char>) -> Char {
+    ch.map_or(Char::Eof, Char::Char)
+}
+
+//fp expected
+/// Get the error for a token that requires `what` at `n` ahead in
+/// the stream (at `pos`), where `ch` has been peeked: an
+/// [Error::Expected], or an [Error::InvalidToken] if the stream
+/// cannot be decoded there
+pub(crate) fn expected<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, n:usize, pos:StreamPosition, what:&str, ch:Option<char>) -> Error {
+    match error_at(reader, n) {
+        Some(error) => Error::InvalidToken(pos, format!("expected {}, found {}", what, error)),
+        None => Error::Expected(pos, what.into(), found(ch)),
+    }
+}
+
+//fp collect_while
+/// Collect the characters from `n` ahead in the stream that satisfy
+/// `pred`, returning the index after them and their text
+pub(crate) fn collect_while<R:ByteSource, O:Observer, F:Fn(char) -> bool>(reader:&mut Reader<R, O>, mut n:usize, pred:F) -> core::result::Result<(usize, String), Stop> {
+    let mut text = String::new();
+    while let (_, Some(ch)) = peek(reader, n)? {
+        if !pred(ch) {
+            break;
+        }
+        text.push(ch);
+        n += 1;
+    }
+    Ok((n, text))
+}
+
+//fi matches_at
+/// Returns true if the characters from `n` ahead in the stream are
+/// those of `s`
+fn matches_at<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, n:usize, s:&str) -> core::result::Result<bool, Stop> {
+    for (i, want) in s.chars().enumerate() {
+        if peek(reader, n+i)?.1 != Some(want) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+//a Recognizer
+//tp Recognizer
+/// A recognizer for one lexical form, which may be used alone with
+/// [recognize](Recognizer::recognize) or in the table of a [Lexer]
+///
+/// Recognizers peek ahead in the stream, and only consume the
+/// characters of a token once it is complete; if the stream has no
+/// data first then [NoData](Lexed::NoData) is returned, and the
+/// recognizer may be run again when the stream has more data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recognizer {
+    /// A run of whitespace, giving [Token::Whitespace]
+    Whitespace,
+    /// Decimal digits, giving [Token::Decimal]
+    Decimal,
+    /// `0x` or `0X` and hexadecimal digits, giving [Token::Hex]
+    Hex,
+    /// Decimal digits with a fraction (a `.` and digits) or an
+    /// exponent (`e` or `E`, an optional sign, and digits) or both,
+    /// giving [Token::Float]
+    Float,
+    /// An alphabetic character or `_` followed by alphanumeric
+    /// characters and `_`, giving [Token::Identifier]
+    Identifier,
+    /// A quoted string, giving [Token::Str]
+    Str(StringRules),
+    /// A comment from the introducer to the end of the line, giving
+    /// [Token::LineComment]
+    LineComment(String),
+    /// A comment between an opening and a closing delimiter (which do
+    /// not nest), giving [Token::BlockComment]
+    BlockComment(String, String),
+}
+
+//ip Recognizer
+impl Recognizer {
+    //mp recognize
+    /// Recognize a token at the front of the stream, consuming it if
+    /// it is complete; `Ok(None)` is returned (and nothing consumed)
+    /// if the stream does not start with this form of token.
+    ///
+    /// # Errors
+    ///
+    /// Returns [Error::Unterminated] (at the start of the token) for
+    /// an unterminated string or block comment, and
+    /// [Error::InvalidToken] or [Error::Expected] (at the fault) for
+    /// an invalid escape, a missing hexadecimal digit or a number
+    /// that is too large. The characters of the token are consumed,
+    /// so that lexing may continue: up to the fault, except that a
+    /// string with an invalid escape is consumed to its closing quote
+    /// (and the first invalid escape is reported).
+    ///
+    /// Malformed UTF-8 (or an IO error) ends a token that may end
+    /// there, such as an identifier; within a string, escape or block
+    /// comment, or where a digit is required, [Error::InvalidToken] is
+    /// returned at its position, with the characters before it
+    /// consumed. The malformed UTF-8 error itself is returned next; at
+    /// the front of the stream it is returned without consuming
+    /// anything.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Escapes, Error, Lexed, Reader, Recognizer, StringRules, Token};
+    ///     let string = Recognizer::Str(StringRules::new('"', Escapes::Backslash));
+    ///     let mut reader = Reader::new(r#""a\tb" "oops"#.as_bytes());
+    ///     let lexed = string.recognize(&mut reader).unwrap().unwrap();
+    ///     assert!(matches!(lexed, Lexed::Token(Token::Str(s), _) if s == "a\tb"));
+    ///     assert!(string.recognize(&mut reader).unwrap().is_none());
+    ///     Recognizer::Whitespace.recognize(&mut reader).unwrap();
+    ///     match string.recognize(&mut reader) {
+    ///         Err(Error::Unterminated(pos, _)) => { assert_eq!(pos.byte(), 7); }
+    ///         x => { panic!("unexpected {:?}", x); }
+    ///     }
+    /// ```
+    pub fn recognize<R:ByteSource, O:Observer>(&self, reader:&mut Reader<R, O>) -> Result<Option<Lexed>> {
+        match self.scan(reader) {
+            Ok(None) => Ok(None),
+            Ok(Some((n, token))) => {
+                let (_, span) = reader.take(n);
+                Ok(Some(Lexed::Token(token, span)))
+            }
+            Err(Stop::NoData) => Ok(Some(Lexed::NoData)),
+            Err(Stop::Invalid(n, e)) => {
+                reader.take(n);
+                Err(e)
+            }
+            Err(Stop::Failed(e)) => Err(e),
+        }
+    }
+
+    //mi scan
+    /// Scan for the token at the front of the stream
+    fn scan<R:ByteSource, O:Observer>(&self, reader:&mut Reader<R, O>) -> Scanned {
+        match self {
+            Self::Whitespace => {
+                let (n, _) = collect_while(reader, 0, char::is_whitespace)?;
+                Ok((n > 0).then_some((n, Token::Whitespace)))
+            }
+            Self::Decimal            => scan_decimal(reader),
+            Self::Hex                => scan_hex(reader),
+            Self::Float              => scan_float(reader),
+            Self::Identifier         => scan_identifier(reader),
+            Self::Str(rules)         => scan_string(reader, rules),
+            Self::LineComment(intro) => {
+                if !matches_at(reader, 0, intro)? {
+                    return Ok(None);
+                }
+                let (n, text) = collect_while(reader, intro.chars().count(), |c| c != '\n')?;
+                Ok(Some((n, Token::LineComment(text))))
+            }
+            Self::BlockComment(open, close) => scan_block_comment(reader, open, close),
+        }
+    }
+}
+
+//fi scan_decimal
+/// Scan for a decimal integer
+fn scan_decimal<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>) -> Scanned {
+    let (n, digits) = collect_while(reader, 0, |c| c.is_ascii_digit())?;
+    if n == 0 {
+        return Ok(None);
+    }
+    match digits.parse() {
+        Ok(value) => Ok(Some((n, Token::Decimal(value)))),
+        Err(_) => {
+            let pos = peek(reader, 0)?.0;
+            Err(Stop::Invalid(n, Error::InvalidToken(pos, "decimal number too large".into())))
+        }
+    }
+}
+
+//fi scan_hex
+/// Scan for a hexadecimal integer
+fn scan_hex<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>) -> Scanned {
+    if !(matches_at(reader, 0, "0x")? || matches_at(reader, 0, "0X")?) {
+        return Ok(None);
+    }
+    let (n, digits) = collect_while(reader, 2, |c| c.is_ascii_hexdigit())?;
+    if n == 2 {
+        let (pos, ch) = peek(reader, 2)?;
+        return Err(Stop::Invalid(2, expected(reader, 2, pos, "hexadecimal digit", ch)));
+    }
+    match u64::from_str_radix(&digits, 16) {
+        Ok(value) => Ok(Some((n, Token::Hex(value)))),
+        Err(_) => {
+            let pos = peek(reader, 0)?.0;
+            Err(Stop::Invalid(n, Error::InvalidToken(pos, "hexadecimal number too large".into())))
+        }
+    }
+}
+
+//fi scan_float
+/// Scan for a floating point number
+fn scan_float<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>) -> Scanned {
+    let is_digit = |c:Option<char>| c.is_some_and(|c| c.is_ascii_digit());
+    let (mut n, mut text) = collect_while(reader, 0, |c| c.is_ascii_digit())?;
+    if n == 0 {
+        return Ok(None);
+    }
+    let mut is_float = false;
+    if peek(reader, n)?.1 == Some('.') && is_digit(peek(reader, n+1)?.1) {
+        let (end, fraction) = collect_while(reader, n+1, |c| c.is_ascii_digit())?;
+        text.push('.');
+        text += &fraction;
+        n = end;
+        is_float = true;
+    }
+    if matches!(peek(reader, n)?.1, Some('e') | Some('E')) {
+        let sign = peek(reader, n+1)?.1;
+        let sign_len = if matches!(sign, Some('+') | Some('-')) { 1 } else { 0 };
+        if is_digit(peek(reader, n+1+sign_len)?.1) {
+            let (end, exponent) = collect_while(reader, n+1+sign_len, |c| c.is_ascii_digit())?;
+            text.push('e');
+            if sign_len > 0 {
+                text.push(sign.unwrap());
+            }
+            text += &exponent;
+            n = end;
+            is_float = true;
+        }
+    }
+    if !is_float {
+        return Ok(None);
+    }
+    // The text is always a valid float, which may be infinite
+    Ok(Some((n, Token::Float(text.parse().unwrap_or(f64::INFINITY)))))
+}
+
+//fi scan_identifier
+/// Scan for an identifier
+fn scan_identifier<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>) -> Scanned {
+    match peek(reader, 0)?.1 {
+        Some(c) if c.is_alphabetic() || c == '_' => (),
+        _ => { return Ok(None); }
+    }
+    let (n, text) = collect_while(reader, 0, |c| c.is_alphanumeric() || c == '_')?;
+    Ok(Some((n, Token::Identifier(text))))
+}
+
+//fi scan_string
+/// Scan for a quoted string
+fn scan_string<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, rules:&StringRules) -> Scanned {
+    let (start, ch) = peek(reader, 0)?;
+    if ch != Some(rules.quote) {
+        return Ok(None);
+    }
+    let unterminated = |n| Stop::Invalid(n, Error::Unterminated(start, "string".into()));
+    let mut text = String::new();
+    let mut invalid_escape = None;
+    let mut n = 1;
+    loop {
+        let (pos, ch) = peek(reader, n)?;
+        let ch = {
+            match ch {
+                Some(ch) => ch,
+                None => { return Err(unended(reader, n, pos, "string").unwrap_or_else(|| unterminated(n))); }
+            }
+        };
+        if ch == rules.quote {
+            if rules.escapes == Escapes::DoubledQuote && peek(reader, n+1)?.1 == Some(rules.quote) {
+                text.push(ch);
+                n += 2;
+                continue;
+            }
+            return match invalid_escape {
+                None    => Ok(Some((n+1, Token::Str(text)))),
+                Some(e) => Err(Stop::Invalid(n+1, e)),
+            };
+        }
+        if ch == '\n' && !rules.multiline {
+            return Err(unterminated(n));
+        }
+        if ch == '\\' && rules.escapes == Escapes::Backslash {
+            // After an invalid escape, continue to the end of the
+            // string so that it is consumed as a whole
+            match scan_escape(reader, n, pos) {
+                Ok((len, Some(escaped))) => {
+                    text.push(escaped);
+                    n += len;
+                }
+                Ok((len, None)) => {
+                    let pos = peek(reader, n+len)?.0;
+                    return Err(unended(reader, n+len, pos, "string").unwrap_or_else(|| unterminated(n+len)));
+                }
+                Err(Stop::Invalid(end, e)) => {
+                    invalid_escape.get_or_insert(e);
+                    n = end;
+                }
+                Err(e) => { return Err(e); }
+            }
+            continue;
+        }
+        text.push(ch);
+        n += 1;
+    }
+}
+
+//fi unended
+/// Get the [Stop] for a token (such as a string) that is not ended
+/// at `n` ahead in the stream (at `pos`) because the stream cannot be
+/// decoded there, if that is the case: the characters before it are
+/// consumed, and an [Error::InvalidToken] is returned at its position
+fn unended<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, n:usize, pos:StreamPosition, what:&str) -> Option<Stop> {
+    error_at(reader, n).map(|error| Stop::Invalid(n, Error::InvalidToken(pos, format!("{} in {}", error, what))))
+}
+
+//fi scan_escape
+/// Scan for the backslash escape at `n` ahead in the stream, which is
+/// at position `pos`, returning its length in characters and the
+/// character it escapes (or `None` if the stream ends first)
+fn scan_escape<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, n:usize, pos:StreamPosition) -> core::result::Result<(usize, Option<char>), Stop> {
+    let invalid = |len, what:&str| Stop::Invalid(n+len, Error::InvalidToken(pos, format!("invalid escape {}", what)));
+    let escaped = {
+        match peek(reader, n+1)?.1 {
+            None       => { return Ok((1, None)); }
+            Some('n')  => '\n',
+            Some('r')  => '\r',
+            Some('t')  => '\t',
+            Some('0')  => '\0',
+            Some('\\') => '\\',
+            Some('\'') => '\'',
+            Some('"')  => '"',
+            Some('u')  => {
+                if peek(reader, n+2)?.1 != Some('{') {
+                    return Err(invalid(2, "\\u without '{'"));
+                }
+                let (end, digits) = collect_while(reader, n+3, |c| c.is_ascii_hexdigit())?;
+                if peek(reader, end)?.1 != Some('}') || digits.is_empty() || digits.len() > 6 {
+                    return Err(invalid(end-n, &format!("\\u{{{}", digits)));
+                }
+                let ch = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+                return match ch {
+                    Some(ch) => Ok((end+1-n, Some(ch))),
+                    None => Err(invalid(end+1-n, &format!("\\u{{{}}}", digits))),
+                };
+            }
+            Some(c) => { return Err(invalid(2, &format!("\\{}", c))); }
+        }
+    };
+    Ok((2, Some(escaped)))
+}
+
+//fi scan_block_comment
+/// Scan for a block comment
+fn scan_block_comment<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, open:&str, close:&str) -> Scanned {
+    if !matches_at(reader, 0, open)? {
+        return Ok(None);
+    }
+    let start = peek(reader, 0)?.0;
+    let mut text = String::new();
+    let mut n = open.chars().count();
+    loop {
+        if matches_at(reader, n, close)? {
+            return Ok(Some((n + close.chars().count(), Token::BlockComment(text))));
+        }
+        match peek(reader, n)? {
+            (_, Some(ch)) => { text.push(ch); n += 1; }
+            (pos, None) => {
+                return Err(unended(reader, n, pos, "block comment")
+                           .unwrap_or_else(|| Stop::Invalid(n, Error::Unterminated(start, "block comment".into()))));
+            }
+        }
+    }
+}
+
+//a Lexer
+//tp Lexer
+/// A [Lexer] runs a table of [Recognizer]s to split a stream into
+/// tokens
+///
+/// The recognizers are tried in the order that they are added, and
+/// the first to recognize a token wins; so, for example,
+/// [Float](Recognizer::Float) and [Hex](Recognizer::Hex) should come
+/// before [Decimal](Recognizer::Decimal), and comments before any
+/// recognizer for the characters that introduce them.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{Escapes, Lexed, Lexer, Reader, Recognizer, StringRules, Token};
+///     let lexer = Lexer::new()
+///         .add_recognizer(Recognizer::Whitespace)
+///         .add_recognizer(Recognizer::LineComment("#".into()))
+///         .add_recognizer(Recognizer::Hex)
+///         .add_recognizer(Recognizer::Float)
+///         .add_recognizer(Recognizer::Decimal)
+///         .add_recognizer(Recognizer::Identifier)
+///         .add_recognizer(Recognizer::Str(StringRules::new('\'', Escapes::DoubledQuote)));
+///     let mut reader = Reader::new("x 0x1f 2.5e3 'it''s' # done".as_bytes());
+///     let mut tokens = Vec::new();
+///     while let Lexed::Token(token, _) = lexer.next_token(&mut reader).unwrap() {
+///         if token != Token::Whitespace {
+///             tokens.push(token);
+///         }
+///     }
+///     assert_eq!(tokens, [Token::Identifier("x".into()),
+///                         Token::Hex(0x1f),
+///                         Token::Float(2500.0),
+///                         Token::Str("it's".into()),
+///                         Token::LineComment(" done".into())]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Lexer {
+    /// The recognizers, in the order they are tried
+    recognizers : Vec<Recognizer>,
+}
+
+//ip Lexer
+impl Lexer {
+    //fp new
+    /// Create a new [Lexer] with no recognizers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //cp add_recognizer
+    /// Build pattern function to add a recognizer to the table
+    pub fn add_recognizer(mut self, recognizer:Recognizer) -> Self {
+        self.recognizers.push(recognizer);
+        self
+    }
+
+    //mp next_token
+    /// Recognize and consume the next token of the stream, returning
+    /// it with its [StreamSpan]; [NoData](Lexed::NoData) is returned
+    /// if the stream has no data before the token is complete, and
+    /// [Eof](Lexed::Eof) at the end of the stream.
+    ///
+    /// # Errors
+    ///
+    /// As for [recognize](Recognizer::recognize); if no recognizer
+    /// matches then an [Error::Expected] is returned, and the
+    /// character is consumed.
+    pub fn next_token<R:ByteSource, O:Observer>(&self, reader:&mut Reader<R, O>) -> Result<Lexed> {
+        let (pos, ch) = {
+            match reader.peek_nth(0) {
+                Peeked::Char(pos, ch) => (pos, ch),
+                Peeked::NoData        => { return Ok(Lexed::NoData); }
+                Peeked::Eof(_)        => { return Ok(Lexed::Eof); }
+                Peeked::Error(_)      => { return Err(reader.take_peeked_error()); }
+            }
+        };
+        for recognizer in self.recognizers.iter() {
+            if let Some(lexed) = recognizer.recognize(reader)? {
+                return Ok(lexed);
+            }
+        }
+        reader.take(1);
+        Err(Error::Expected(pos, "a token".into(), Char::Char(ch)))
+    }
+}
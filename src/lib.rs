@@ -17,6 +17,7 @@ limitations under the License.
  */
 
 //a Documentation
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 #![warn(missing_doc_code_examples)]
 
@@ -38,6 +39,18 @@ gets more data.
 The [Reader] provided also allows for reading large UTF8 files
 piecewise; it only reads up to 2kB of data at a time from its stream.
 
+# no_std
+
+The [Reader] reads from any [ByteSource]; with the default `std`
+feature every [std::io::Read] is a [ByteSource], and errors from the
+stream are [std::io::Error]s.
+
+Without the `std` feature the crate is `no_std`, requiring only the
+`alloc` crate, so that (for example) a microcontroller can decode
+UTF-8 from a UART with position tracking; the client implements
+[ByteSource] for its device, and errors are the crate's own
+[IoError].
+
 
 # Example
 
@@ -57,23 +70,81 @@ for x in reader.into_iter() {
 From a file, one could do:
 
 ```
+# #[cfg(feature = "std")] {
 use utf8_read::Reader;
 let in_file = std::fs::File::open("Cargo.toml").unwrap();
 let mut reader = Reader::new(&in_file);
 for x in reader.into_iter() {
     // use char x
 }
-
+# }
 ```
 
 !*/
 
 //a Imports
+extern crate alloc;
+
+mod ascii;
+mod byte_source;
 mod types;
 mod stream_position;
 mod reader;
+mod source_position;
+mod chain_reader;
+mod include_reader;
+mod reader_state;
+mod diagnostic;
+mod limits;
+mod progress;
+mod observer;
+mod text_stats;
+mod decoder;
+mod lookahead;
+mod iterators;
+mod scanner;
+mod lexer;
+mod unescape;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "mmap")]
+mod mmap_reader;
+#[cfg(feature = "embedded-io")]
+mod embedded;
+#[cfg(feature = "regex")]
+mod stream_regex;
+#[cfg(feature = "aho-corasick")]
+mod keywords;
+#[cfg(feature = "unicode-ident")]
+mod identifier;
 
 //a Exports
-pub use types::{Char, Error, Result};
-pub use stream_position::StreamPosition;
+pub use types::{Char, Error, Result, Utf8ErrorKind};
+pub use byte_source::{ByteSource, IoError, IoErrorKind};
+pub use stream_position::{StreamPosition, StreamSpan};
 pub use reader::Reader;
+pub use observer::{NoObserver, Observer};
+pub use progress::ProgressFn;
+pub use iterators::{CharsRaw, Positions};
+pub use decoder::{Decoder, Feed};
+pub use reader_state::ReaderState;
+pub use text_stats::{LineEndings, TextStats};
+pub use source_position::SourcePosition;
+pub use chain_reader::ChainReader;
+pub use include_reader::IncludeReader;
+pub use lexer::{Escapes, Lexed, Lexer, Recognizer, StringRules, Token};
+pub use unescape::{EscapeDialect, Unescape};
+#[cfg(feature = "rayon")]
+pub use parallel::{par_chunks, par_lines, Text};
+#[cfg(feature = "mmap")]
+pub use mmap_reader::{MmapReader, MmapSource};
+#[cfg(feature = "embedded-io")]
+pub use embedded::EmbeddedIo;
+#[cfg(feature = "embedded-io-async")]
+pub use embedded::EmbeddedIoAsync;
+#[cfg(feature = "regex")]
+pub use stream_regex::{FindIter, RegexMatcher, StreamRegex};
+#[cfg(feature = "aho-corasick")]
+pub use keywords::{KeywordMatcher, KeywordSet, Keywords};
+#[cfg(feature = "unicode-ident")]
+pub use identifier::{is_pattern_syntax, is_pattern_white_space, is_xid_continue, is_xid_start, Identifier};
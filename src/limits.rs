@@ -0,0 +1,162 @@
+//a Imports
+use crate::{ascii, Error, Result, StreamPosition};
+
+//a Limit
+//tp Limit
+/// A limit of [Limits] that may be exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Limit {
+    /// The maximum byte offset in the stream
+    Bytes(usize),
+    /// The maximum number of characters in a line
+    LineChars(usize),
+    /// The maximum number of malformed UTF-8 sequences
+    Malformed(usize),
+}
+
+//ip Limit
+impl Limit {
+    //mp error
+    /// Create the error for the limit being exceeded at `pos`
+    fn error(self, pos:StreamPosition) -> Error {
+        match self {
+            Limit::Bytes(n)     => Error::ByteLimit(pos, n),
+            Limit::LineChars(n) => Error::LineLimit(pos, n),
+            Limit::Malformed(n) => Error::MalformedLimit(pos, n),
+        }
+    }
+}
+
+//a Limits
+//tp Limits
+/// The limits on the data that a [Reader](crate::Reader) will read,
+/// for untrusted input; once a limit is exceeded the [Reader] fails
+/// with the same error from then on
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Limits {
+    /// The maximum byte offset in the stream of the end of a character
+    /// or malformed UTF-8 sequence
+    max_bytes      : Option<usize>,
+    /// The maximum number of characters in a line, excluding the
+    /// newline
+    max_line_chars : Option<usize>,
+    /// The maximum number of malformed UTF-8 sequences
+    max_malformed  : Option<usize>,
+    /// The number of malformed UTF-8 sequences found
+    malformed      : usize,
+    /// The limit that has been exceeded, and where
+    exceeded       : Option<(Limit, StreamPosition)>,
+}
+
+//ip Limits
+impl Limits {
+    //mp set_max_bytes
+    /// Set the maximum byte offset in the stream of the end of a
+    /// character or malformed UTF-8 sequence
+    pub(crate) fn set_max_bytes(&mut self, max_bytes:usize) {
+        self.max_bytes = Some(max_bytes);
+    }
+
+    //mp set_max_line_chars
+    /// Set the maximum number of characters in a line
+    pub(crate) fn set_max_line_chars(&mut self, max_line_chars:usize) {
+        self.max_line_chars = Some(max_line_chars);
+    }
+
+    //mp set_max_malformed
+    /// Set the maximum number of malformed UTF-8 sequences
+    pub(crate) fn set_max_malformed(&mut self, max_malformed:usize) {
+        self.max_malformed = Some(max_malformed);
+    }
+
+    //mi exceed
+    /// Record that `limit` has been exceeded at `pos`, returning the error
+    fn exceed<T>(&mut self, limit:Limit, pos:StreamPosition) -> Result<T> {
+        self.exceeded = Some((limit, pos));
+        Err(limit.error(pos))
+    }
+
+    //mp check_exceeded
+    /// Return the error for a limit that has already been exceeded
+    #[inline]
+    pub(crate) fn check_exceeded(&self) -> Result<()> {
+        match self.exceeded {
+            Some((limit, pos)) => Err(limit.error(pos)),
+            None => Ok(()),
+        }
+    }
+
+    //mp check_bytes
+    /// Check that the `n` bytes of the stream at `pos` are within the
+    /// byte limit
+    #[inline]
+    pub(crate) fn check_bytes(&mut self, pos:StreamPosition, n:usize) -> Result<()> {
+        match self.max_bytes {
+            Some(max) if pos.byte() + n > max => self.exceed(Limit::Bytes(max), pos),
+            _ => Ok(()),
+        }
+    }
+
+    //mp check_char
+    /// Check that the character `ch` at `pos` is within the limits
+    #[inline]
+    pub(crate) fn check_char(&mut self, pos:StreamPosition, ch:char) -> Result<()> {
+        self.check_bytes(pos, ch.len_utf8())?;
+        if let Some(max) = self.max_line_chars {
+            if ch != '\n' && pos.line_position().1 > max {
+                return self.exceed(Limit::LineChars(max), pos);
+            }
+        }
+        Ok(())
+    }
+
+    //mp check_malformed
+    /// Count a malformed UTF-8 sequence of `n` bytes at `pos`,
+    /// checking that this is within the limits
+    pub(crate) fn check_malformed(&mut self, pos:StreamPosition, n:usize) -> Result<()> {
+        self.check_bytes(pos, n)?;
+        self.malformed += 1;
+        match self.max_malformed {
+            Some(max) if self.malformed > max => self.exceed(Limit::Malformed(max), pos),
+            _ => Ok(()),
+        }
+    }
+
+    //mp allowed
+    /// Return the length of the longest prefix of `text`, which starts
+    /// at `pos`, that is within the limits
+    pub(crate) fn allowed(&self, pos:&StreamPosition, text:&str) -> usize {
+        let mut n = text.len();
+        if let Some(max) = self.max_bytes {
+            n = n.min(max.saturating_sub(pos.byte()));
+            while !text.is_char_boundary(n) {
+                n -= 1;
+            }
+        }
+        if let Some(max) = self.max_line_chars {
+            // Check each line (excluding its newline) of the prefix
+            let bytes = &text.as_bytes()[..n];
+            let mut line_chars = pos.line_position().1 - 1;
+            let mut line_start = 0;
+            loop {
+                let line_end = memchr::memchr(b'\n', &bytes[line_start..]).map_or(n, |i| line_start + i);
+                let line = &bytes[line_start..line_end];
+                if line_chars + ascii::count_chars(line) > max {
+                    let excess = text[line_start..line_end]
+                        .char_indices()
+                        .nth(max.saturating_sub(line_chars))
+                        .map_or(line_end, |(i, _)| i);
+                    return line_start + excess;
+                }
+                if line_end == n {
+                    break;
+                }
+                line_chars = 0;
+                line_start = line_end + 1;
+            }
+        }
+        n
+    }
+
+    //zz All done
+}
@@ -0,0 +1,134 @@
+//a Imports
+use alloc::string::String;
+use crate::StreamPosition;
+
+//a Constants
+/// The number of bytes of removed characters at the start of the text
+/// of a [Lookahead] above which the text is compacted (if they are
+/// at least half of it)
+const COMPACT_SIZE : usize = 1024;
+
+//a Lookahead
+//tp Lookahead
+/// The characters that a [Reader](crate::Reader) has decoded by
+/// peeking but not yet returned
+///
+/// The characters are held as their UTF-8 encoding, with the position
+/// of the first; there is no malformed UTF-8 between them, so the
+/// position of each character follows from those before it. A cursor
+/// is kept at the last character peeked, so that peeking at
+/// successive characters (as the scanning methods do) does not walk
+/// the text from its start every time.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Lookahead {
+    /// The encoding of the characters, from `start`
+    text   : String,
+    /// Offset in `text` of the first character
+    start  : usize,
+    /// Position of the first character
+    pos    : StreamPosition,
+    /// Number of characters
+    len    : usize,
+    /// Index, offset in `text` and position of a character at or
+    /// after the first
+    cursor : (usize, usize, StreamPosition),
+}
+
+//ip Lookahead
+impl Lookahead {
+    //mp len
+    /// Get the number of characters
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    //mp is_empty
+    /// Returns true if there are no characters
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    //mp front_pos
+    /// Borrow the position of the first character, if there is one
+    #[inline]
+    pub(crate) fn front_pos(&self) -> Option<&StreamPosition> {
+        if self.len == 0 { None } else { Some(&self.pos) }
+    }
+
+    //mp as_bytes
+    /// Get the UTF-8 encoding of the characters
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.text.as_bytes()[self.start..]
+    }
+
+    //mp push_back
+    /// Add a character, at `pos`, after the others
+    pub(crate) fn push_back(&mut self, pos:StreamPosition, ch:char) {
+        if self.len == 0 {
+            self.pos    = pos;
+            self.cursor = (0, self.start, pos);
+        }
+        self.text.push(ch);
+        self.len += 1;
+    }
+
+    //mp pop_front
+    /// Remove the first character, returning it and its position
+    pub(crate) fn pop_front(&mut self) -> Option<(StreamPosition, char)> {
+        if self.len == 0 {
+            return None;
+        }
+        let pos = self.pos;
+        let ch = self.text[self.start..].chars().next().unwrap();
+        self.start += ch.len_utf8();
+        self.pos.move_by(ch.len_utf8(), ch);
+        self.len -= 1;
+        if self.len == 0 {
+            self.clear();
+            return Some((pos, ch));
+        }
+        if self.cursor.0 == 0 {
+            self.cursor = (0, self.start, self.pos);
+        } else {
+            self.cursor.0 -= 1;
+        }
+        if self.start >= COMPACT_SIZE && self.start * 2 >= self.text.len() {
+            // Drop the text of the characters that have been removed
+            self.text.drain(..self.start);
+            self.cursor.1 -= self.start;
+            self.start = 0;
+        }
+        Some((pos, ch))
+    }
+
+    //mp nth
+    /// Get the character `n` after the first, and its position; `n`
+    /// must be less than the number of characters
+    pub(crate) fn nth(&mut self, n:usize) -> (StreamPosition, char) {
+        if n < self.cursor.0 {
+            self.cursor = (0, self.start, self.pos);
+        }
+        let (mut i, mut ofs, mut pos) = self.cursor;
+        let mut chars = self.text[ofs..].chars();
+        let mut ch = chars.next().unwrap();
+        while i < n {
+            pos.move_by(ch.len_utf8(), ch);
+            ofs += ch.len_utf8();
+            i   += 1;
+            ch = chars.next().unwrap();
+        }
+        self.cursor = (i, ofs, pos);
+        (pos, ch)
+    }
+
+    //mp clear
+    /// Remove all of the characters
+    pub(crate) fn clear(&mut self) {
+        self.text.clear();
+        self.start  = 0;
+        self.len    = 0;
+        self.cursor = (0, 0, self.pos);
+    }
+}
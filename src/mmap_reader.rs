@@ -0,0 +1,188 @@
+//a Imports
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+use memmap2::Mmap;
+use crate::{Reader, Result};
+
+//a MmapSource
+//tp Source
+/// The bytes of an [MmapSource]
+enum Source {
+    /// A regular file that has been mapped into memory (which is
+    /// shared with the [Reader] that decodes it), and the offset of
+    /// the next byte to be read from the mapping
+    Mapped(Arc<Mmap>, usize),
+    /// A file that cannot be mapped, which is read directly
+    File(File),
+}
+
+//tp MmapSource
+/// The [MmapSource] is the source of an [MmapReader], holding a
+/// memory mapping of a file if it is a regular file; the
+/// [MmapReader] decodes the characters directly from the mapping,
+/// rather than copying the bytes through its internal buffer.
+///
+/// An [MmapSource] may also be read (as any [Read]), which copies the
+/// bytes from the mapping.
+///
+/// Files that are not regular files - such as pipes, devices and
+/// `/proc` entries, which report a length of zero - and files that
+/// cannot be mapped are read directly instead, with the same results.
+///
+/// Requires the `mmap` feature.
+///
+/// # Safety of the mapping
+///
+/// The contents of the mapping change if the file is modified while
+/// it is being read (for example, if it is truncated the process may
+/// receive SIGBUS). The characters returned are always valid, but
+/// they may not be a consistent snapshot of the file; files that may
+/// be modified by other processes should be read with a [Reader] of
+/// the [File].
+pub struct MmapSource {
+    /// The mapping or file
+    source : Source,
+}
+
+//ip MmapSource
+impl MmapSource {
+    //fp from_file
+    /// Create an [MmapSource] for a file that has been opened for
+    /// reading, mapping it if it is a regular file that is not empty
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metadata of the file cannot be read
+    pub fn from_file(file:File) -> std::io::Result<Self> {
+        let metadata = file.metadata()?;
+        if metadata.file_type().is_file() && metadata.len() > 0 {
+            // Safety: see the documentation of MmapSource
+            if let Ok(map) = unsafe { Mmap::map(&file) } {
+                return Ok(Self { source:Source::Mapped(Arc::new(map), 0) });
+            }
+        }
+        Ok(Self { source:Source::File(file) })
+    }
+
+    //mp is_mapped
+    /// Returns true if the file is mapped into memory, and false if it
+    /// is read directly
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.source, Source::Mapped(_, _))
+    }
+
+    //mp mapping
+    /// Borrow the bytes of the file, if it is mapped into memory
+    pub fn mapping(&self) -> Option<&[u8]> {
+        match &self.source {
+            Source::Mapped(map, _) => Some(map),
+            Source::File(_) => None,
+        }
+    }
+
+    //mi take_mapping
+    /// Get the mapping of the file from the current offset, if it is
+    /// mapped, for a [Reader] to decode in place; the source is moved
+    /// to the end of the mapping, as its bytes are then consumed
+    fn take_mapping(&mut self) -> Option<Arc<Mmap>> {
+        match &mut self.source {
+            Source::Mapped(map, ofs) if *ofs == 0 => {
+                *ofs = map.len();
+                Some(map.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+//ip Read for MmapSource
+impl Read for MmapSource {
+    fn read(&mut self, buf:&mut [u8]) -> std::io::Result<usize> {
+        match &mut self.source {
+            Source::Mapped(map, ofs) => {
+                let mut bytes = &map[(*ofs).min(map.len())..];
+                let n = bytes.read(buf)?;
+                *ofs += n;
+                Ok(n)
+            }
+            Source::File(file) => file.read(buf),
+        }
+    }
+}
+
+//ip Seek for MmapSource
+impl Seek for MmapSource {
+    fn seek(&mut self, pos:SeekFrom) -> std::io::Result<u64> {
+        match &mut self.source {
+            Source::Mapped(map, ofs) => {
+                let mut cursor = std::io::Cursor::new(&map[..]);
+                cursor.set_position(*ofs as u64);
+                let new_ofs = cursor.seek(pos)?;
+                *ofs = new_ofs as usize;
+                Ok(new_ofs)
+            }
+            Source::File(file) => file.seek(pos),
+        }
+    }
+}
+
+//a MmapReader
+//tp MmapReader
+/// An [MmapReader] is a [Reader] of a file that is memory mapped if
+/// it is a regular file (see [MmapSource]), decoding the characters
+/// directly from the mapping; it has the full API of a [Reader],
+/// and the length hint of the [Reader] is set from the length of the
+/// file.
+///
+/// The mapping is added to the internal buffer of the [Reader] a
+/// window (of 64kB) at a time, without copying, so
+/// [borrow_buffer](Reader::borrow_buffer) borrows the mapping itself.
+///
+/// Requires the `mmap` feature.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::MmapReader;
+///     let mut reader = MmapReader::open("Cargo.toml").unwrap();
+///     assert!(reader.borrow().is_mapped());
+///     let mut line = String::new();
+///     reader.read_line(&mut line).unwrap();
+///     assert_eq!(line, "[package]\n");
+///     assert_eq!(reader.borrow_pos().line_position(), (2, 1));
+/// ```
+pub type MmapReader = Reader<MmapSource>;
+
+//ip MmapReader
+impl Reader<MmapSource> {
+    //fp open
+    /// Open a file and create an [MmapReader] for it
+    ///
+    /// # Errors
+    ///
+    /// Returns [Error::IoError](crate::Error::IoError) if the file
+    /// cannot be opened
+    pub fn open<P:AsRef<Path>>(path:P) -> Result<Self> {
+        Self::from_file(File::open(path)?)
+    }
+
+    //fp from_file
+    /// Create an [MmapReader] for a file that has been opened for
+    /// reading
+    ///
+    /// # Errors
+    ///
+    /// Returns [Error::IoError](crate::Error::IoError) if the metadata
+    /// of the file cannot be read
+    pub fn from_file(file:File) -> Result<Self> {
+        let mut reader = Reader::new(MmapSource::from_file(file)?).length_from_source();
+        if let Some(map) = reader.borrow_mut().take_mapping() {
+            reader.decode_mapping(map);
+        }
+        Ok(reader)
+    }
+
+    //zz All done
+}
@@ -0,0 +1,127 @@
+//a Imports
+use crate::{Error, StreamPosition};
+
+//a Observer
+//tp Observer
+/// An [Observer] is attached to a [Reader](crate::Reader) with
+/// [set_observer](crate::Reader::set_observer) to be told of the
+/// characters, line ends, errors and reads of the underlying stream
+/// (and, optionally, the progress of reading it),
+/// as the stream is consumed; this allows side effects (such as
+/// building a line index or an audit log) without changing the
+/// client of the [Reader](crate::Reader).
+///
+/// Every method has a default implementation that does nothing, so an
+/// observer need only implement the hooks that it requires. The
+/// [Reader](crate::Reader) is generic on its observer, so the hooks
+/// are resolved at compile time; a [Reader](crate::Reader) without an
+/// observer uses [NoObserver], whose hooks compile to nothing.
+///
+/// Characters are observed as they are consumed (by any method of the
+/// [Reader](crate::Reader)), not when they are peeked.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{Observer, Reader, StreamPosition};
+///     /// Record the byte offset of the start of every line
+///     #[derive(Default)]
+///     struct LineIndex { starts : Vec<usize> }
+///     impl Observer for LineIndex {
+///         fn on_line_end(&mut self, pos:StreamPosition) {
+///             self.starts.push(pos.byte() + 1);
+///         }
+///     }
+///     let mut reader = Reader::new("one\ntwo\nthree".as_bytes()).set_observer(LineIndex::default());
+///     let mut s = String::new();
+///     reader.read_to_string(&mut s).unwrap();
+///     assert_eq!(reader.borrow_observer().starts, [4, 8]);
+/// ```
+pub trait Observer {
+    //mp on_char
+    /// Invoked when the character `ch`, at `pos`, is consumed
+    #[inline]
+    fn on_char(&mut self, _pos:StreamPosition, _ch:char) {}
+
+    //mp on_line_end
+    /// Invoked when a newline, at `pos`, is consumed (after
+    /// [on_char](Observer::on_char) for the newline)
+    #[inline]
+    fn on_line_end(&mut self, _pos:StreamPosition) {}
+
+    //mp on_str
+    /// Invoked when the characters of `text`, the first of which is at
+    /// `pos`, are consumed in one step by a bulk read method (such as
+    /// [read_to_string](crate::Reader::read_to_string)); the default
+    /// invokes [on_char](Observer::on_char) and
+    /// [on_line_end](Observer::on_line_end) for each character
+    fn on_str(&mut self, mut pos:StreamPosition, text:&str) {
+        for ch in text.chars() {
+            self.on_char(pos, ch);
+            if ch == '\n' {
+                self.on_line_end(pos);
+            }
+            pos.move_by(ch.len_utf8(), ch);
+        }
+    }
+
+    //mp on_error
+    /// Invoked when an error (such as malformed UTF-8, or an IO error
+    /// from the underlying stream) is returned to the client
+    #[inline]
+    fn on_error(&mut self, _err:&Error) {}
+
+    //mp on_fill
+    /// Invoked when the underlying stream has been read, with the
+    /// number of bytes read; zero indicates that the stream has no
+    /// data or is at its end
+    #[inline]
+    fn on_fill(&mut self, _bytes_read:usize) {}
+
+    //mp on_progress
+    /// Invoked when the stream has been read by another `every` bytes,
+    /// and once at EOF, if enabled with
+    /// [set_progress_interval](crate::Reader::set_progress_interval),
+    /// with the [StreamPosition] of the next character and the
+    /// fraction of the stream that has been read (if its length is
+    /// known)
+    #[inline]
+    fn on_progress(&mut self, _pos:StreamPosition, _fraction:Option<f64>) {}
+}
+
+//ip Observer for &mut Observer
+/// A mutable reference to an [Observer] is an [Observer], so that the
+/// client may keep ownership of it
+impl <O:Observer + ?Sized> Observer for &mut O {
+    fn on_char(&mut self, pos:StreamPosition, ch:char) {
+        (**self).on_char(pos, ch)
+    }
+    fn on_line_end(&mut self, pos:StreamPosition) {
+        (**self).on_line_end(pos)
+    }
+    fn on_str(&mut self, pos:StreamPosition, text:&str) {
+        (**self).on_str(pos, text)
+    }
+    fn on_error(&mut self, err:&Error) {
+        (**self).on_error(err)
+    }
+    fn on_fill(&mut self, bytes_read:usize) {
+        (**self).on_fill(bytes_read)
+    }
+    fn on_progress(&mut self, pos:StreamPosition, fraction:Option<f64>) {
+        (**self).on_progress(pos, fraction)
+    }
+}
+
+//a NoObserver
+//tp NoObserver
+/// The [Observer] of a [Reader](crate::Reader) that has none, which
+/// observes nothing at no cost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoObserver;
+
+//ip Observer for NoObserver
+impl Observer for NoObserver {
+    #[inline]
+    fn on_str(&mut self, _pos:StreamPosition, _text:&str) {}
+}
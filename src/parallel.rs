@@ -0,0 +1,262 @@
+//a Imports
+use rayon::prelude::*;
+use crate::ascii;
+use crate::decoder::{validate, Decoded};
+use crate::{Error, Result, StreamPosition, Utf8ErrorKind};
+
+//a Types
+/// A piece of decoded text, with the position of its first character
+pub type Text<'a> = (StreamPosition, &'a str);
+
+//a Chunking
+//fi chunk_starts
+/// Split `data` into chunks of approximately `chunk_size` bytes,
+/// returning the offset of the start of each chunk followed by the
+/// length of the data.
+///
+/// If `at_newlines` is set then each chunk starts after a newline;
+/// otherwise the start of a chunk is moved past up to three
+/// continuation bytes, so that it is never within a UTF-8 encoding
+/// nor part way through a malformed encoding (as it would be split
+/// by [std::str::Utf8Error::error_len]).
+fn chunk_starts(data:&[u8], chunk_size:usize, at_newlines:bool) -> Vec<usize> {
+    let chunk_size = chunk_size.max(1);
+    let mut starts = vec![0];
+    let mut start = chunk_size;
+    while start < data.len() {
+        if at_newlines {
+            match memchr::memchr(b'\n', &data[start..]) {
+                Some(n) => { start += n + 1; }
+                None    => { start = data.len(); }
+            }
+        } else {
+            for _ in 0..3 {
+                if start < data.len() && (data[start] & 0xc0) == 0x80 {
+                    start += 1;
+                }
+            }
+        }
+        if start < data.len() {
+            starts.push(start);
+        }
+        start += chunk_size;
+    }
+    starts.push(data.len());
+    starts
+}
+
+//a Chunk
+//tp Chunk
+/// An iterator over the text and errors of the chunk of the data
+/// from `ofs` to `end`, with positions relative to the start of the
+/// chunk (at line 1 char 1, but with true byte offsets)
+///
+/// If `split_lines` is set then the text is split after every newline.
+struct Chunk<'a> {
+    /// The complete data
+    data        : &'a [u8],
+    /// Offset of the next byte of the chunk to decode
+    ofs         : usize,
+    /// Offset of the end of the chunk
+    end         : usize,
+    /// Position of the next text or error, relative to the start of the chunk
+    pos         : StreamPosition,
+    /// Set if the text is split after every newline
+    split_lines : bool,
+    /// Lines of valid text still to be returned, if `split_lines` is set
+    lines       : Option<core::str::SplitInclusive<'a, char>>,
+}
+
+//ip Chunk
+impl <'a> Chunk<'a> {
+    //fp new
+    /// Create a new [Chunk] of `data` from `start` to `end`
+    fn new(data:&'a [u8], start:usize, end:usize, split_lines:bool) -> Self {
+        let pos = StreamPosition::of_blc(start, 1, 1);
+        Self { data, ofs:start, end, pos, split_lines, lines:None }
+    }
+
+    //mp end_pos
+    /// Find the position at the end of the chunk, relative to its start
+    fn end_pos(mut self) -> StreamPosition {
+        for _ in &mut self {}
+        self.pos
+    }
+
+    //fp chunk_end
+    /// Find the position at the end of the chunk of `data` from
+    /// `start` to `end`, relative to its start; newlines are counted
+    /// across the chunk, and only the bytes after the last newline
+    /// are decoded
+    ///
+    /// Malformed UTF-8 never includes a newline, and decoding
+    /// restarts at a newline, so this matches a full decode.
+    fn chunk_end(data:&'a [u8], start:usize, end:usize) -> StreamPosition {
+        match memchr::memrchr(b'\n', &data[start..end]) {
+            None => Self::new(data, start, end, false).end_pos(),
+            Some(last) => {
+                let line_num = 2 + ascii::count_newlines(&data[start..start+last]);
+                let (_, char_ofs) = Self::new(data, start+last+1, end, false).end_pos().line_position();
+                StreamPosition::of_blc(end, line_num, char_ofs)
+            }
+        }
+    }
+
+    //mi text
+    /// Return `s` (which is valid text at the current position) and
+    /// move the position past it
+    fn text(&mut self, s:&'a str) -> Result<Text<'a>> {
+        let pos = self.pos;
+        self.pos.move_by_str(s);
+        Ok((pos, s))
+    }
+
+    //mi malformed
+    /// Return an error for the `n` bytes of malformed UTF-8 at the
+    /// current position, and move past them
+    fn malformed(&mut self, kind:Utf8ErrorKind, n:usize) -> Result<Text<'a>> {
+        let r = Error::malformed_utf8(self.pos, kind, &self.data[self.ofs..self.ofs+n]);
+        self.pos.move_on_bytes(n);
+        self.ofs += n;
+        r
+    }
+}
+
+//ip Iterator for Chunk
+impl <'a> Iterator for Chunk<'a> {
+    type Item = Result<Text<'a>>;
+
+    //mp next - return the next text or error of the chunk
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(line) = self.lines.as_mut().and_then(|l| l.next()) {
+            return Some(self.text(line));
+        }
+        self.lines = None;
+        if self.ofs >= self.end {
+            return None;
+        }
+        let data = self.data;
+        let bytes = &data[self.ofs..self.end];
+        match validate(bytes) {
+            Ok(n) => {
+                // std::str::from_utf8(&bytes[..n]).unwrap()
+                let s = unsafe { std::str::from_utf8_unchecked(&bytes[..n]) };
+                self.ofs += n;
+                if self.split_lines {
+                    self.lines = Some(s.split_inclusive('\n'));
+                    self.next()
+                } else {
+                    Some(self.text(s))
+                }
+            }
+            Err(Decoded::Malformed(kind, n)) => {
+                Some(self.malformed(kind, n))
+            }
+            Err(_) if self.end == data.len() => {
+                Some(self.malformed(Utf8ErrorKind::classify_at_eof(bytes), bytes.len()))
+            }
+            Err(_) => { // incomplete at the end of the chunk, so malformed given the start of the next chunk
+                let kind = {
+                    match validate(&data[self.ofs..data.len().min(self.ofs+4)]) {
+                        Err(Decoded::Malformed(kind, _)) => kind,
+                        _ => Utf8ErrorKind::Incomplete,
+                    }
+                };
+                Some(self.malformed(kind, bytes.len()))
+            }
+        }
+    }
+}
+
+//fi par_decode
+/// Decode the chunks of `data` in parallel
+///
+/// The position at the end of each chunk is found first (in
+/// parallel, from a count of its newlines and the characters of its
+/// last line) to give the position at the start of each chunk; the
+/// chunks are then decoded as the results are consumed, fixing up the
+/// positions of the results with the position of their chunk.
+fn par_decode(data:&[u8], chunk_size:usize, split_lines:bool) -> impl ParallelIterator<Item = Result<Text<'_>>> {
+    let starts = chunk_starts(data, chunk_size, split_lines);
+    let ends : Vec<_> = starts
+        .par_windows(2)
+        .map(|w| Chunk::chunk_end(data, w[0], w[1]))
+        .collect();
+    let mut base = StreamPosition::new();
+    let mut chunks = Vec::with_capacity(ends.len());
+    for (w, end) in starts.windows(2).zip(ends) {
+        chunks.push((w[0], w[1], base));
+        base = end.offset_by(&base);
+    }
+    chunks
+        .into_par_iter()
+        .flat_map_iter(move |(start, end, base)| {
+            Chunk::new(data, start, end, split_lines).map(move |r| {
+                match r {
+                    Ok((pos, s)) => Ok((pos.offset_by(&base), s)),
+                    Err(Error::MalformedUtf8(pos, kind, bytes)) => Err(Error::MalformedUtf8(pos.offset_by(&base), kind, bytes)),
+                    Err(e) => Err(e),
+                }
+            })
+        })
+}
+
+//a Public functions
+//fp par_chunks
+/// Decode `data` - such as a large file that has been read or mapped
+/// into memory - as UTF-8 on multiple threads, splitting it into
+/// chunks of approximately `chunk_size` bytes.
+///
+/// The result yields, in order, the valid text of the data as string
+/// slices with the true [StreamPosition] of their first character,
+/// and an [Error::MalformedUtf8] at its true position for each
+/// malformed encoding; this is exactly the text and errors that a
+/// [Reader](crate::Reader) would return. A chunk boundary is never
+/// within the encoding of a character, and the line numbers and
+/// character offsets of each chunk are fixed up from the newline and
+/// character counts of the chunks before it; these are counted first,
+/// and each chunk is then decoded only as its results are consumed.
+///
+/// Requires the `rayon` feature.
+///
+/// # Example
+///
+/// ```
+///     use rayon::prelude::*;
+///     let data = "caf\u{e9}\nna\u{ef}ve\n".repeat(1000);
+///     let text : Vec<_> = utf8_read::par_chunks(data.as_bytes(), 100).collect();
+///     let (pos, s) = text.last().unwrap().as_ref().unwrap();
+///     assert_eq!(pos.byte() + s.len(), data.len());
+///     assert_eq!(text.iter().map(|r| r.as_ref().unwrap().1).collect::<String>(), data);
+/// ```
+pub fn par_chunks(data:&[u8], chunk_size:usize) -> impl ParallelIterator<Item = Result<Text<'_>>> {
+    par_decode(data, chunk_size, false)
+}
+
+//fp par_lines
+/// Decode `data` as UTF-8 on multiple threads, splitting it into
+/// chunks of approximately `chunk_size` bytes that end at newlines,
+/// and yielding the lines in order with the true [StreamPosition] of
+/// their start.
+///
+/// Each line includes its newline, if it has one. A line that
+/// contains malformed UTF-8 is yielded as the valid text before and
+/// after each malformed encoding, with an [Error::MalformedUtf8] for
+/// the malformed encoding between them.
+///
+/// Requires the `rayon` feature.
+///
+/// # Example
+///
+/// ```
+///     use rayon::prelude::*;
+///     let data = "first\nsecond\nthird";
+///     let lines : Vec<_> = utf8_read::par_lines(data.as_bytes(), 4)
+///         .map(|r| r.map(|(pos, line)| (pos.line_position(), line)))
+///         .collect::<Result<_,_>>()
+///         .unwrap();
+///     assert_eq!(lines, vec![((1,1), "first\n"), ((2,1), "second\n"), ((3,1), "third")]);
+/// ```
+pub fn par_lines(data:&[u8], chunk_size:usize) -> impl ParallelIterator<Item = Result<Text<'_>>> {
+    par_decode(data, chunk_size, true)
+}
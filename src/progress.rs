@@ -0,0 +1,88 @@
+//a Imports
+use crate::{Observer, StreamPosition};
+
+//a Progress
+//tp Progress
+/// The progress reporting of a [Reader](crate::Reader), enabled with
+/// [set_progress_interval](crate::Reader::set_progress_interval) (or
+/// [set_progress](crate::Reader::set_progress)); the reports are made
+/// to the [Observer] of the [Reader](crate::Reader)
+#[derive(Debug, Clone)]
+pub(crate) struct Progress {
+    /// The number of bytes between reports
+    every    : usize,
+    /// The byte offset at or after which the next report is made
+    next     : usize,
+}
+
+//ip Progress
+impl Progress {
+    //fp new
+    /// Create a new [Progress] reporting every `every` bytes
+    pub(crate) fn new(every:usize) -> Self {
+        let every = every.max(1);
+        Self { every, next:every }
+    }
+
+    //fi fraction
+    /// Get the fraction of a stream of `length` bytes (if known) that
+    /// has been read at `pos`
+    fn fraction(pos:&StreamPosition, length:Option<u64>) -> Option<f64> {
+        length.map(|length| {
+            if length == 0 {
+                1.0
+            } else {
+                (pos.byte() as f64 / length as f64).min(1.0)
+            }
+        })
+    }
+
+    //mp update
+    /// Report the progress to `observer` if the stream (of `length`
+    /// bytes, if known) has been read up to the next report at `pos`,
+    /// or if it is at EOF
+    pub(crate) fn update<O:Observer>(&mut self, observer:&mut O, pos:&StreamPosition, length:Option<u64>, eof:bool) {
+        if eof || pos.byte() >= self.next {
+            self.next = (pos.byte() / self.every + 1) * self.every;
+            observer.on_progress(*pos, Self::fraction(pos, length));
+        }
+    }
+
+    //zz All done
+}
+
+//a ProgressFn
+//tp ProgressFn
+/// The [Observer] of a [Reader](crate::Reader) that reports its
+/// progress to a callback, attached with
+/// [set_progress](crate::Reader::set_progress)
+///
+/// The callback may borrow local state; the [Reader](crate::Reader)
+/// is `Send` (or `Sync`) if the callback is.
+#[derive(Debug, Clone)]
+pub struct ProgressFn<F>(F);
+
+//ip ProgressFn
+impl <F:FnMut(StreamPosition, Option<f64>)> ProgressFn<F> {
+    //fp new
+    /// Create a new [ProgressFn] for a callback
+    pub fn new(callback:F) -> Self {
+        Self(callback)
+    }
+
+    //mp into_inner
+    /// Get the callback back
+    pub fn into_inner(self) -> F {
+        self.0
+    }
+}
+
+//ip Observer for ProgressFn
+impl <F:FnMut(StreamPosition, Option<f64>)> Observer for ProgressFn<F> {
+    #[inline]
+    fn on_str(&mut self, _pos:StreamPosition, _text:&str) {}
+
+    fn on_progress(&mut self, pos:StreamPosition, fraction:Option<f64>) {
+        (self.0)(pos, fraction)
+    }
+}
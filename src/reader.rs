@@ -1,5 +1,17 @@
 //a Imports
-use crate::{Char, Error, Result, StreamPosition};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "mmap")]
+use alloc::sync::Arc;
+use crate::{ByteSource, Char, Error, IoErrorKind, Result, ReaderState, StreamPosition, StreamSpan, TextStats, Utf8ErrorKind};
+use crate::diagnostic::{self, LineHistory};
+use crate::limits::Limits;
+use crate::progress::{Progress, ProgressFn};
+use crate::{NoObserver, Observer};
+use crate::decoder::{decode, validate, Decoded};
+use crate::lookahead::Lookahead;
+use crate::iterators::{CharsRaw, Positions};
+use crate::scanner::Scan;
 
 //a Constants
 /// [BUFFER_SIZE] is the maximum number of bytes held in the UTF-8
@@ -16,6 +28,89 @@ const BUFFER_SIZE  : usize = 2048;
 /// There is no reason why `BUFFER_SLACK` should be larger than 4.
 const BUFFER_SLACK : usize = 4;
 
+/// [MAPPED_WINDOW_SIZE] is the number of bytes of a memory mapping
+/// that are added to the internal buffer at a time; this bounds the
+/// bytes validated in one step, and gives an
+/// [on_fill](Observer::on_fill) and a progress check for every window
+#[cfg(feature = "mmap")]
+const MAPPED_WINDOW_SIZE : usize = 64 * 1024;
+
+//a Buffer
+//tp Buffer
+/// The internal buffer of a [Reader], which holds the bytes of the
+/// stream from which characters are decoded
+///
+/// The owned buffer is held inline in the [Reader], so that reading
+/// a stream needs no allocation for it.
+#[allow(clippy::large_enum_variant)]
+enum Buffer {
+    /// A buffer that is filled by reading the stream
+    Owned([u8; BUFFER_SIZE]),
+    /// The memory mapping of a file, which is decoded in place; its
+    /// bytes are added to the buffer a window at a time, without
+    /// copying them
+    #[cfg(feature = "mmap")]
+    Mapped(Arc<memmap2::Mmap>),
+}
+
+//ip Buffer
+impl Buffer {
+    //mi compact
+    /// Move the valid data of an owned buffer, from `start` to `end`,
+    /// to the start of the buffer if there is less than
+    /// `BUFFER_SLACK` space before the end of the buffer; returns the
+    /// new `start` and `end`
+    fn compact(&mut self, start:usize, end:usize) -> (usize, usize) {
+        match self {
+            Buffer::Owned(buffer) if start>BUFFER_SIZE-BUFFER_SLACK => {
+                // Move everything down by start
+                buffer.copy_within(start..end, 0);
+                (0, end - start)
+            }
+            _ => (start, end),
+        }
+    }
+
+    //mi free_space
+    /// Borrow the space in the buffer after `end`, which may be filled
+    /// by reading the stream; a mapping has none
+    fn free_space(&mut self, end:usize) -> &mut [u8] {
+        match self {
+            Buffer::Owned(buffer) => &mut buffer[end..],
+            #[cfg(feature = "mmap")]
+            Buffer::Mapped(_) => &mut [],
+        }
+    }
+}
+
+//ip Deref for Buffer
+impl core::ops::Deref for Buffer {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        match self {
+            Buffer::Owned(buffer) => buffer,
+            #[cfg(feature = "mmap")]
+            Buffer::Mapped(map) => map,
+        }
+    }
+}
+
+//a Peeked
+//tp Peeked
+/// The result of peeking ahead in the stream of a [Reader]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Peeked {
+    /// A character, with its position
+    Char(StreamPosition, char),
+    /// The stream has no data
+    NoData,
+    /// The stream is at EOF, which is at the position
+    Eof(StreamPosition),
+    /// An error occurs at the position; it may be taken with
+    /// [take_peeked_error](Reader::take_peeked_error)
+    Error(StreamPosition),
+}
+
 //a Reader
 //tp Reader
 /// The [Reader] provides a stream of characters by UTF-8 decoding a byte
@@ -53,7 +148,7 @@ const BUFFER_SLACK : usize = 4;
 /// [Read](std::io::Read) trait such as a a
 /// [TcpStrema](std::net::TcpStream).
 ///
-pub struct Reader<R:std::io::Read> {
+pub struct Reader<R:ByteSource, O:Observer = NoObserver> {
     /// The reader from which data is to be fetched
     buf_reader  : R,
     /// `eof_on_no_data` defaults to true; it can be set to false to indicate that
@@ -64,24 +159,67 @@ pub struct Reader<R:std::io::Read> {
     /// requested once `eof` is asserted will be `Char::Eof`.
     eof        : bool,
     /// Internal buffer
-    current    : [u8; BUFFER_SIZE],
+    current    : Buffer,
     /// Offset of the first byte within the internal buffer that is valid
     start      : usize,
     /// `Offset of the last byte + 1 within the internal buffer that is valid
     end        : usize,
-    /// `valid_end` is the last byte + 1 within the internal buffer
-    /// used by a valid UTF-8 byte stream that begins with `start` As
-    /// such `start` <= `valid_end` <= `end` If `start` < `valid_end`
-    /// then the bytes in the buffer between the two are a valid UTF-8
-    /// byte stream; this should perhaps be kept in a string inside
-    /// the structure for performance
-    valid_end  : usize,
+    /// Bytes of the stream that precede the data from `buf_reader`
+    /// but do not fit in the internal buffer, from a [Reader] that is
+    /// resumed with more leftover bytes than the buffer holds; these
+    /// fill the buffer before `buf_reader` is read
+    resumed    : Vec<u8>,
+    /// `valid_len` is the number of bytes from `start` within the
+    /// internal buffer that are known to form a valid UTF-8 byte
+    /// stream; as such `start` + `valid_len` <= `end`
+    valid_len  : usize,
     /// position in the file
     stream_pos : StreamPosition,
+    /// Characters that have been decoded by
+    /// [peek_char](Reader::peek_char) (or the scanning methods) but
+    /// not yet returned, with their positions
+    lookahead  : Lookahead,
+    /// An error that occurred while peeking, after the characters in
+    /// `lookahead`, with the position at which it occurred; this is
+    /// returned once those characters have been returned
+    lookahead_error : Option<(StreamPosition, Error)>,
+    /// `scan` is the progress of the last scan for a sequence of
+    /// characters by [read_until](Reader::read_until) or
+    /// [read_until_str](Reader::read_until_str), or for characters
+    /// that satisfy a predicate by [skip_while](Reader::skip_while) or
+    /// [take_while](Reader::take_while), that stopped as the stream
+    /// had no data, so that repeating it continues from where it
+    /// stopped
+    scan       : Option<Scan>,
+    /// `capture` is the start position and text of the characters
+    /// returned since [begin_capture](Reader::begin_capture) was
+    /// invoked, if a capture is in progress
+    capture    : Option<(StreamPosition, String)>,
+    /// `lines` holds the text of the current line and some previous
+    /// lines, if enabled with [keep_lines](Reader::keep_lines), for
+    /// rendering diagnostics
+    lines      : Option<LineHistory>,
+    /// `source_name` is the name of the stream (such as a file name)
+    /// used in rendering diagnostics
+    source_name : Option<String>,
+    /// `stats` holds the statistics of the text read, if enabled with
+    /// [collect_stats](Reader::collect_stats)
+    stats      : Option<TextStats>,
+    /// `limits` are the limits on the data read, for untrusted input
+    limits     : Limits,
+    /// `progress` reports the progress of reading the stream, if
+    /// enabled with [set_progress](Reader::set_progress)
+    progress   : Option<Progress>,
+    /// `length_hint` is the length of the stream in bytes, if known
+    length_hint : Option<u64>,
+    /// `observer` is told of the characters, line ends, errors and
+    /// reads of the stream, if attached with
+    /// [set_observer](Reader::set_observer)
+    observer   : O,
 }
 
-//ip Reader
-impl <R:std::io::Read> Reader<R> {
+//ip Reader - construction
+impl <R:ByteSource> Reader<R> {
 
     //fp new
     /// Returns a new UTF-8 character [Reader], with a stream position
@@ -97,14 +235,163 @@ impl <R:std::io::Read> Reader<R> {
             buf_reader,
             eof_on_no_data : true,
             eof            : false,
-            current        : [0; BUFFER_SIZE],
+            current        : Buffer::Owned([0; BUFFER_SIZE]),
             start          : 0,
             end            : 0,
-            valid_end      : 0,
+            resumed        : Vec::new(),
+            valid_len      : 0,
             stream_pos     : StreamPosition::new(),
+            lookahead      : Lookahead::default(),
+            lookahead_error : None,
+            scan           : None,
+            capture        : None,
+            lines          : None,
+            source_name    : None,
+            stats          : None,
+            limits         : Limits::default(),
+            progress       : None,
+            length_hint    : None,
+            observer       : NoObserver,
         }
     }
 
+    //fp resume
+    /// Returns a new UTF-8 character [Reader] that continues a stream
+    /// from a previous [Reader] that was finished with
+    /// [complete](Reader::complete); the stream position is that of
+    /// the next character, and `leftover` are the unused bytes that
+    /// were returned by `complete`.
+    ///
+    /// The `buf_reader` must provide the data of the stream that
+    /// follows the leftover bytes; for example, a reconnected
+    /// TcpStream that continues from where the previous connection
+    /// stopped. A partial UTF-8 encoding at the end of the leftover
+    /// bytes is completed by the data from the new stream.
+    ///
+    /// There may be any number of leftover bytes (for example,
+    /// `complete` returns all of the characters that have been peeked
+    /// by a long scan); those that do not fit in the internal buffer
+    /// are held until it has room for them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Char, Reader};
+    ///     let mut first : &[u8] = b"a\xe2\x9d";
+    ///     let mut reader = Reader::new(&mut first).set_eof_on_no_data(false);
+    ///     assert_eq!(reader.next_char().unwrap(), Char::Char('a'));
+    ///     assert_eq!(reader.next_char().unwrap(), Char::NoData);
+    ///     let (_, pos, leftover) = reader.complete();
+    ///     let mut second : &[u8] = b"\xa4b";
+    ///     let mut reader = Reader::resume(&mut second, pos, &leftover);
+    ///     assert_eq!(reader.next_char().unwrap(), Char::Char('\u{2764}'));
+    ///     assert_eq!(reader.next_char().unwrap(), Char::Char('b'));
+    ///     assert_eq!(reader.borrow_pos().byte(), 5);
+    /// ```
+    pub fn resume(buf_reader: R, stream_pos:StreamPosition, leftover:&[u8]) -> Self {
+        let mut reader = Self::new(buf_reader);
+        let n = leftover.len().min(BUFFER_SIZE);
+        reader.current.free_space(0)[0..n].copy_from_slice(&leftover[..n]);
+        reader.end        = n;
+        reader.resumed    = leftover[n..].to_vec();
+        reader.stream_pos = stream_pos;
+        reader
+    }
+
+    //fp from_state
+    /// Returns a new UTF-8 character [Reader] that continues a stream
+    /// from a [ReaderState] snapshot taken with
+    /// [state](Reader::state)
+    ///
+    /// The `buf_reader` must provide the data of the original stream
+    /// from byte [stream_offset](ReaderState::stream_offset) onwards.
+    pub fn from_state(buf_reader: R, state:&ReaderState) -> Self {
+        Self::resume(buf_reader, state.position(), state.leftover())
+    }
+
+    //cp set_progress
+    /// Build pattern function to report the progress of reading the
+    /// stream by invoking `callback`, which is attached as the
+    /// [Observer] of the [Reader] (as a [ProgressFn]); see
+    /// [set_progress_interval](Reader::set_progress_interval) for
+    /// when `callback` is invoked with the [StreamPosition] and the
+    /// fraction of the stream that has been read.
+    ///
+    /// The callback may borrow local state, and it need not be `Send`
+    /// or `Sync` (although the [Reader] is then not either); a
+    /// [Reader] with another [Observer] reports the progress to that
+    /// instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::Reader;
+    ///     let text = "a line of text\n".repeat(1000);
+    ///     let mut reports = Vec::new();
+    ///     let mut reader = Reader::new(text.as_bytes())
+    ///         .set_length_hint(text.len() as u64)
+    ///         .set_progress(4096, |pos, fraction| reports.push((pos.byte(), fraction)));
+    ///     let mut s = String::new();
+    ///     reader.read_to_string(&mut s).unwrap();
+    ///     drop(reader);
+    ///     assert_eq!(reports.len(), 4);
+    ///     assert_eq!(*reports.last().unwrap(), (15000, Some(1.0)));
+    /// ```
+    pub fn set_progress<F:FnMut(StreamPosition, Option<f64>)>(self, every:usize, callback:F) -> Reader<R, ProgressFn<F>> {
+        self.set_observer(ProgressFn::new(callback)).set_progress_interval(every)
+    }
+
+    //zz All done
+}
+
+//ip Reader
+impl <R:ByteSource, O:Observer> Reader<R, O> {
+    //cp set_observer
+    /// Build pattern function to attach an [Observer] to the
+    /// [Reader], which is told of the characters, line ends, errors
+    /// and reads of the stream as it is consumed; this replaces any
+    /// observer already attached
+    ///
+    /// The observer may be borrowed with
+    /// [borrow_observer](Reader::borrow_observer); alternatively a
+    /// mutable reference to an observer may be attached.
+    pub fn set_observer<P:Observer>(self, observer:P) -> Reader<R, P> {
+        Reader {
+            buf_reader      : self.buf_reader,
+            eof_on_no_data  : self.eof_on_no_data,
+            eof             : self.eof,
+            current         : self.current,
+            start           : self.start,
+            end             : self.end,
+            resumed         : self.resumed,
+            valid_len       : self.valid_len,
+            stream_pos      : self.stream_pos,
+            lookahead       : self.lookahead,
+            lookahead_error : self.lookahead_error,
+            scan            : self.scan,
+            capture         : self.capture,
+            lines           : self.lines,
+            source_name     : self.source_name,
+            stats           : self.stats,
+            limits          : self.limits,
+            progress        : self.progress,
+            length_hint     : self.length_hint,
+            observer,
+        }
+    }
+
+    //mp borrow_observer
+    /// Borrow the [Observer] of the [Reader]
+    pub fn borrow_observer(&self) -> &O {
+        &self.observer
+    }
+
+    //mp borrow_observer_mut
+    /// Borrow the [Observer] of the [Reader] mutably
+    pub fn borrow_observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
     //cp set_eof_on_no_data
     /// Build pattern function to set the `eof_on_no_data` on the [Reader] to true or false
     ///
@@ -116,20 +403,176 @@ impl <R:std::io::Read> Reader<R> {
         self
     }
 
+    //cp keep_lines
+    /// Build pattern function to keep the text of the current line,
+    /// and up to `previous_lines` lines before it, as the stream is
+    /// read; this text is used by
+    /// [render_diagnostic](Reader::render_diagnostic) to show the
+    /// source of an error.
+    ///
+    /// By default no text is kept, and diagnostics only show the
+    /// position of an error.
+    pub fn keep_lines(mut self, previous_lines:usize) -> Self {
+        self.lines = Some(LineHistory::new(previous_lines));
+        self
+    }
+
+    //cp set_source_name
+    /// Build pattern function to set the name of the stream (such as
+    /// its file name), to be used when rendering diagnostics
+    pub fn set_source_name(mut self, name:&str) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    //cp collect_stats
+    /// Build pattern function to collect [TextStats] for the text read
+    /// from the current stream position, without a second pass; these
+    /// may be borrowed at any time with
+    /// [text_stats](Reader::text_stats)
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{LineEndings, Reader};
+    ///     let mut reader = Reader::new("caf\u{e9}\r\nlonger line\n".as_bytes()).collect_stats();
+    ///     let mut text = String::new();
+    ///     reader.read_to_string(&mut text).unwrap();
+    ///     let stats = reader.text_stats().unwrap();
+    ///     assert_eq!((stats.bytes(), stats.chars(), stats.lines()), (19, 18, 2));
+    ///     assert_eq!(stats.longest_line().0, 11);
+    ///     assert_eq!(stats.non_ascii().1.unwrap().line_position(), (1, 4));
+    ///     let (endings, first_mixed) = stats.line_endings();
+    ///     assert_eq!(endings, LineEndings::Mixed);
+    ///     assert_eq!(first_mixed.unwrap().line_position(), (2, 12));
+    ///     assert!(stats.has_trailing_newline());
+    /// ```
+    pub fn collect_stats(mut self) -> Self {
+        self.stats = Some(TextStats::new(*self.borrow_pos()));
+        self
+    }
+
+    //mp text_stats
+    /// Borrow the [TextStats] of the text read, if enabled with
+    /// [collect_stats](Reader::collect_stats)
+    pub fn text_stats(&self) -> Option<&TextStats> {
+        self.stats.as_ref()
+    }
+
+    //cp set_max_bytes
+    /// Build pattern function to limit the data read to the first
+    /// `max_bytes` bytes of the stream (by byte offset in the stream,
+    /// so this includes any data before a resumed position); reading a
+    /// character or malformed UTF-8 sequence that ends beyond this
+    /// fails with [Error::ByteLimit], and so does an incomplete
+    /// sequence that could only be completed beyond it (without
+    /// reading more of the stream)
+    ///
+    /// This, and the other limits, are intended for reading untrusted
+    /// input: once a limit is exceeded the [Reader] returns the same
+    /// error (at the same position) from every read, without reading
+    /// further from the stream. The characters before the limit are
+    /// returned first, even if they have been peeked beyond.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Char, Error, Reader};
+    ///     let mut reader = Reader::new("ab\u{e9}".as_bytes()).set_max_bytes(3);
+    ///     let mut s = String::new();
+    ///     assert!(matches!(reader.read_to_string(&mut s), Err(Error::ByteLimit(_, 3))));
+    ///     assert_eq!(s, "ab");
+    ///     match reader.next_char() {
+    ///         Err(Error::ByteLimit(pos, 3)) => { assert_eq!(pos.byte(), 2); }
+    ///         x => { panic!("unexpected {:?}", x); }
+    ///     }
+    /// ```
+    pub fn set_max_bytes(mut self, max_bytes:usize) -> Self {
+        self.limits.set_max_bytes(max_bytes);
+        self
+    }
+
+    //cp set_max_line_chars
+    /// Build pattern function to limit the length of every line to
+    /// `max_line_chars` characters (excluding the newline, but
+    /// including a carriage return before it); reading a character
+    /// beyond this fails with [Error::LineLimit]
+    ///
+    /// The limit applies to the line position given by the
+    /// [StreamPosition], so a resumed first line includes the
+    /// characters before the resumed position.
+    pub fn set_max_line_chars(mut self, max_line_chars:usize) -> Self {
+        self.limits.set_max_line_chars(max_line_chars);
+        self
+    }
+
+    //cp set_max_malformed
+    /// Build pattern function to limit the number of malformed UTF-8
+    /// sequences to `max_malformed`; the malformed sequences up to
+    /// this are returned as [Error::MalformedUtf8] as usual, and the
+    /// next one fails with [Error::MalformedLimit]
+    pub fn set_max_malformed(mut self, max_malformed:usize) -> Self {
+        self.limits.set_max_malformed(max_malformed);
+        self
+    }
+
+    //cp set_progress_interval
+    /// Build pattern function to report the progress of reading the
+    /// stream to the [Observer], by invoking its
+    /// [on_progress](Observer::on_progress) with the [StreamPosition]
+    /// of the next character to be read each time the stream has been
+    /// read by another `every` bytes, and once at EOF
+    ///
+    /// The observer is also passed the fraction of the stream that
+    /// has been read, if its length is known from
+    /// [set_length_hint](Reader::set_length_hint) (or, with the `std`
+    /// feature, [length_from_source](Reader::length_from_source)).
+    ///
+    /// The progress is checked only when data is read from the
+    /// underlying stream, which is once per buffer of data (of about
+    /// 2kB), so a report may be later than `every` bytes; if this is
+    /// not set then nothing is checked. A [Reader] without an
+    /// observer may use [set_progress](Reader::set_progress) instead.
+    pub fn set_progress_interval(mut self, every:usize) -> Self {
+        self.progress = Some(Progress::new(every));
+        self
+    }
+
+    //cp set_length_hint
+    /// Build pattern function to set the length of the stream in
+    /// bytes (as a byte offset, so including any data before a
+    /// resumed position), if it is known, for the fraction complete
+    /// reported by [set_progress_interval](Reader::set_progress_interval)
+    pub fn set_length_hint(mut self, length:u64) -> Self {
+        self.length_hint = Some(length);
+        self
+    }
+
+    //mp length_hint
+    /// Get the length of the stream in bytes, if it is known
+    pub fn length_hint(&self) -> Option<u64> {
+        self.length_hint
+    }
+
     //mp set_position
     /// Set the current stream position
     ///
     /// This may be used if, for example, a stream is being restarted;
     /// or if a UTF8 encoded stream occurs in the middle of a byte
-    /// file.
+    /// file. It should not be used when characters have been peeked
+    /// but not yet returned, as they keep their original positions.
     pub fn set_position(&mut self, stream_pos:StreamPosition) {
         self.stream_pos = stream_pos;
     }
 
     //mp set_eof
     /// Set the eof indicator as required; when `true` this will halt
-    /// any new data being returned, and the internal buffer points
-    /// will not change when more data is requested of the [Reader].
+    /// any new data being read from the stream. Characters already in
+    /// the internal buffer are still returned by the [Reader], and if
+    /// the buffer ends with an incomplete UTF-8 sequence then that is
+    /// reported as a [MalformedUtf8](Error::MalformedUtf8) error of
+    /// the kind given by
+    /// [classify_at_eof](Utf8ErrorKind::classify_at_eof).
     ///
     /// This method may be invoked on behalf of a stream that has
     /// completed, but that cannot indicate this by a read operation
@@ -155,26 +598,65 @@ impl <R:std::io::Read> Reader<R> {
     //mp complete
     /// Finish with the stream, returning the buffer handle, the
     /// position of the *next* character in the stream (if there were
-    /// to be one), and any unused buffer data.
+    /// to be one), and any unused buffer data (including the UTF-8
+    /// encoding of any characters that have been peeked).
     pub fn complete(self) -> (R, StreamPosition, Vec<u8>) {
-        (self.buf_reader, self.stream_pos, self.current[self.start..self.end].into())
+        let pos = *self.borrow_pos();
+        let leftover = self.unconsumed_bytes();
+        (self.buf_reader, pos, leftover)
+    }
+
+    //mp state
+    /// Take a snapshot of the state of the [Reader], which is the
+    /// position of the *next* character in the stream and any unused
+    /// buffer data; a new [Reader] can be created from this with
+    /// [from_state](Reader::from_state).
+    pub fn state(&self) -> ReaderState {
+        ReaderState::new(*self.borrow_pos(), &self.unconsumed_bytes())
+    }
+
+    //mi unconsumed_bytes
+    /// Get the bytes of the stream that have been read but not
+    /// consumed - the encoding of any peeked characters and any
+    /// malformed UTF-8 peeked after them, followed by the data in the
+    /// internal buffer and any leftover bytes of a resumed [Reader]
+    /// that are yet to be buffered
+    fn unconsumed_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.lookahead.as_bytes().to_vec();
+        if let Some((_, Error::MalformedUtf8(_, _, malformed))) = &self.lookahead_error {
+            bytes.extend_from_slice(malformed);
+        }
+        bytes.extend_from_slice(self.borrow_buffer());
+        bytes.extend_from_slice(&self.resumed);
+        bytes
     }
 
     //mp drop_buffer
     /// Drop the unconsumed data, for example after it has been borrowed and used, and before [complete](Reader::complete) is invoked
+    ///
+    /// Any characters that have been peeked are also dropped.
     pub fn drop_buffer(&mut self) {
-        self.stream_pos.move_on_bytes(self.end - self.start);
+        self.lookahead.clear();
+        self.lookahead_error = None;
+        self.scan = None;
+        self.stream_pos.move_on_bytes(self.end - self.start + self.resumed.len());
         self.start = self.end;
+        self.valid_len = 0;
+        self.resumed.clear();
     }
 
     //mp buffer_is_empty
-    /// Returns true if the internal buffer is empty
+    /// Returns true if the internal buffer is empty and no characters
+    /// have been peeked
     pub fn buffer_is_empty(&self) -> bool {
-        self.start == self.end
+        self.start == self.end && self.lookahead.is_empty() && self.resumed.is_empty()
     }
 
     //mp borrow_buffer
     /// Borrow the data held in the [Reader]'s buffer.
+    ///
+    /// This does not include the encoding of characters that have
+    /// been peeked, which have been decoded out of the buffer.
     pub fn borrow_buffer(&self) -> &[u8] {
         &self.current[self.start..self.end]
     }
@@ -182,17 +664,120 @@ impl <R:std::io::Read> Reader<R> {
     //mp borrow_pos
     /// Borrow the stream position of the next character to be returned
     pub fn borrow_pos(&self) -> &StreamPosition {
-        &self.stream_pos
+        if let Some(pos) = self.lookahead.front_pos() {
+            pos
+        } else if let Some((pos, _)) = &self.lookahead_error {
+            pos
+        } else {
+            &self.stream_pos
+        }
+    }
+
+    //mp begin_capture
+    /// Start capturing the characters returned by the [Reader], from
+    /// the current stream position; any capture already in progress
+    /// is discarded.
+    ///
+    /// The captured text is held separately from the internal buffer,
+    /// so a capture may span any number of reads from the underlying
+    /// stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::Reader;
+    ///     let mut buf_bytes = "let x = 1;".as_bytes();
+    ///     let mut reader    = Reader::new(&mut buf_bytes);
+    ///     reader.next_char().unwrap(); // skip 'l'
+    ///     reader.begin_capture();
+    ///     reader.next_char().unwrap();
+    ///     reader.next_char().unwrap();
+    ///     let (text, span) = reader.end_capture();
+    ///     assert_eq!(text, "et");
+    ///     assert_eq!(format!("{}", span), "line 1 char 2 to line 1 char 4");
+    /// ```
+    pub fn begin_capture(&mut self) {
+        self.capture = Some((*self.borrow_pos(), String::new()));
+    }
+
+    //mp end_capture
+    /// Complete a capture started with
+    /// [begin_capture](Reader::begin_capture), returning the text of
+    /// the characters returned since then and the [StreamSpan] that
+    /// they cover.
+    ///
+    /// If no capture is in progress then an empty string is returned
+    /// with an empty span at the current stream position.
+    pub fn end_capture(&mut self) -> (String, StreamSpan) {
+        let pos = *self.borrow_pos();
+        match self.capture.take() {
+            Some((start, text)) => (text, StreamSpan::new(start, pos)),
+            None => (String::new(), StreamSpan::new(pos, pos)),
+        }
+    }
+
+    //mp is_capturing
+    /// Returns true if a capture is in progress
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    //mp render_diagnostic
+    /// Render an [Error] from the [Reader] for humans, in the style of
+    /// rustc: the error message, the source name and position, and
+    /// (if [keep_lines](Reader::keep_lines) has been used and the line
+    /// is still held) the text of the line with a caret under the
+    /// character at which the error occurred; malformed UTF-8 in the
+    /// line is shown as U+FFFD.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::Reader;
+    ///     let mut buf_bytes : &[u8] = b"first line\nab\xc0cd\n";
+    ///     let mut reader    = Reader::new(&mut buf_bytes).keep_lines(1).set_source_name("bad.txt");
+    ///     let err = loop {
+    ///         if let Err(e) = reader.next_char() { break e; }
+    ///     };
+    ///     assert_eq!(reader.render_diagnostic(&err),
+    ///         "error: malformed UTF-8 at line 2 char 3: overlong encoding (bytes c0)\n \
+    ///          --> bad.txt:2:3\n  |\n\
+    ///          1 | first line\n\
+    ///          2 | ab\u{fffd}cd\n  |   ^\n");
+    /// ```
+    pub fn render_diagnostic(&self, err:&Error) -> String {
+        self.render_message(err.position(), &err.to_string())
+    }
+
+    //mp render_diagnostic_at
+    /// Render a diagnostic for a message at a [StreamPosition] in the
+    /// same style as [render_diagnostic](Reader::render_diagnostic),
+    /// for errors detected by the user of the [Reader]
+    pub fn render_diagnostic_at(&self, pos:StreamPosition, message:&str) -> String {
+        self.render_message(Some(pos), message)
+    }
+
+    //mi render_message
+    /// Render a message at an optional position
+    fn render_message(&self, pos:Option<StreamPosition>, message:&str) -> String {
+        diagnostic::render(self.source_name.as_deref(),
+                           self.lines.as_ref(),
+                           &self.unconsumed_bytes(),
+                           self.borrow_pos().byte(),
+                           pos,
+                           message)
     }
 
     //mp borrow
     /// Borrow the underlying stream
+    #[allow(clippy::should_implement_trait)]
     pub fn borrow(&self) -> &R {
         &self.buf_reader
     }
 
     //mp borrow_mut
     /// Borrow the underlying stream as a mutable reference
+    #[allow(clippy::should_implement_trait)]
     pub fn borrow_mut(&mut self) -> &mut R {
         &mut self.buf_reader
     }
@@ -202,25 +787,98 @@ impl <R:std::io::Read> Reader<R> {
     /// moving valid data to the start of the buffer first if
     /// required.  This method should only be invoked if more data is
     /// required; it is relatively code-heavy.
+    ///
+    /// A read that is interrupted is retried; a read that would block
+    /// or that times out returns zero bytes without indicating EOF.
+    /// Leftover bytes of a resumed [Reader] that did not fit in the
+    /// buffer are fetched before the underlying stream is read.
     fn fetch_input(&mut self) -> Result<usize> {
-        if self.start>BUFFER_SIZE-BUFFER_SLACK {
-            // Move everything down by self.start
-            let n = self.end - self.start;
-            if n>0 {
-                for i in 0..n {
-                    self.current[i] = self.current[self.start+i];
+        #[cfg(feature = "mmap")]
+        if let Some(n) = self.fetch_mapped() {
+            return Ok(n);
+        }
+        if !self.resumed.is_empty() {
+            self.buffer_to_fill();
+            let n = (BUFFER_SIZE - self.end).min(self.resumed.len());
+            self.current.free_space(self.end)[..n].copy_from_slice(&self.resumed[..n]);
+            self.resumed.drain(..n);
+            self.buffer_filled(n);
+            return Ok(n);
+        }
+        let n = loop {
+            let (stream, buffer) = self.buffer_to_fill();
+            match stream.read(buffer) {
+                Ok(n) => { break n; }
+                Err(e) => {
+                    match e.kind() {
+                        // Interrupted reads are retried
+                        IoErrorKind::Interrupted => (),
+                        // A non-blocking or timed-out stream has no data
+                        // at the moment, but it is not at EOF
+                        IoErrorKind::WouldBlock |
+                        IoErrorKind::TimedOut => { return Ok(0); }
+                        _ => { return Err(e.into()); }
+                    }
                 }
             }
-            self.valid_end -= self.start;
-            self.start      = 0; // == self.start - self.start
-            self.end        = n; // == self.end   - self.start
+        };
+        self.buffer_filled(n);
+        Ok(n)
+    }
+
+    //mp decode_mapping
+    /// Decode the stream from a memory mapping in place, rather than
+    /// reading it into the internal buffer; the mapping holds the
+    /// rest of the stream, and the underlying stream is not read
+    #[cfg(feature = "mmap")]
+    pub(crate) fn decode_mapping(&mut self, map:Arc<memmap2::Mmap>) {
+        self.current   = Buffer::Mapped(map);
+        self.start     = 0;
+        self.end       = 0;
+        self.valid_len = 0;
+    }
+
+    //mi fetch_mapped
+    /// Add the next window of a memory mapping to the internal
+    /// buffer, returning the number of bytes added (which is zero at
+    /// the end of the mapping), or None if the buffer is not a mapping
+    #[cfg(feature = "mmap")]
+    fn fetch_mapped(&mut self) -> Option<usize> {
+        match &self.current {
+            Buffer::Mapped(map) => {
+                let n = (map.len() - self.end).min(MAPPED_WINDOW_SIZE);
+                self.buffer_filled(n);
+                Some(n)
+            }
+            _ => None,
         }
-        let n = self.buf_reader.read( &mut self.current[self.end..BUFFER_SIZE] )?;
+    }
+
+    //mi buffer_to_fill
+    /// Borrow the underlying stream and the free space at the end of
+    /// the internal buffer, moving valid data to the start of the
+    /// buffer first if required, so that the space may be filled by
+    /// reading the stream
+    pub(crate) fn buffer_to_fill(&mut self) -> (&mut R, &mut [u8]) {
+        let (start, end) = self.current.compact(self.start, self.end);
+        self.start = start;
+        self.end   = end;
+        (&mut self.buf_reader, self.current.free_space(self.end))
+    }
+
+    //mi buffer_filled
+    /// Record that `n` bytes have been read into the space returned by
+    /// [buffer_to_fill](Reader::buffer_to_fill); zero bytes indicates
+    /// EOF if the [Reader] is configured to treat it as such
+    pub(crate) fn buffer_filled(&mut self, n:usize) {
         self.end += n;
         if n==0 && self.eof_on_no_data {
             self.eof = true;
         }
-        Ok(n)
+        self.observer.on_fill(n);
+        if let Some(progress) = &mut self.progress {
+            progress.update(&mut self.observer, &self.stream_pos, self.length_hint, n==0 && self.eof);
+        }
     }
 
     //mp next_char
@@ -228,81 +886,417 @@ impl <R:std::io::Read> Reader<R> {
     ///
     /// If there is no data - or not enough data - from the underlying stream, and the [Reader] is operating with the underlying stream *not* indicating EOF with a zero-byte read result, then [NoData](Char::NoData) is returned.
     ///
+    /// [NoData](Char::NoData) is also returned if a read of the
+    /// underlying stream fails with
+    /// [WouldBlock](IoErrorKind::WouldBlock) or
+    /// [TimedOut](IoErrorKind::TimedOut), as for a non-blocking
+    /// TcpStream or one with a read timeout; any partial UTF-8
+    /// encoding is kept until more data arrives. Reads that fail with
+    /// [Interrupted](IoErrorKind::Interrupted) are retried.
+    ///
     /// # Errors
     ///
     /// May return [Error::MalformedUtf8] if the next bytes in the stream do not make a well-formed UTF8 character.
     ///
     /// May return [Error::IoError] if the underlying stream has an IO Error.
     pub fn next_char(&mut self) -> Result<Char> {
-        if self.eof {
-            Ok(Char::Eof)
-        } else if self.start == self.end { // no data present, try reading data
-            if self.fetch_input()? == 0 {
-                Ok(Char::NoData)
+        let (pos, r) = {
+            if let Some((pos, ch)) = self.lookahead.pop_front() {
+                (pos, Ok(Char::Char(ch)))
+            } else if let Some((_, e)) = self.lookahead_error.take() {
+                self.error_returned(&e);
+                return Err(e);
             } else {
-                self.next_char()
+                (self.stream_pos, self.decode_char())
+            }
+        };
+        if let Ok(Char::Char(ch)) = r {
+            if let Some((_, text)) = &mut self.capture {
+                text.push(ch);
+            }
+            if let Some(lines) = &mut self.lines {
+                lines.push(pos.line_position().0, ch);
+            }
+            if let Some(stats) = &mut self.stats {
+                stats.push(pos, ch);
+            }
+            self.observer.on_char(pos, ch);
+            if ch == '\n' {
+                self.observer.on_line_end(pos);
+            }
+        }
+        if let Err(e) = &r {
+            self.error_returned(e);
+        }
+        r
+    }
+
+    //mi error_returned
+    /// Record an error that is being returned, in the line history
+    /// (if it is malformed UTF-8) and with the observer
+    fn error_returned(&mut self, e:&Error) {
+        if let (Some(lines), Error::MalformedUtf8(pos, _, _)) = (&mut self.lines, e) {
+            lines.push_malformed(pos);
+        }
+        self.observer.on_error(e);
+    }
+
+    //mi decode_char
+    /// Decode the next character from the internal buffer, fetching
+    /// more data from the stream if required, and checking it against
+    /// the limits
+    fn decode_char(&mut self) -> Result<Char> {
+        self.limits.check_exceeded()?;
+        let pos = self.stream_pos;
+        match self.decode_next() {
+            Ok(Char::Char(ch)) => {
+                self.limits.check_char(pos, ch)?;
+                Ok(Char::Char(ch))
+            }
+            Err(e @ Error::MalformedUtf8(..)) => {
+                self.limits.check_malformed(pos, self.stream_pos.byte() - pos.byte())?;
+                Err(e)
+            }
+            r => r,
+        }
+    }
+
+    //mi decode_next
+    /// Decode the next character from the internal buffer, fetching
+    /// more data from the stream if required
+    fn decode_next(&mut self) -> Result<Char> {
+        loop {
+            match decode(&self.current[self.start..self.end], &mut self.valid_len) {
+                Decoded::Char(ch, n) => {
+                    self.start += n;
+                    self.stream_pos.move_by(n, ch);
+                    return Ok(Char::Char(ch));
+                }
+                Decoded::Malformed(kind, n) => { // Bad UTF-8 with n bytes used
+                    return self.malformed(kind, n);
+                }
+                Decoded::Incomplete => { // no data, or incomplete UTF-8; fetch more
+                    if !self.eof && self.start < self.end {
+                        // The rest of the sequence must not be beyond the byte limit
+                        self.limits.check_bytes(self.stream_pos, self.end - self.start + 1)?;
+                    }
+                    if !self.eof && self.fetch_input()? > 0 {
+                        // ... got more data so try that!
+                    } else if self.start == self.end {
+                        return if self.eof { Ok(Char::Eof) } else { Ok(Char::NoData) };
+                    } else if self.eof {
+                        // ... and eof reached when incomplete UTF8 is present
+                        let kind = Utf8ErrorKind::classify_at_eof(&self.current[self.start..self.end]);
+                        return self.malformed(kind, self.end-self.start);
+                    } else {
+                        return Ok(Char::NoData);
+                    }
+                }
+            }
+        }
+    }
+
+    //mi malformed
+    /// Consume `n` bytes of malformed UTF-8 from the internal buffer,
+    /// returning an error describing them
+    fn malformed(&mut self, kind:Utf8ErrorKind, n:usize) -> Result<Char> {
+        let r = Error::malformed_utf8(self.stream_pos, kind, &self.current[self.start..self.start+n]);
+        self.stream_pos.move_on_bytes(n);
+        self.start += n;
+        r
+    }
+
+    //mp read_to_string
+    /// Read characters from the stream until EOF, or until the stream
+    /// has no data, appending them to `s`; returns the number of bytes
+    /// appended.
+    ///
+    /// This is much faster than reading the stream with
+    /// [next_char](Reader::next_char), as valid UTF-8 in the internal
+    /// buffer is appended in one step, with ASCII validated and the
+    /// stream position updated many bytes at a time.
+    ///
+    /// # Errors
+    ///
+    /// May return [Error::MalformedUtf8] if the stream contains bytes
+    /// that are not well-formed UTF8; the text before the malformed
+    /// bytes will have been appended to `s`, and reading may continue
+    /// after the error.
+    ///
+    /// May return [Error::IoError] if the underlying stream has an IO Error.
+    pub fn read_to_string(&mut self, s:&mut String) -> Result<usize> {
+        self.read_bulk(s, false)
+    }
+
+    //mp read_line
+    /// Read characters from the stream up to and including the next
+    /// newline, appending them to `s`; returns the number of bytes
+    /// appended, which is zero at EOF.
+    ///
+    /// If the stream reaches EOF or has no data before a newline then
+    /// the characters up to that point are appended; when the stream
+    /// has no data this may be a partial line, and the rest of the
+    /// line is appended by the next call once more data is available.
+    ///
+    /// This uses the same fast path as
+    /// [read_to_string](Reader::read_to_string).
+    ///
+    /// # Errors
+    ///
+    /// As for [read_to_string](Reader::read_to_string)
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::Reader;
+    ///     let mut buf_bytes = "first\nsecond".as_bytes();
+    ///     let mut reader    = Reader::new(&mut buf_bytes);
+    ///     let mut line = String::new();
+    ///     assert_eq!(reader.read_line(&mut line).unwrap(), 6);
+    ///     assert_eq!(line, "first\n");
+    ///     line.clear();
+    ///     assert_eq!(reader.read_line(&mut line).unwrap(), 6);
+    ///     assert_eq!(line, "second");
+    ///     assert_eq!(reader.read_line(&mut line).unwrap(), 0);
+    /// ```
+    pub fn read_line(&mut self, s:&mut String) -> Result<usize> {
+        self.read_bulk(s, true)
+    }
+
+    //mi read_bulk
+    /// Append characters from the stream to `s` until EOF, no data,
+    /// or (if `to_newline` is true) a newline has been appended
+    ///
+    /// Valid UTF-8 in the buffer is handled in one step; anything else
+    /// (peeked characters, a UTF-8 encoding split across reads,
+    /// malformed UTF-8, or an empty buffer) is handled by
+    /// [next_char](Reader::next_char).
+    fn read_bulk(&mut self, s:&mut String, to_newline:bool) -> Result<usize> {
+        let mut total = 0;
+        loop {
+            let peeked = !self.lookahead.is_empty() || self.lookahead_error.is_some();
+            if !peeked && self.valid_len == 0 {
+                if let Ok(n) = validate(&self.current[self.start..self.end]) {
+                    self.valid_len = n;
+                }
             }
-        } else if self.start < self.valid_end { // there is valid UTF-8 data at buffer+self.start
-            let s = {
-                // std::str::from_utf8(&self.current[self.start..self.valid_end]).unwrap()
-                unsafe {
-                    std::str::from_utf8_unchecked(&self.current[self.start..self.valid_end])
+            let allowed = {
+                if peeked || self.valid_len == 0 || self.limits.check_exceeded().is_err() {
+                    0
+                } else {
+                    // core::str::from_utf8(..).unwrap() - the valid bytes are well-formed UTF-8
+                    let text = unsafe {
+                        core::str::from_utf8_unchecked(&self.current[self.start..self.start+self.valid_len])
+                    };
+                    self.limits.allowed(&self.stream_pos, text)
                 }
             };
-            let ch = s.chars().next().unwrap();
-            let n = ch.len_utf8();
-            self.start += n;
-            self.stream_pos.move_by(n, ch);
-            Ok(Char::Char(ch))
-        } else { // there is data but it may or may not be valid
-            match std::str::from_utf8(&self.current[self.start..self.end]) {
-                Ok(_) => { // the data is valid, mark it and the return from there
-                    self.valid_end = self.end;
-                    self.next_char()
+            if allowed > 0 {
+                let bytes = &self.current[self.start..self.start+allowed];
+                let (n, done) = {
+                    match memchr::memchr(b'\n', bytes) {
+                        Some(i) if to_newline => (i+1, true),
+                        _ => (bytes.len(), false),
+                    }
+                };
+                let text = {
+                    // core::str::from_utf8(&bytes[..n]).unwrap()
+                    unsafe {
+                        core::str::from_utf8_unchecked(&bytes[..n])
+                    }
+                };
+                s.push_str(text);
+                if let Some((_, t)) = &mut self.capture {
+                    t.push_str(text);
+                }
+                if let Some(lines) = &mut self.lines {
+                    lines.push_str(self.stream_pos.line_position().0, text);
+                }
+                if let Some(stats) = &mut self.stats {
+                    stats.push_str(self.stream_pos, text);
                 }
-                Err(e) => { // the data is not all valid
-                    if e.valid_up_to()>0 { // some bytes form valid UTF-8 - mark them and return that data
-                        self.valid_end = self.start+e.valid_up_to();
-                        self.next_char()
-                    } else { // no valid data - check it is just incomplete, or an actual error
-                        match e.error_len() {
-                            None => { // incomplete UTF-8 fetch more
-                                match self.fetch_input()? {
-                                    0 => { // ... and eof reached when incomplete UTF8 is present
-                                        if self.eof {
-                                            Error::malformed_utf8(self.stream_pos, self.end-self.start)
-                                        } else {
-                                            Ok(Char::NoData)
-                                        }
-                                    }
-                                    _ => { // ... but got more data so try that!
-                                        self.next_char()
-                                    }
-                                }
-                            }
-                            Some(n) => { // Bad UTF-8 with n bytes used
-                                let r = Error::malformed_utf8(self.stream_pos, n);
-                                self.stream_pos.move_on_bytes(n);
-                                self.start += n;
-                                r
-                            },
+                self.observer.on_str(self.stream_pos, text);
+                self.stream_pos.move_by_str(text);
+                self.start     += n;
+                self.valid_len -= n;
+                total          += n;
+                if done {
+                    return Ok(total);
+                }
+            } else {
+                match self.next_char()? {
+                    Char::Char(ch) => {
+                        s.push(ch);
+                        total += ch.len_utf8();
+                        if to_newline && ch == '\n' {
+                            return Ok(total);
                         }
                     }
-                },
+                    _ => {
+                        return Ok(total);
+                    }
+                }
+            }
+        }
+    }
+
+    //mp peek_char
+    /// Return the next character from the stream without consuming
+    /// it, so that it is returned again by the next
+    /// [next_char](Reader::next_char), or [NoData](Char::NoData) or
+    /// [EOF](Char::Eof) as `next_char` would.
+    ///
+    /// The [StreamPosition] of the next character (from
+    /// [borrow_pos](Reader::borrow_pos)) is not changed by peeking.
+    ///
+    /// # Errors
+    ///
+    /// As for [next_char](Reader::next_char); an error is returned
+    /// (and the malformed bytes consumed) as if by `next_char`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Char, Reader};
+    ///     let mut buf_bytes = "<=".as_bytes();
+    ///     let mut reader    = Reader::new(&mut buf_bytes);
+    ///     assert_eq!(reader.next_char().unwrap(), Char::Char('<'));
+    ///     assert_eq!(reader.peek_char().unwrap(), Char::Char('='));
+    ///     assert_eq!(reader.borrow_pos().line_position(), (1, 2));
+    ///     assert_eq!(reader.next_char().unwrap(), Char::Char('='));
+    /// ```
+    pub fn peek_char(&mut self) -> Result<Char> {
+        match self.peek_nth(0) {
+            Peeked::Char(_, ch) => Ok(Char::Char(ch)),
+            Peeked::NoData      => Ok(Char::NoData),
+            Peeked::Eof(_)      => Ok(Char::Eof),
+            Peeked::Error(_)    => Err(self.take_peeked_error()),
+        }
+    }
+
+    //mi take_peeked_error
+    /// Take the error that was found by [peek_nth](Reader::peek_nth),
+    /// leaving the characters peeked before it to be returned
+    pub(crate) fn take_peeked_error(&mut self) -> Error {
+        let e = self.lookahead_error.take().unwrap().1;
+        self.error_returned(&e);
+        e
+    }
+
+    //mi borrow_peeked_error
+    /// Borrow the error that was found by [peek_nth](Reader::peek_nth)
+    /// and is held after the characters peeked before it, if there is one
+    pub(crate) fn borrow_peeked_error(&self) -> Option<&Error> {
+        self.lookahead_error.as_ref().map(|(_, e)| e)
+    }
+
+    //mi scan_mut
+    /// Borrow the progress of the last scan that stopped as the stream
+    /// had no data, for the scanning methods
+    pub(crate) fn scan_mut(&mut self) -> &mut Option<Scan> {
+        &mut self.scan
+    }
+
+    //mi peek_nth
+    /// Peek at the character `n` characters ahead of the next one,
+    /// decoding more characters into the lookahead as required; an
+    /// error while decoding is held after the lookahead, and is not
+    /// consumed
+    pub(crate) fn peek_nth(&mut self, n:usize) -> Peeked {
+        while self.lookahead.len() <= n {
+            if let Some((pos, _)) = &self.lookahead_error {
+                return Peeked::Error(*pos);
+            }
+            let pos = self.stream_pos;
+            match self.decode_char() {
+                Ok(Char::Char(ch)) => { self.lookahead.push_back(pos, ch); }
+                Ok(Char::NoData)   => { return Peeked::NoData; }
+                Ok(Char::Eof)      => { return Peeked::Eof(pos); }
+                Err(e) => {
+                    self.lookahead_error = Some((pos, e));
+                    return Peeked::Error(pos);
+                }
             }
         }
+        let (pos, ch) = self.lookahead.nth(n);
+        Peeked::Char(pos, ch)
+    }
+
+    //mp positions
+    /// Get an iterator over the characters of the stream with their
+    /// positions, in the manner of [str::char_indices]; the iteration
+    /// ends at EOF or when the stream has no data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Reader, StreamPosition};
+    ///     let mut buf_bytes = "a\n\u{2764}b".as_bytes();
+    ///     let mut reader    = Reader::new(&mut buf_bytes);
+    ///     let chars : Vec<(StreamPosition, char)> = reader.positions().map(|x| x.unwrap()).collect();
+    ///     assert_eq!(chars[2], (StreamPosition::of_blc(2,2,1), '\u{2764}'));
+    ///     assert_eq!(chars[3], (StreamPosition::of_blc(5,2,2), 'b'));
+    /// ```
+    pub fn positions(&mut self) -> Positions<'_, R, O> {
+        Positions::new(self)
+    }
+
+    //mp chars_raw
+    /// Get an iterator over the results of
+    /// [next_char](Reader::next_char), including
+    /// [NoData](Char::NoData), that ends after [Eof](Char::Eof)
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Char, Reader};
+    ///     let mut buf_bytes : &[u8] = b"a\xe2\x9d";
+    ///     let mut reader    = Reader::new(&mut buf_bytes).set_eof_on_no_data(false);
+    ///     let chars : Vec<Char> = reader.chars_raw().take(3).map(|x| x.unwrap()).collect();
+    ///     assert_eq!(chars, vec![Char::Char('a'), Char::NoData, Char::NoData]);
+    /// ```
+    pub fn chars_raw(&mut self) -> CharsRaw<'_, R, O> {
+        CharsRaw::new(self)
     }
 
     //zz All done
 }
 
 
+//ip Reader - length of a seekable source
+#[cfg(feature = "std")]
+impl <R:ByteSource + std::io::Seek, O:Observer> Reader<R, O> {
+    //cp length_from_source
+    /// Build pattern function to set the length hint of the stream
+    /// (see [set_length_hint](Reader::set_length_hint)) from the
+    /// length of a seekable source, such as a [std::fs::File] (for
+    /// which this is the length given by its metadata); if the length
+    /// cannot be found (for example, for a pipe) then the hint is not
+    /// changed
+    ///
+    /// The source is returned to its current offset.
+    ///
+    /// Requires the `std` feature.
+    pub fn length_from_source(mut self) -> Self {
+        let source = &mut self.buf_reader;
+        let length = source.stream_position().and_then(|offset| {
+            let length = source.seek(std::io::SeekFrom::End(0))?;
+            source.seek(std::io::SeekFrom::Start(offset))?;
+            Ok(length)
+        });
+        if let Ok(length) = length {
+            self.length_hint = Some(length);
+        }
+        self
+    }
+}
+
 //ip Iterator for Reader - iterate over characters
 //
 // allow missing doc code examples for this as it *has* an example but
 // rustdoc does not pick it up.
 #[allow(missing_doc_code_examples)]
-impl <'a, R:std::io::Read> Iterator for &'a mut Reader<R> {
+impl <R:ByteSource, O:Observer> Iterator for &mut Reader<R, O> {
     // we will be counting with usize
     type Item = Result<char>;
 
@@ -0,0 +1,63 @@
+//a Imports
+use alloc::vec::Vec;
+use crate::StreamPosition;
+
+//a ReaderState
+//tp ReaderState
+/// A [ReaderState] is a snapshot of the state of a
+/// [Reader](crate::Reader) that is required to restart reading a
+/// stream without losing or duplicating characters: the position of
+/// the next character, and the bytes that have been read from the
+/// underlying stream but not yet decoded.
+///
+/// It is returned by [Reader::state](crate::Reader::state) and used
+/// by [Reader::from_state](crate::Reader::from_state); with the
+/// `serde` feature it may be serialized, for example to checkpoint
+/// long-running ingestion to disk.
+///
+/// When restarting, the new underlying stream must supply the data
+/// that follows the unconsumed bytes - that is, from byte
+/// [stream_offset](ReaderState::stream_offset) of the original
+/// stream.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReaderState {
+    /// Position of the next character to be returned
+    position : StreamPosition,
+    /// Bytes read from the stream but not yet consumed
+    leftover : Vec<u8>,
+}
+
+//ip ReaderState
+impl ReaderState {
+    //fp new
+    /// Construct a new [ReaderState] from the position of the next
+    /// character and the unconsumed bytes that start at that position
+    pub fn new(position:StreamPosition, leftover:&[u8]) -> Self {
+        Self { position, leftover:leftover.into() }
+    }
+
+    //mp position
+    /// Get the position of the next character to be returned
+    #[inline]
+    pub fn position(&self) -> StreamPosition {
+        self.position
+    }
+
+    //mp leftover
+    /// Borrow the bytes read from the stream but not yet consumed
+    #[inline]
+    pub fn leftover(&self) -> &[u8] {
+        &self.leftover
+    }
+
+    //mp stream_offset
+    /// Get the byte offset within the original stream at which
+    /// reading must restart; this is after the unconsumed bytes
+    #[inline]
+    pub fn stream_offset(&self) -> usize {
+        self.position.byte() + self.leftover.len()
+    }
+
+    //zz All done
+}
@@ -0,0 +1,367 @@
+//a Imports
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::reader::Peeked;
+use crate::{ByteSource, Char, Error, Observer, Reader, Result, StreamSpan};
+
+//a Scan
+//tp Scan
+/// The progress of a scan for a sequence of characters, or of
+/// characters that satisfy a predicate, that stopped as the stream
+/// had no data
+#[derive(Debug, Clone)]
+pub(crate) struct Scan {
+    /// The characters scanned for, or `None` for a scan of the
+    /// characters that satisfy a predicate
+    chars   : Option<Vec<char>>,
+    /// The byte offset of the first character of the stream when the
+    /// scan stopped; the progress is only valid if nothing has been
+    /// consumed since
+    front   : usize,
+    /// The number of characters that have been scanned
+    scanned : usize,
+    /// The number of characters of `chars` that match the end of those
+    /// scanned (zero for a scan of a predicate)
+    matched : usize,
+}
+
+//fi prefix_lengths
+/// Find, for each prefix of `chars`, the length of the longest proper
+/// prefix of `chars` that is also a suffix of it (the failure function
+/// of the Knuth-Morris-Pratt string search)
+fn prefix_lengths(chars:&[char]) -> Vec<usize> {
+    let mut lengths = vec![0; chars.len()];
+    let mut matched = 0;
+    for i in 1..chars.len() {
+        while matched > 0 && chars[i] != chars[matched] {
+            matched = lengths[matched-1];
+        }
+        if chars[i] == chars[matched] {
+            matched += 1;
+        }
+        lengths[i] = matched;
+    }
+    lengths
+}
+
+//a Scanning methods
+//ip Reader - scanning methods
+/// Scanning methods, for writing lexers on top of a [Reader]
+///
+/// Each scanning method peeks ahead in the stream as far as it needs
+/// to, and only consumes characters once the scan is complete. If
+/// the stream has no data before then, `Ok(None)` is returned and
+/// nothing is consumed; the characters that have been peeked are
+/// kept, so the scan may simply be repeated when the stream has more
+/// data, and it resumes without losing any partially matched input.
+///
+/// Malformed UTF-8 (or an IO error) is returned in stream order: if
+/// it is at the front of the stream then it is returned (and the
+/// malformed bytes consumed) by a scan, and repeating the scan
+/// continues after the malformed bytes; if a scan finds it after
+/// other characters then the scan stops there, as described for each
+/// method, and it is returned after those characters have been
+/// consumed.
+impl <R:ByteSource, O:Observer> Reader<R, O> {
+    //mp scan_while
+    /// Count the characters at the front of the stream that satisfy
+    /// `pred`, peeking as required
+    ///
+    /// The scan stops before malformed UTF-8 (or an IO error) that is
+    /// not at the front of the stream.
+    ///
+    /// The number of characters that satisfied `pred` is kept by the
+    /// [Reader] if the stream has no data, and a repeated scan
+    /// continues after them if nothing has been consumed since.
+    pub(crate) fn scan_while<F:FnMut(char) -> bool>(&mut self, mut pred:F) -> Result<Option<usize>> {
+        let front = self.borrow_pos().byte();
+        let mut n = {
+            match self.scan_mut().take() {
+                Some(scan) if scan.front == front && scan.chars.is_none() => scan.scanned,
+                _ => 0,
+            }
+        };
+        loop {
+            match self.peek_nth(n) {
+                Peeked::Char(_, ch) if pred(ch) => { n += 1; }
+                Peeked::NoData => {
+                    *self.scan_mut() = Some(Scan { chars:None, front, scanned:n, matched:0 });
+                    return Ok(None);
+                }
+                Peeked::Error(_) if n == 0 => { return Err(self.take_peeked_error()); }
+                _ => { return Ok(Some(n)); }
+            }
+        }
+    }
+
+    //mi scan_error
+    /// Get the error for a scan that finds malformed UTF-8 (or an IO
+    /// error) `n` characters ahead; if `n` is zero this is the error
+    /// itself, otherwise malformed UTF-8 is left to be returned after
+    /// the characters before it and a copy of its error is returned
+    fn scan_error(&mut self, n:usize) -> Error {
+        if n > 0 {
+            if let Some(Error::MalformedUtf8(pos, kind, bytes)) = self.borrow_peeked_error() {
+                return Error::MalformedUtf8(*pos, *kind, bytes.clone());
+            }
+        }
+        self.take_peeked_error()
+    }
+
+    //mi scan_for
+    /// Find the number of characters before the first occurrence of
+    /// `chars` in the stream, peeking as required
+    ///
+    /// Each character is peeked once, even if the scan is repeated
+    /// after the stream has no data: the progress of the scan is kept
+    /// by the [Reader], and used by a repeated scan for the same
+    /// characters if nothing has been consumed since.
+    ///
+    /// An [Error::Expected] is returned if the stream ends first.
+    fn scan_for(&mut self, chars:&[char], description:&str) -> Result<Option<usize>> {
+        let front = self.borrow_pos().byte();
+        let (mut scanned, mut matched) = {
+            match self.scan_mut().take() {
+                Some(scan) if scan.front == front && scan.chars.as_deref() == Some(chars) => (scan.scanned, scan.matched),
+                _ => (0, 0),
+            }
+        };
+        let lengths = prefix_lengths(chars);
+        loop {
+            match self.peek_nth(scanned) {
+                Peeked::Char(_, ch) => {
+                    while matched > 0 && ch != chars[matched] {
+                        matched = lengths[matched-1];
+                    }
+                    if ch == chars[matched] {
+                        matched += 1;
+                    }
+                    scanned += 1;
+                    if matched == chars.len() {
+                        return Ok(Some(scanned - matched));
+                    }
+                }
+                Peeked::NoData => {
+                    *self.scan_mut() = Some(Scan { chars:Some(chars.into()), front, scanned, matched });
+                    return Ok(None);
+                }
+                Peeked::Error(_)   => { return Err(self.scan_error(scanned)); }
+                Peeked::Eof(pos)   => { return Err(Error::Expected(pos, description.into(), Char::Eof)); }
+            }
+        }
+    }
+
+    //mi expect_chars
+    /// Match `chars` at the front of the stream, peeking as required,
+    /// and consume them if they match
+    fn expect_chars(&mut self, chars:&[char], description:&str) -> Result<Option<StreamSpan>> {
+        for (i, want) in chars.iter().enumerate() {
+            match self.peek_nth(i) {
+                Peeked::Char(_, ch) if ch == *want => (),
+                Peeked::Char(pos, ch) => { return Err(Error::Expected(pos, description.into(), Char::Char(ch))); }
+                Peeked::NoData        => { return Ok(None); }
+                Peeked::Error(_)      => { return Err(self.scan_error(i)); }
+                Peeked::Eof(pos)      => { return Err(Error::Expected(pos, description.into(), Char::Eof)); }
+            }
+        }
+        Ok(Some(self.take(chars.len()).1))
+    }
+
+    //mp take
+    /// Consume `n` characters that have been peeked, returning them
+    /// and the span that they cover
+    pub(crate) fn take(&mut self, n:usize) -> (String, StreamSpan) {
+        let start = *self.borrow_pos();
+        let mut text = String::new();
+        for _ in 0..n {
+            if let Ok(Char::Char(ch)) = self.next_char() {
+                text.push(ch);
+            }
+        }
+        (text, StreamSpan::new(start, *self.borrow_pos()))
+    }
+
+    //mp skip_while
+    /// Skip the characters at the front of the stream that satisfy
+    /// `pred`, returning the number skipped; `Ok(None)` is returned if
+    /// the stream has no data before a character that does not
+    /// satisfy `pred` (or EOF).
+    ///
+    /// `pred` is invoked once for each character, even if the scan is
+    /// repeated after the stream has no data: a repeated scan
+    /// continues after the characters that satisfied `pred`, if
+    /// nothing has been consumed since, so it must use the same
+    /// `pred`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a malformed UTF-8 or IO error at the front of the
+    /// stream; the scan stops before one after other characters
+    pub fn skip_while<F:FnMut(char) -> bool>(&mut self, pred:F) -> Result<Option<usize>> {
+        match self.scan_while(pred)? {
+            None => Ok(None),
+            Some(n) => {
+                self.take(n);
+                Ok(Some(n))
+            }
+        }
+    }
+
+    //mp take_while
+    /// Take the characters at the front of the stream that satisfy
+    /// `pred`, returning them and the [StreamSpan] that they cover;
+    /// `Ok(None)` is returned if the stream has no data before a
+    /// character that does not satisfy `pred` (or EOF).
+    ///
+    /// `pred` is invoked once for each character, even if the scan is
+    /// repeated after the stream has no data: a repeated scan
+    /// continues after the characters that satisfied `pred`, if
+    /// nothing has been consumed since, so it must use the same
+    /// `pred`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a malformed UTF-8 or IO error at the front of the
+    /// stream; the scan stops before one after other characters
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::Reader;
+    ///     let mut buf_bytes = "count = 42;".as_bytes();
+    ///     let mut reader    = Reader::new(&mut buf_bytes);
+    ///     let (ident, span) = reader.take_while(|c| c.is_alphanumeric()).unwrap().unwrap();
+    ///     assert_eq!(ident, "count");
+    ///     assert_eq!(span.byte_len(), 5);
+    ///     reader.skip_whitespace().unwrap();
+    ///     reader.expect_char('=').unwrap();
+    ///     reader.skip_whitespace().unwrap();
+    ///     let (value, _) = reader.take_while(|c| c.is_ascii_digit()).unwrap().unwrap();
+    ///     assert_eq!(value, "42");
+    ///     let err = reader.expect_str("+1").unwrap_err();
+    ///     assert_eq!(format!("{}", err), "expected \"+1\" at line 1 char 11, found ';'");
+    /// ```
+    pub fn take_while<F:FnMut(char) -> bool>(&mut self, pred:F) -> Result<Option<(String, StreamSpan)>> {
+        Ok(self.scan_while(pred)?.map(|n| self.take(n)))
+    }
+
+    //mp skip_whitespace
+    /// Skip whitespace characters at the front of the stream, as for
+    /// [skip_while](Reader::skip_while) with [char::is_whitespace]
+    ///
+    /// # Errors
+    ///
+    /// Returns a malformed UTF-8 or IO error at the front of the
+    /// stream; the scan stops before one after other characters
+    pub fn skip_whitespace(&mut self) -> Result<Option<usize>> {
+        self.skip_while(char::is_whitespace)
+    }
+
+    //mp expect_char
+    /// Consume the character `c`, which must be the next character of
+    /// the stream, returning its [StreamSpan]; `Ok(None)` is returned
+    /// if the stream has no data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [Error::Expected] at the position of the next
+    /// character if it is not `c`; the character is not consumed.
+    ///
+    /// Returns a malformed UTF-8 or IO error at the front of the
+    /// stream; malformed UTF-8 found after other characters gives a
+    /// copy of its error, and is returned again after those
+    /// characters by the next reads
+    pub fn expect_char(&mut self, c:char) -> Result<Option<StreamSpan>> {
+        self.expect_chars(&[c], &format!("{:?}", c))
+    }
+
+    //mp expect_str
+    /// Consume the characters of `s`, which must be the next
+    /// characters of the stream, returning their [StreamSpan];
+    /// `Ok(None)` is returned if the stream has no data before the
+    /// match is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [Error::Expected] at the position of the first
+    /// character that does not match; nothing is consumed.
+    ///
+    /// Returns a malformed UTF-8 or IO error at the front of the
+    /// stream; malformed UTF-8 found after other characters gives a
+    /// copy of its error, and is returned again after those
+    /// characters by the next reads
+    pub fn expect_str(&mut self, s:&str) -> Result<Option<StreamSpan>> {
+        let chars : Vec<char> = s.chars().collect();
+        self.expect_chars(&chars, &format!("{:?}", s))
+    }
+
+    //mp read_until
+    /// Take the characters up to the next occurrence of `c`, returning
+    /// them and the [StreamSpan] that they cover, and consuming `c`;
+    /// `Ok(None)` is returned if the stream has no data before `c` is
+    /// found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [Error::Expected] at the end of the stream if it
+    /// ends before `c` is found; nothing is consumed.
+    ///
+    /// Returns a malformed UTF-8 or IO error at the front of the
+    /// stream; malformed UTF-8 found after other characters gives a
+    /// copy of its error, and is returned again after those
+    /// characters by the next reads
+    pub fn read_until(&mut self, c:char) -> Result<Option<(String, StreamSpan)>> {
+        self.read_until_chars(&[c], &format!("{:?}", c))
+    }
+
+    //mp read_until_str
+    /// Take the characters up to the next occurrence of `s`,
+    /// returning them and the [StreamSpan] that they cover, and
+    /// consuming `s`; `Ok(None)` is returned if the stream has no
+    /// data before `s` is found, even if it has a partial match of
+    /// `s`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [Error::Expected] at the end of the stream if it
+    /// ends before `s` is found; nothing is consumed.
+    ///
+    /// Returns a malformed UTF-8 or IO error at the front of the
+    /// stream; malformed UTF-8 found after other characters gives a
+    /// copy of its error, and is returned again after those
+    /// characters by the next reads
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::Reader;
+    ///     let mut buf_bytes = "<!-- a -- comment -->text".as_bytes();
+    ///     let mut reader    = Reader::new(&mut buf_bytes);
+    ///     reader.expect_str("<!--").unwrap();
+    ///     let (comment, _) = reader.read_until_str("-->").unwrap().unwrap();
+    ///     assert_eq!(comment, " a -- comment ");
+    ///     assert_eq!(reader.borrow_pos().line_position(), (1, 22));
+    /// ```
+    pub fn read_until_str(&mut self, s:&str) -> Result<Option<(String, StreamSpan)>> {
+        let chars : Vec<char> = s.chars().collect();
+        self.read_until_chars(&chars, &format!("{:?}", s))
+    }
+
+    //mi read_until_chars
+    /// Take the characters up to the next occurrence of `chars`, and
+    /// consume `chars`
+    fn read_until_chars(&mut self, chars:&[char], description:&str) -> Result<Option<(String, StreamSpan)>> {
+        match self.scan_for(chars, description)? {
+            None => Ok(None),
+            Some(n) => {
+                let r = self.take(n);
+                self.take(chars.len());
+                Ok(Some(r))
+            }
+        }
+    }
+
+    //zz All done
+}
@@ -0,0 +1,51 @@
+//a Imports
+use alloc::sync::Arc;
+use crate::StreamPosition;
+
+//a SourcePosition
+//tp SourcePosition
+/// A [SourcePosition] is a [StreamPosition] within a named source,
+/// such as one of the files read by a [ChainReader](crate::ChainReader)
+///
+/// The name is reference counted, so cloning a [SourcePosition] is cheap.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SourcePosition {
+    /// Name of the source
+    name : Arc<str>,
+    /// Position within the source
+    pos  : StreamPosition,
+}
+
+//ip SourcePosition
+impl SourcePosition {
+    //fp new
+    /// Construct a new [SourcePosition] from a source name and a
+    /// position within that source
+    pub fn new(name:Arc<str>, pos:StreamPosition) -> Self {
+        Self { name, pos }
+    }
+
+    //mp name
+    /// Get the name of the source
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    //mp position
+    /// Get the position within the source
+    #[inline]
+    pub fn position(&self) -> StreamPosition {
+        self.pos
+    }
+
+    //zz All done
+}
+
+//ip Display for SourcePosition
+impl core::fmt::Display for SourcePosition {
+    //mp fmt - format for humans
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{} {}", self.name, self.pos)
+    }
+}
@@ -1,6 +1,10 @@
+//a Imports
+use crate::ascii;
+
 //a StreamPosition
 /// This representes the position of a character within a UTF8 stream
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StreamPosition {
     /// Byte offset from start of file - starting at 0
     byte     : usize,
@@ -10,6 +14,14 @@ pub struct StreamPosition {
     char_ofs : usize,
 }
 
+//ip Default for StreamPosition
+impl Default for StreamPosition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//ip StreamPosition
 impl StreamPosition {
     //fp new
     /// Constructs a new [StreamPosition] for the default of byte 0,
@@ -48,6 +60,39 @@ impl StreamPosition {
         }
     }
 
+    //mp move_by_str
+    /// Move the [StreamPosition] on past a string, counting its
+    /// newlines and characters many bytes at a time
+    #[inline]
+    pub(crate) fn move_by_str(&mut self, s:&str) {
+        let bytes = s.as_bytes();
+        self.byte += bytes.len();
+        match memchr::memrchr(b'\n', bytes) {
+            None => {
+                self.char_ofs += ascii::count_chars(bytes);
+            }
+            Some(last) => {
+                self.line_num += ascii::count_newlines(&bytes[..last]) + 1;
+                self.char_ofs = 1 + ascii::count_chars(&bytes[last+1..]);
+            }
+        }
+    }
+
+    //mp offset_by
+    /// Convert a position that is relative to the start of a chunk of
+    /// a stream (at line 1 char 1) into the position within the
+    /// whole stream, given the position of the start of the chunk;
+    /// the byte offset is not changed
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub(crate) fn offset_by(&self, base:&StreamPosition) -> Self {
+        if self.line_num == 1 {
+            Self { byte:self.byte, line_num:base.line_num, char_ofs:base.char_ofs + self.char_ofs - 1 }
+        } else {
+            Self { byte:self.byte, line_num:base.line_num + self.line_num - 1, char_ofs:self.char_ofs }
+        }
+    }
+
     //mp byte
     /// Find the byte that the [StreamPosition] holds
     #[inline]
@@ -68,11 +113,67 @@ impl StreamPosition {
 
 
 //ip Display for StreamPosition
-impl std::fmt::Display for StreamPosition {
+impl core::fmt::Display for StreamPosition {
     //mp fmt - format for humans
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "line {} char {}", self.line_num, self.char_ofs)
     }
 }
 
+//a StreamSpan
+//tp StreamSpan
+/// A [StreamSpan] is the range of a UTF8 stream between two
+/// [StreamPosition]s; the `start` is the position of the first
+/// character within the span, and the `end` is the position of the
+/// character *after* the span.
+///
+/// It is returned, for example, by the
+/// [end_capture](crate::Reader::end_capture) method.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamSpan {
+    /// Position of the first character in the span
+    start : StreamPosition,
+    /// Position of the character after the span
+    end   : StreamPosition,
+}
+
+//ip StreamSpan
+impl StreamSpan {
+    //fp new
+    /// Construct a new [StreamSpan] from a start and end position
+    pub fn new(start:StreamPosition, end:StreamPosition) -> Self {
+        Self { start, end }
+    }
+
+    //mp start
+    /// Get the position of the first character of the span
+    #[inline]
+    pub fn start(&self) -> StreamPosition {
+        self.start
+    }
 
+    //mp end
+    /// Get the position of the character after the span
+    #[inline]
+    pub fn end(&self) -> StreamPosition {
+        self.end
+    }
+
+    //mp byte_len
+    /// Get the number of bytes of the stream covered by the span
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        self.end.byte - self.start.byte
+    }
+
+    //zz All done
+}
+
+//ip Display for StreamSpan
+impl core::fmt::Display for StreamSpan {
+    //mp fmt - format for humans
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{} to {}", self.start, self.end)
+    }
+}
@@ -0,0 +1,413 @@
+//a Imports
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use regex_automata::dfa::{dense, Automaton, StartKind};
+use regex_automata::nfa::thompson;
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use crate::{ByteSource, Char, Error, NoObserver, Observer, Reader, Result, StreamPosition, StreamSpan};
+
+//a StreamRegex
+//tp StreamRegex
+/// A regular expression compiled for matching against an unbounded
+/// stream of characters from a [Reader], using a pair of DFAs from
+/// the `regex-automata` crate
+///
+/// A forward DFA finds where each match ends, as the characters are
+/// read; a reverse DFA then finds where the match starts, searching
+/// back through the text that is held for the match. Matching follows
+/// the leftmost-first semantics of the `regex` crate.
+///
+/// Requires the `regex` feature.
+#[derive(Debug, Clone)]
+pub struct StreamRegex {
+    /// Unanchored forward DFA, to find the ends of matches
+    forward : dense::DFA<Vec<u32>>,
+    /// Anchored reverse DFA, to find the start of a match from its end
+    reverse : dense::DFA<Vec<u32>>,
+}
+
+//ip StreamRegex
+impl StreamRegex {
+    //fp new
+    /// Compile a regular expression, with the syntax of the `regex`
+    /// crate
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `regex-automata` if the pattern is
+    /// invalid, or cannot be compiled to a DFA (for example, if it
+    /// uses a Unicode word boundary)
+    pub fn new(pattern:&str) -> core::result::Result<Self, Box<dense::BuildError>> {
+        let forward = dense::Builder::new()
+            .configure(dense::Config::new()
+                       .start_kind(StartKind::Unanchored)
+                       .match_kind(MatchKind::LeftmostFirst))
+            .build(pattern)
+            .map_err(Box::new)?;
+        let reverse = dense::Builder::new()
+            .configure(dense::Config::new()
+                       .prefilter(None)
+                       .specialize_start_states(false)
+                       .start_kind(StartKind::Anchored)
+                       .match_kind(MatchKind::All))
+            .thompson(thompson::Config::new().reverse(true))
+            .build(pattern)
+            .map_err(Box::new)?;
+        Ok(Self { forward, reverse })
+    }
+
+    //mi forward_start
+    /// Get the start state of the forward DFA given the byte before
+    /// the search (if any)
+    fn forward_start(&self, look_behind:Option<u8>) -> StateID {
+        let config = start::Config::new().anchored(Anchored::No).look_behind(look_behind);
+        // The DFA has no quit bytes, and supports unanchored searches
+        self.forward.start_state(&config).unwrap()
+    }
+
+    //mi reverse_start
+    /// Get the start state of the reverse DFA given the byte after
+    /// the end of the match (if any)
+    fn reverse_start(&self, look_ahead:Option<u8>) -> StateID {
+        let config = start::Config::new().anchored(Anchored::Yes).look_behind(look_ahead);
+        // The DFA has no quit bytes, and supports anchored searches
+        self.reverse.start_state(&config).unwrap()
+    }
+
+    //zz All done
+}
+
+//a RegexMatcher
+//tp RegexMatcher
+/// The state of a search for the matches of a [StreamRegex] in the
+/// characters read from a [Reader]
+///
+/// The [RegexMatcher] holds a window of the text that has been read
+/// from the [Reader] but that may still be part of a match; this is
+/// emptied whenever the forward DFA returns to its start state (so
+/// that no match can be in progress), and up to the end of each match
+/// that is found. Text that cannot be part of a match is therefore
+/// not held, but a pattern such as `a.*b` holds all of the text after
+/// an `a` until the `b` is found (or the stream ends).
+///
+/// A match is only found once the character after it has been read
+/// (or EOF has been reached), as until then the match may be
+/// extended; any characters read beyond the end of a match are kept
+/// in the window and searched again for the next match.
+///
+/// # Stalls
+///
+/// If the [Reader] returns [NoData](Char::NoData) (for example, a
+/// socket stalls) then [next_match](RegexMatcher::next_match) returns
+/// `Ok(None)`, but the [RegexMatcher] keeps the window and the state
+/// of the DFA. When the stream has more data the search simply
+/// continues; a match that spans the stall is found as a single match,
+/// with the same text and [StreamSpan] as if there had been no stall.
+///
+/// The characters of the [Reader] should only be consumed by the
+/// [RegexMatcher] during a search, as characters consumed by other
+/// methods are not seen by the search.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{Reader, RegexMatcher, StreamRegex};
+///     let regex = StreamRegex::new(r"[0-9]+").unwrap();
+///     let mut reader = Reader::new("ab 12 cd 345".as_bytes());
+///     let mut matcher = RegexMatcher::new(&regex);
+///     let (text, span) = matcher.next_match(&mut reader).unwrap().unwrap();
+///     assert_eq!(text, "12");
+///     assert_eq!(span.start().byte(), 3);
+///     let (text, _) = matcher.next_match(&mut reader).unwrap().unwrap();
+///     assert_eq!(text, "345");
+///     assert!(matcher.next_match(&mut reader).unwrap().is_none());
+/// ```
+#[derive(Debug)]
+pub struct RegexMatcher<'a> {
+    /// The regular expression being matched
+    regex        : &'a StreamRegex,
+    /// Text read that may be part of a match; the search started at
+    /// its first character
+    window       : String,
+    /// Position of the first character of the window
+    window_pos   : StreamPosition,
+    /// The byte before the window, if it is not the start of the stream
+    prev_byte    : Option<u8>,
+    /// Number of bytes of the window fed to the forward DFA
+    fed          : usize,
+    /// State of the forward DFA after the bytes that have been fed
+    state        : StateID,
+    /// Offset in the window of the end of the latest match found by
+    /// the forward DFA
+    match_end    : Option<usize>,
+    /// Set after an empty match, so that the search resumes one
+    /// character after it
+    skip_char    : bool,
+    /// Set after a non-empty match, so that an empty match at its end
+    /// is not found
+    reject_empty : bool,
+    /// An error from the [Reader], returned once the search of the
+    /// text before it is complete
+    error        : Option<Error>,
+}
+
+//ip RegexMatcher
+impl <'a> RegexMatcher<'a> {
+    //fp new
+    /// Create a new [RegexMatcher] to find the matches of `regex`
+    pub fn new(regex:&'a StreamRegex) -> Self {
+        Self {
+            regex,
+            window       : String::new(),
+            window_pos   : StreamPosition::new(),
+            prev_byte    : None,
+            fed          : 0,
+            state        : regex.forward_start(None),
+            match_end    : None,
+            skip_char    : false,
+            reject_empty : false,
+            error        : None,
+        }
+    }
+
+    //mp window_len
+    /// Get the number of bytes of text held in the window, which may
+    /// be part of a match still in progress
+    pub fn window_len(&self) -> usize {
+        self.window.len()
+    }
+
+    //mp next_match
+    /// Find the next match in the stream, returning its text and the
+    /// [StreamSpan] that it covers; `Ok(None)` is returned if the
+    /// [Reader] returns [NoData](Char::NoData) or [Eof](Char::Eof)
+    /// before a match is found.
+    ///
+    /// After `Ok(None)` the reader's [eof](Reader::eof) distinguishes
+    /// the end of the stream from a stall, after which the search may
+    /// be continued when the stream has more data.
+    ///
+    /// # Errors
+    ///
+    /// Returns a malformed UTF-8 or IO error from the [Reader]; this
+    /// ends the text being searched as if it were the end of the
+    /// stream, so any match before it is returned first, and no match
+    /// spans it. The search may be continued after the error.
+    pub fn next_match<R:ByteSource, O:Observer>(&mut self, reader:&mut Reader<R, O>) -> Result<Option<(String, StreamSpan)>> {
+        loop {
+            if self.fed == self.window.len() {
+                let ended = {
+                    if self.error.is_some() {
+                        true
+                    } else {
+                        if self.window.is_empty() {
+                            self.window_pos = *reader.borrow_pos();
+                        }
+                        match reader.next_char() {
+                            Ok(Char::Char(ch)) => { self.window.push(ch); false }
+                            Ok(Char::NoData)   => { return Ok(None); }
+                            Ok(_)              => true,
+                            Err(e)             => { self.error = Some(e); true }
+                        }
+                    }
+                };
+                if ended {
+                    if !self.skip_char {
+                        let state = self.regex.forward.next_eoi_state(self.state);
+                        if self.regex.forward.is_match_state(state) {
+                            self.match_end = Some(self.window.len());
+                        }
+                    }
+                    if let Some(end) = self.match_end {
+                        match self.take_match(end) {
+                            Some(m) => { return Ok(Some(m)); }
+                            None    => { continue; }
+                        }
+                    }
+                    return match self.error.take() {
+                        None => {
+                            // Keep the flags of an empty window, so
+                            // that a match at the end is not repeated
+                            if !self.window.is_empty() {
+                                self.restart(self.fed);
+                            }
+                            Ok(None)
+                        }
+                        Some(e) => {
+                            self.restart(self.fed);
+                            self.prev_byte = None;
+                            self.state     = self.regex.forward_start(None);
+                            Err(e)
+                        }
+                    };
+                }
+            }
+            let ch = self.window[self.fed..].chars().next().unwrap();
+            if self.skip_char {
+                self.restart(ch.len_utf8());
+                continue;
+            }
+            let ofs = self.fed;
+            let bytes = &self.window.as_bytes()[ofs..ofs+ch.len_utf8()];
+            for (i, b) in bytes.iter().enumerate() {
+                self.state = self.regex.forward.next_state(self.state, *b);
+                // Match states are delayed by one byte; only matches
+                // ending at a character boundary are valid
+                if i == 0 && self.regex.forward.is_match_state(self.state) {
+                    self.match_end = Some(ofs);
+                }
+            }
+            self.fed += bytes.len();
+            if self.regex.forward.is_dead_state(self.state) {
+                match self.match_end {
+                    Some(end) => {
+                        if let Some(m) = self.take_match(end) {
+                            return Ok(Some(m));
+                        }
+                    }
+                    None => { self.restart(self.fed); }
+                }
+            } else if self.match_end.is_none()
+                && self.state == self.regex.forward_start(Some(bytes[bytes.len()-1])) {
+                self.restart(self.fed);
+            }
+        }
+    }
+
+    //mi take_match
+    /// Return the match ending at `end` in the window, unless it is an
+    /// empty match at the end of the previous match, in which case the
+    /// search is restarted one character later
+    fn take_match(&mut self, end:usize) -> Option<(String, StreamSpan)> {
+        if end == 0 && self.reject_empty {
+            self.restart(0);
+            self.skip_char = true;
+            None
+        } else {
+            Some(self.found(end))
+        }
+    }
+
+    //mi found
+    /// Return the match ending at `end` in the window, finding its
+    /// start with the reverse DFA, and restart the search after it
+    fn found(&mut self, end:usize) -> (String, StreamSpan) {
+        let bytes = self.window.as_bytes();
+        let reverse = &self.regex.reverse;
+        let mut state = self.regex.reverse_start(bytes.get(end).copied());
+        let mut start = end;
+        let mut dead = false;
+        for i in (0..end).rev() {
+            state = reverse.next_state(state, bytes[i]);
+            if reverse.is_match_state(state) {
+                start = i + 1;
+            } else if reverse.is_dead_state(state) {
+                dead = true;
+                break;
+            }
+        }
+        if !dead {
+            state = {
+                match self.prev_byte {
+                    Some(b) => reverse.next_state(state, b),
+                    None    => reverse.next_eoi_state(state),
+                }
+            };
+            if reverse.is_match_state(state) {
+                start = 0;
+            }
+        }
+        let text : String = self.window[start..end].into();
+        let mut start_pos = self.window_pos;
+        start_pos.move_by_str(&self.window[..start]);
+        let mut end_pos = start_pos;
+        end_pos.move_by_str(&text);
+        self.restart(end);
+        if start == end {
+            self.skip_char = true;
+        } else {
+            self.reject_empty = true;
+        }
+        (text, StreamSpan::new(start_pos, end_pos))
+    }
+
+    //mi restart
+    /// Drop the first `n` bytes of the window, and restart the search
+    /// at the new start of the window
+    fn restart(&mut self, n:usize) {
+        if n > 0 {
+            self.window_pos.move_by_str(&self.window[..n]);
+            self.prev_byte = Some(self.window.as_bytes()[n-1]);
+            self.window.drain(..n);
+        }
+        self.fed          = 0;
+        self.state        = self.regex.forward_start(self.prev_byte);
+        self.match_end    = None;
+        self.skip_char    = false;
+        self.reject_empty = false;
+    }
+
+    //zz All done
+}
+
+//a FindIter
+//tp FindIter
+/// An iterator over the matches of a [StreamRegex] in a [Reader],
+/// yielding the text of each match and the [StreamSpan] that it
+/// covers
+///
+/// This is returned by the [find_iter](Reader::find_iter) method. As
+/// with iterating over the [Reader] itself, the iterator ends when the
+/// [Reader] returns [Eof](Char::Eof) or [NoData](Char::NoData); to
+/// continue a search after the stream stalls (without losing a match
+/// in progress), use a [RegexMatcher] directly.
+pub struct FindIter<'a, 'r, R:ByteSource, O:Observer = NoObserver> {
+    /// The reader being searched
+    reader  : &'a mut Reader<R, O>,
+    /// The state of the search
+    matcher : RegexMatcher<'r>,
+}
+
+//ip Iterator for FindIter
+impl <R:ByteSource, O:Observer> Iterator for FindIter<'_, '_, R, O> {
+    type Item = Result<(String, StreamSpan)>;
+
+    //mp next - return the next match, or None
+    fn next(&mut self) -> Option<Self::Item> {
+        self.matcher.next_match(self.reader).transpose()
+    }
+}
+
+//ip Reader - regex matching
+impl <R:ByteSource, O:Observer> Reader<R, O> {
+    //mp find_iter
+    /// Iterate over the matches of `regex` in the characters of the
+    /// stream, yielding the text of each match and the [StreamSpan]
+    /// that it covers, until the [Reader] returns [Eof](Char::Eof) or
+    /// [NoData](Char::NoData)
+    ///
+    /// Only the text that may be part of a match is held; see
+    /// [RegexMatcher] for details, and for searching a stream that
+    /// may stall.
+    ///
+    /// Requires the `regex` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Reader, StreamRegex};
+    ///     let regex = StreamRegex::new(r"ERROR: [^\n]*").unwrap();
+    ///     let mut reader = Reader::new("ok\nERROR: disk\nok\nERROR: fan\n".as_bytes());
+    ///     let errors : Vec<_> = reader.find_iter(&regex)
+    ///         .map(|m| m.unwrap())
+    ///         .map(|(text, span)| (text, span.start().line_position().0))
+    ///         .collect();
+    ///     assert_eq!(errors, [("ERROR: disk".to_string(), 2), ("ERROR: fan".to_string(), 4)]);
+    /// ```
+    pub fn find_iter<'a, 'r>(&'a mut self, regex:&'r StreamRegex) -> FindIter<'a, 'r, R, O> {
+        FindIter { reader:self, matcher:RegexMatcher::new(regex) }
+    }
+}
@@ -0,0 +1,273 @@
+//a Imports
+use crate::StreamPosition;
+
+//a LineEndings
+//tp LineEndings
+/// The style of line endings seen in a stream by [TextStats]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEndings {
+    /// No line endings have been seen
+    None,
+    /// Every line ends with `\n`
+    Lf,
+    /// Every line ends with `\r\n`
+    CrLf,
+    /// Every line ends with a `\r` that is not followed by `\n`
+    Cr,
+    /// More than one style of line ending has been seen
+    Mixed,
+}
+
+//a TextStats
+//tp TextStats
+/// Statistics of the text read by a [Reader](crate::Reader), in the
+/// style of `wc`: the numbers of bytes, characters and lines, the
+/// longest line, the numbers of non-ASCII, control and whitespace
+/// characters, and the style of line endings
+///
+/// These are collected as the stream is read, if enabled with
+/// [collect_stats](crate::Reader::collect_stats), and may be borrowed
+/// at any time with [text_stats](crate::Reader::text_stats); they
+/// cover every character returned by the [Reader](crate::Reader) (by
+/// any method) since collection was enabled. Malformed UTF-8 is not
+/// counted.
+///
+/// Lines are as for [StreamPosition] - ended by `\n` - and the
+/// position of the first instance of each anomaly (a non-ASCII or
+/// control character, or a line ending of a second style) is
+/// recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextStats {
+    /// Position of the first character
+    start         : StreamPosition,
+    /// Position after the last character
+    end           : StreamPosition,
+    /// Number of bytes of the characters
+    bytes         : usize,
+    /// Number of characters
+    chars         : usize,
+    /// Number of `\n` characters
+    newlines      : usize,
+    /// Number of characters of the current line, excluding a `\r`
+    /// that may be part of its line ending
+    line_len      : usize,
+    /// Position of the start of the current line
+    line_start    : StreamPosition,
+    /// Length and start of the first longest complete line
+    longest       : (usize, StreamPosition),
+    /// Number of non-ASCII characters, and the position of the first
+    non_ascii     : (usize, Option<StreamPosition>),
+    /// Number of control characters other than `\t`, `\n` and `\r`,
+    /// and the position of the first
+    control       : (usize, Option<StreamPosition>),
+    /// Number of whitespace characters, including line endings
+    whitespace    : usize,
+    /// Position of a `\r` that has been seen, if the character after
+    /// it has not yet been seen
+    pending_cr    : Option<StreamPosition>,
+    /// The style of the first line ending
+    first_ending  : LineEndings,
+    /// Position of the first line ending of a different style
+    mixed_ending  : Option<StreamPosition>,
+    /// The last character, if any
+    last          : Option<char>,
+}
+
+//ip TextStats
+impl TextStats {
+    //fp new
+    /// Create a new [TextStats] for text starting at `start`
+    pub(crate) fn new(start:StreamPosition) -> Self {
+        Self {
+            start,
+            end          : start,
+            bytes        : 0,
+            chars        : 0,
+            newlines     : 0,
+            line_len     : 0,
+            line_start   : start,
+            longest      : (0, start),
+            non_ascii    : (0, None),
+            control      : (0, None),
+            whitespace   : 0,
+            pending_cr   : None,
+            first_ending : LineEndings::None,
+            mixed_ending : None,
+            last         : None,
+        }
+    }
+
+    //mi line_ending
+    /// Record a line ending of `style` at `pos`
+    fn line_ending(&mut self, style:LineEndings, pos:StreamPosition) {
+        if self.first_ending == LineEndings::None {
+            self.first_ending = style;
+        } else if self.first_ending != style && self.mixed_ending.is_none() {
+            self.mixed_ending = Some(pos);
+        }
+    }
+
+    //mp push
+    /// Record the character `ch` which was read at `pos`
+    pub(crate) fn push(&mut self, pos:StreamPosition, ch:char) {
+        let after_cr = self.pending_cr.take();
+        match (after_cr, ch) {
+            (Some(cr_pos), '\n') => { self.line_ending(LineEndings::CrLf, cr_pos); }
+            (None, '\n')         => { self.line_ending(LineEndings::Lf, pos); }
+            (Some(cr_pos), _)    => {
+                self.line_ending(LineEndings::Cr, cr_pos);
+                self.line_len += 1;
+            }
+            _ => (),
+        }
+        let n = ch.len_utf8();
+        self.bytes += n;
+        self.chars += 1;
+        self.end = pos;
+        self.end.move_by(n, ch);
+        self.last = Some(ch);
+        if !ch.is_ascii() {
+            self.non_ascii.0 += 1;
+            self.non_ascii.1.get_or_insert(pos);
+        }
+        if ch.is_whitespace() {
+            self.whitespace += 1;
+        }
+        if ch.is_control() && !matches!(ch, '\t' | '\n' | '\r') {
+            self.control.0 += 1;
+            self.control.1.get_or_insert(pos);
+        }
+        match ch {
+            '\n' => {
+                self.newlines += 1;
+                if self.line_len > self.longest.0 {
+                    self.longest = (self.line_len, self.line_start);
+                }
+                self.line_len = 0;
+                self.line_start = self.end;
+            }
+            '\r' => { self.pending_cr = Some(pos); }
+            _     => { self.line_len += 1; }
+        }
+    }
+
+    //mp push_str
+    /// Record the characters of `s`, the first of which was read at
+    /// `pos`
+    pub(crate) fn push_str(&mut self, mut pos:StreamPosition, s:&str) {
+        for ch in s.chars() {
+            self.push(pos, ch);
+            pos.move_by(ch.len_utf8(), ch);
+        }
+    }
+
+    //mp start
+    /// Get the position of the first character covered by the
+    /// statistics
+    pub fn start(&self) -> StreamPosition {
+        self.start
+    }
+
+    //mp end
+    /// Get the position after the last character covered by the
+    /// statistics
+    pub fn end(&self) -> StreamPosition {
+        self.end
+    }
+
+    //mp bytes
+    /// Get the number of bytes of the characters read
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    //mp chars
+    /// Get the number of characters read
+    pub fn chars(&self) -> usize {
+        self.chars
+    }
+
+    //mp newlines
+    /// Get the number of `\n` characters read, as counted by `wc -l`
+    pub fn newlines(&self) -> usize {
+        self.newlines
+    }
+
+    //mp lines
+    /// Get the number of lines, including a last line that has no
+    /// newline
+    pub fn lines(&self) -> usize {
+        if self.chars == 0 || self.last == Some('\n') {
+            self.newlines
+        } else {
+            self.newlines + 1
+        }
+    }
+
+    //mp longest_line
+    /// Get the length in characters of the longest line, and the
+    /// position of its start (the first such line, if more than one
+    /// is the longest); the line ending (`\n` or `\r\n`) is not
+    /// included in the length
+    pub fn longest_line(&self) -> (usize, StreamPosition) {
+        if self.line_len > self.longest.0 {
+            (self.line_len, self.line_start)
+        } else {
+            self.longest
+        }
+    }
+
+    //mp non_ascii
+    /// Get the number of non-ASCII characters, and the position of the
+    /// first
+    pub fn non_ascii(&self) -> (usize, Option<StreamPosition>) {
+        self.non_ascii
+    }
+
+    //mp control
+    /// Get the number of control characters other than `\t`, `\n` and
+    /// `\r` (such as NUL or escape), and the position of the first
+    pub fn control(&self) -> (usize, Option<StreamPosition>) {
+        self.control
+    }
+
+    //mp whitespace
+    /// Get the number of whitespace characters (as for
+    /// [char::is_whitespace]), including line endings
+    pub fn whitespace(&self) -> usize {
+        self.whitespace
+    }
+
+    //mp line_endings
+    /// Get the style of line endings seen, and if this is
+    /// [Mixed](LineEndings::Mixed) the position of the first line
+    /// ending that differs from the first line ending
+    ///
+    /// A `\r` at the end of the text is taken to be a line ending of
+    /// style [Cr](LineEndings::Cr), although it may turn out to be
+    /// the start of a `\r\n` as more text is read.
+    pub fn line_endings(&self) -> (LineEndings, Option<StreamPosition>) {
+        let mixed = {
+            match (self.pending_cr, self.first_ending) {
+                (Some(pos), LineEndings::Lf) | (Some(pos), LineEndings::CrLf) => self.mixed_ending.or(Some(pos)),
+                _ => self.mixed_ending,
+            }
+        };
+        match (mixed, self.first_ending, self.pending_cr) {
+            (Some(pos), _, _)              => (LineEndings::Mixed, Some(pos)),
+            (None, LineEndings::None, Some(_)) => (LineEndings::Cr, None),
+            (None, first, _)               => (first, None),
+        }
+    }
+
+    //mp has_trailing_newline
+    /// Returns true if the text ends with a line ending (`\n` or
+    /// `\r`); this is false for empty text
+    pub fn has_trailing_newline(&self) -> bool {
+        matches!(self.last, Some('\n') | Some('\r'))
+    }
+
+    //zz All done
+}
@@ -1,5 +1,9 @@
 //a Imports
-use crate::StreamPosition;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use crate::{IoError, SourcePosition, StreamPosition};
 
 //a Character result and error
 //tp Char
@@ -15,19 +19,21 @@ pub enum Char {
     /// but this is configured to not be EOF.
     ///
     /// This can only be returned
-    /// by the reader if [crate::Reader::set_eof_on_no_data] has been used
+    /// by the reader if [crate::Reader::set_eof_on_no_data] has been used,
+    /// or if the stream is non-blocking (or has a read timeout) and a
+    /// read would block (or timed out)
     NoData,
     /// [Char](Char::Char) indicates a char of a valid Unicode codepoint decoded
     /// from the stream with UTF8
     Char(char)
 }
 
-//ip std::fmt::Display for Char
-impl std::fmt::Display for Char {
+//ip Display for Char
+impl core::fmt::Display for Char {
     //mp fmt - format a character for display
     /// Display the character as either the character itself, or '<EOF>'
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        use std::fmt::Write; // for f.write_char
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        use core::fmt::Write; // for f.write_char
         match self {
             Char::Eof      => write!(f, "<EOF>"),
             Char::NoData   => write!(f, "<NoData>"),
@@ -39,7 +45,110 @@ impl std::fmt::Display for Char {
 //a Result
 //tp Result
 /// The [Result] type is a result with an error type of [crate::Error]
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
+
+//a Utf8ErrorKind
+//tp Utf8ErrorKind
+/// [Utf8ErrorKind] describes why a sequence of bytes in a stream is
+/// not well-formed UTF-8
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Utf8ErrorKind {
+    /// A continuation byte (0x80 to 0xBF) occurred where the first
+    /// byte of a character was expected
+    UnexpectedContinuation,
+    /// The bytes encode a code point using more bytes than required
+    /// (such as 0xC0 0x80 for U+0000)
+    Overlong,
+    /// The bytes encode a UTF-16 surrogate (U+D800 to U+DFFF), which
+    /// is not a valid Unicode scalar value
+    Surrogate,
+    /// The bytes encode a code point above U+10FFFF
+    OutOfRange,
+    /// A multi-byte sequence was interrupted by a byte that is not a
+    /// continuation byte, or the stream ended after a lone lead byte
+    Incomplete,
+    /// The stream ended part way through a multi-byte sequence, after
+    /// at least one of its continuation bytes
+    TruncatedAtEof,
+    /// A byte (0xF8 to 0xFF) that can never occur in UTF-8
+    InvalidLeadByte,
+}
+
+//ip Utf8ErrorKind
+impl Utf8ErrorKind {
+    //fp classify
+    /// Classify the malformed UTF-8 that starts at the beginning of
+    /// `bytes`, which should include the byte following an invalid
+    /// lead byte if it is available.
+    ///
+    /// This is not used for sequences truncated by the end of the
+    /// stream, which are classified by
+    /// [classify_at_eof](Utf8ErrorKind::classify_at_eof)
+    pub fn classify(bytes:&[u8]) -> Self {
+        let b1 = bytes.get(1).copied().unwrap_or(0);
+        match bytes.first().copied().unwrap_or(0x80) {
+            0x80..=0xbf          => Self::UnexpectedContinuation,
+            0xc0 | 0xc1          => Self::Overlong,
+            0xe0 if b1 < 0xa0    => Self::overlong_or_incomplete(b1),
+            0xed if b1 >= 0xa0   => Self::surrogate_or_incomplete(b1),
+            0xf0 if b1 < 0x90    => Self::overlong_or_incomplete(b1),
+            0xf4 if b1 >= 0x90   => Self::out_of_range_or_incomplete(b1),
+            0xf5..=0xf7          => Self::OutOfRange,
+            0xf8..=0xff          => Self::InvalidLeadByte,
+            _                    => Self::Incomplete,
+        }
+    }
+
+    //fp classify_at_eof
+    /// Classify the incomplete UTF-8 encoding `bytes` that is ended by
+    /// the end of the stream
+    ///
+    /// A lone lead byte is classified as for one followed by a byte
+    /// that is not a continuation byte, as it is more likely to be a
+    /// single-byte character of another encoding (such as an accented
+    /// Latin-1 letter) than a truncated sequence; a lead byte with
+    /// continuation bytes is [TruncatedAtEof](Utf8ErrorKind::TruncatedAtEof)
+    pub fn classify_at_eof(bytes:&[u8]) -> Self {
+        if bytes.len() == 1 {
+            Self::classify(bytes)
+        } else {
+            Self::TruncatedAtEof
+        }
+    }
+
+    //fi overlong_or_incomplete
+    fn overlong_or_incomplete(b1:u8) -> Self {
+        if (0x80..=0xbf).contains(&b1) { Self::Overlong } else { Self::Incomplete }
+    }
+
+    //fi surrogate_or_incomplete
+    fn surrogate_or_incomplete(b1:u8) -> Self {
+        if (0x80..=0xbf).contains(&b1) { Self::Surrogate } else { Self::Incomplete }
+    }
+
+    //fi out_of_range_or_incomplete
+    fn out_of_range_or_incomplete(b1:u8) -> Self {
+        if (0x80..=0xbf).contains(&b1) { Self::OutOfRange } else { Self::Incomplete }
+    }
+
+    //zz All done
+}
+
+//ip Display for Utf8ErrorKind
+impl core::fmt::Display for Utf8ErrorKind {
+    //mp fmt - format a `Utf8ErrorKind` for display
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::UnexpectedContinuation => write!(f, "unexpected continuation byte"),
+            Self::Overlong               => write!(f, "overlong encoding"),
+            Self::Surrogate              => write!(f, "encoded surrogate"),
+            Self::OutOfRange             => write!(f, "code point above U+10FFFF"),
+            Self::Incomplete             => write!(f, "incomplete sequence"),
+            Self::TruncatedAtEof         => write!(f, "sequence truncated at end of stream"),
+            Self::InvalidLeadByte        => write!(f, "invalid lead byte"),
+        }
+    }
+}
 
 //a Error
 //tp Error
@@ -48,49 +157,141 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// set of bytes.
 #[derive(Debug)]
 pub enum Error {
-    /// An [IoError](std::io::Error) is passed through from the underlying read object.
-    IoError(std::io::Error),
+    /// An [IoError](crate::IoError) is passed through from the underlying
+    /// read object (with the `std` feature this is a [std::io::Error]).
+    IoError(IoError),
     /// A [MalformedUtf8](Error::MalformedUtf8) error occurs when a byte stream contains
     /// invalid UTF-8; the position within the stream of the Unicode decoding error is
-    /// recorded, and the number of bytes that form the invalid UTF-8
-    /// encoding (which will be from 1 to 3).
-    MalformedUtf8(StreamPosition, usize),
+    /// recorded, with the kind of the error and the bytes that form the invalid UTF-8
+    /// encoding (which will be from 1 to 3 bytes).
+    MalformedUtf8(StreamPosition, Utf8ErrorKind, Vec<u8>),
+    /// An [InSource](Error::InSource) error is an error that occurred
+    /// within a named source of a [ChainReader](crate::ChainReader) or
+    /// an [IncludeReader](crate::IncludeReader); the position of the
+    /// inner error is within that source.
+    InSource(Arc<str>, Box<Error>),
+    /// An [IncludedFrom](Error::IncludedFrom) error is an error that
+    /// occurred within a source included by an
+    /// [IncludeReader](crate::IncludeReader); the position is that in
+    /// the including source after the include directive.
+    IncludedFrom(SourcePosition, Box<Error>),
+    /// An [IncludeDepth](Error::IncludeDepth) error occurs when an
+    /// [IncludeReader](crate::IncludeReader) would exceed its maximum
+    /// include depth; the position of the attempted include is
+    /// recorded, with the maximum depth.
+    IncludeDepth(StreamPosition, usize),
+    /// An [Expected](Error::Expected) error occurs when a scanning
+    /// method of a [Reader](crate::Reader) (such as
+    /// [expect_str](crate::Reader::expect_str)) does not find what it
+    /// requires; the position at which the expectation failed is
+    /// recorded, with a description of what was expected, and the
+    /// character that was found there (or [Eof](Char::Eof) if the
+    /// stream ended there).
+    Expected(StreamPosition, String, Char),
+    /// An [Unterminated](Error::Unterminated) error occurs when a
+    /// token (such as a quoted string or a block comment) is not
+    /// terminated; the position of the start of the token is
+    /// recorded, with a description of the token.
+    Unterminated(StreamPosition, String),
+    /// An [InvalidToken](Error::InvalidToken) error occurs when a
+    /// token is malformed (such as a string with an invalid escape,
+    /// or a number that is too large); the position of the fault is
+    /// recorded, with a description of it.
+    InvalidToken(StreamPosition, String),
+    /// A [ByteLimit](Error::ByteLimit) error occurs when a
+    /// [Reader](crate::Reader) would read beyond its maximum number of
+    /// bytes (set with [set_max_bytes](crate::Reader::set_max_bytes));
+    /// the position of the character that would exceed it is
+    /// recorded, with the maximum.
+    ByteLimit(StreamPosition, usize),
+    /// A [LineLimit](Error::LineLimit) error occurs when a line read by
+    /// a [Reader](crate::Reader) would exceed its maximum number of
+    /// characters (set with
+    /// [set_max_line_chars](crate::Reader::set_max_line_chars)); the
+    /// position of the first character beyond the maximum is
+    /// recorded, with the maximum.
+    LineLimit(StreamPosition, usize),
+    /// A [MalformedLimit](Error::MalformedLimit) error occurs when a
+    /// [Reader](crate::Reader) finds more malformed UTF-8 sequences
+    /// than its maximum (set with
+    /// [set_max_malformed](crate::Reader::set_max_malformed)); the
+    /// position of the malformed sequence that exceeds it is recorded,
+    /// with the maximum.
+    MalformedLimit(StreamPosition, usize),
 }
 
 //ip Error
 impl Error {
     //mp malformedutf8
     /// Create an error for a malformed UTF8 decoding within a stream
-    pub fn malformed_utf8<T>(stream_pos:StreamPosition, num_bytes:usize) -> Result<T> {
-        Err(Self::MalformedUtf8(stream_pos, num_bytes))
+    pub fn malformed_utf8<T>(stream_pos:StreamPosition, kind:Utf8ErrorKind, bytes:&[u8]) -> Result<T> {
+        Err(Self::MalformedUtf8(stream_pos, kind, bytes.into()))
+    }
+
+    //mp position
+    /// Get the stream position at which the error occurred, if it has one
+    pub fn position(&self) -> Option<StreamPosition> {
+        match self {
+            Error::MalformedUtf8(pos, _, _) => Some(*pos),
+            Error::InSource(_, e) => e.position(),
+            Error::IncludedFrom(_, e) => e.position(),
+            Error::IncludeDepth(pos, _) => Some(*pos),
+            Error::Expected(pos, _, _) => Some(*pos),
+            Error::Unterminated(pos, _) => Some(*pos),
+            Error::InvalidToken(pos, _) => Some(*pos),
+            Error::ByteLimit(pos, _) => Some(*pos),
+            Error::LineLimit(pos, _) => Some(*pos),
+            Error::MalformedLimit(pos, _) => Some(*pos),
+            Error::IoError(_) => None,
+        }
     }
 }
 
-//ip From<std::io::Error> for Error
-/// Provides an implicit conversion from a std::io::Error to a Error
-impl From<std::io::Error> for Error {
-    fn from(e: std::io::Error) -> Self {
+//ip From<IoError> for Error
+/// Provides an implicit conversion from a std::io::Error (or an
+/// [IoError](crate::IoError) without the `std` feature) to a Error
+impl From<IoError> for Error {
+    fn from(e: IoError) -> Self {
         Error::IoError(e)
     }
 }
 
-//ip std::fmt::Display for Error
-impl std::fmt::Display for Error {
+//ip Display for Error
+impl core::fmt::Display for Error {
     //mp fmt - format a `Error` for display
     /// Display the `Error` in a human-readable form
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
-            Error::MalformedUtf8(pos, n) => write!(f, "malformed UTF-8 of {} bytes at {}", n, pos),
+            Error::MalformedUtf8(pos, kind, bytes) => {
+                write!(f, "malformed UTF-8 at {}: {} (bytes", pos, kind)?;
+                for b in bytes {
+                    write!(f, " {:02x}", b)?;
+                }
+                write!(f, ")")
+            }
             Error::IoError(e) => write!(f, "IO error: {}", e),
+            Error::InSource(name, e) => write!(f, "in {}: {}", name, e),
+            Error::IncludedFrom(pos, e) => write!(f, "{}, included from {}", e, pos),
+            Error::IncludeDepth(pos, n) => write!(f, "include depth limit of {} exceeded at {}", n, pos),
+            Error::Expected(pos, expected, Char::Char(ch)) => write!(f, "expected {} at {}, found {:?}", expected, pos, ch),
+            Error::Expected(pos, expected, _) => write!(f, "expected {} at {}, found end of stream", expected, pos),
+            Error::Unterminated(pos, what) => write!(f, "unterminated {} starting at {}", what, pos),
+            Error::InvalidToken(pos, what) => write!(f, "{} at {}", what, pos),
+            Error::ByteLimit(pos, n) => write!(f, "limit of {} bytes exceeded at {}", n, pos),
+            Error::LineLimit(pos, n) => write!(f, "line length limit of {} characters exceeded at {}", n, pos),
+            Error::MalformedLimit(pos, n) => write!(f, "limit of {} malformed UTF-8 sequences exceeded at {}", n, pos),
         }
     }
 }
 
 //ip std::error::Error for Error
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Error::IoError(e) => Some(e),
+            Error::InSource(_, e) => Some(e.as_ref()),
+            Error::IncludedFrom(_, e) => Some(e.as_ref()),
             _ => None,
         }
     }
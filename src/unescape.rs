@@ -0,0 +1,330 @@
+//a Imports
+use alloc::format;
+use crate::lexer::{collect_while, expected, peek, Stop};
+use crate::{ByteSource, Char, Error, NoObserver, Observer, Reader, Result, StreamPosition, StreamSpan};
+
+//a EscapeDialect
+//tp EscapeDialect
+/// The escape sequences decoded by an [Unescape] adapter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeDialect {
+    /// JSON strings: `\"`, `\\`, `\/`, `\b`, `\f`, `\n`, `\r`, `\t`
+    /// and `\uXXXX` with four hexadecimal digits, where characters
+    /// outside the basic multilingual plane are written as a
+    /// surrogate pair `\ud83d\ude00`
+    Json,
+    /// Rust strings: `\n`, `\r`, `\t`, `\\`, `\0`, `\'`, `\"`, `\x`
+    /// with two hexadecimal digits up to `7F`, `\u{...}` with one to
+    /// six hexadecimal digits, and a backslash at the end of a line,
+    /// which skips the newline and the whitespace that follows it
+    Rust,
+    /// C strings: `\a`, `\b`, `\f`, `\n`, `\r`, `\t`, `\v`, `\\`,
+    /// `\'`, `\"`, `\?`, one to three octal digits, `\x` with one or
+    /// more hexadecimal digits, `\uXXXX` and `\UXXXXXXXX`; the octal
+    /// and hexadecimal escapes give the character with that code
+    /// point
+    C,
+}
+
+//a Scanning
+//fi hex_digits
+/// Scan between `min` and `max` hexadecimal digits from `n` ahead in
+/// the stream, returning the index after them and their value
+fn hex_digits<R:ByteSource, O:Observer>(reader:&mut Reader<R, O>, n:usize, min:usize, max:usize) -> core::result::Result<(usize, u32), Stop> {
+    let mut value = 0;
+    let mut end = n;
+    while end - n < max {
+        match peek(reader, end)?.1.and_then(|c| c.to_digit(16)) {
+            Some(d) => { value = (value << 4) | d; end += 1; }
+            None => { break; }
+        }
+    }
+    if end - n < min {
+        let (pos, ch) = peek(reader, end)?;
+        return Err(Stop::Invalid(end, expected(reader, end, pos, "hexadecimal digit", ch)));
+    }
+    Ok((end, value))
+}
+
+//fi code_point
+/// Convert the value of an escape (at `pos`, and ending at `end`
+/// ahead in the stream) to a character
+fn code_point(value:u32, pos:StreamPosition, end:usize) -> core::result::Result<char, Stop> {
+    char::from_u32(value).ok_or_else(|| {
+        let what = {
+            if (0xd800..=0xdfff).contains(&value) {
+                format!("lone surrogate U+{:04X}", value)
+            } else {
+                format!("invalid code point {:#x}", value)
+            }
+        };
+        Stop::Invalid(end, Error::InvalidToken(pos, what))
+    })
+}
+
+//a Unescape
+//tp Unescape
+/// An adapter over a [Reader] that decodes the escape sequences of
+/// string literals, returning each logical character with the
+/// [StreamSpan] of the source text that produced it
+///
+/// This is returned by the [unescape](Reader::unescape) method. The
+/// escapes decoded are given by an [EscapeDialect]; other characters
+/// are returned as they are. With [stop_at](Unescape::stop_at), an
+/// unescaped terminator (such as the closing quote of a string) ends
+/// the text without being consumed.
+///
+/// An escape sequence is only consumed once it is complete; if the
+/// stream has no data first then [NoData](Char::NoData) is returned,
+/// and the escape is decoded when the stream has more data.
+///
+/// # Example
+///
+/// ```
+///     use utf8_read::{Char, EscapeDialect, Reader};
+///     let mut reader = Reader::new(r#"a\n\ud83d\ude00" rest"#.as_bytes());
+///     let mut unescape = reader.unescape(EscapeDialect::Json).stop_at('"');
+///     let mut text = String::new();
+///     let mut spans = Vec::new();
+///     while let (Char::Char(ch), span) = unescape.next_char().unwrap() {
+///         text.push(ch);
+///         spans.push((span.start().byte(), span.end().byte()));
+///     }
+///     assert_eq!(text, "a\n\u{1f600}");
+///     assert_eq!(spans, [(0, 1), (1, 3), (3, 15)]);
+///     assert_eq!(reader.next_char().unwrap(), Char::Char('"'));
+/// ```
+pub struct Unescape<'a, R:ByteSource, O:Observer = NoObserver> {
+    /// The reader of the source text
+    reader     : &'a mut Reader<R, O>,
+    /// The escapes to decode
+    dialect    : EscapeDialect,
+    /// An unescaped character that ends the text
+    terminator : Option<char>,
+}
+
+//ip Unescape
+impl <'a, R:ByteSource, O:Observer> Unescape<'a, R, O> {
+    //fp new
+    /// Create a new [Unescape] adapter
+    pub(crate) fn new(reader:&'a mut Reader<R, O>, dialect:EscapeDialect) -> Self {
+        Self { reader, dialect, terminator:None }
+    }
+
+    //cp stop_at
+    /// Build pattern function to set a character, such as a closing
+    /// quote, that ends the text
+    ///
+    /// When the next character of the stream is `terminator` (and not
+    /// part of an escape sequence), [next_char](Unescape::next_char)
+    /// returns [Eof](Char::Eof) without consuming it, so the next
+    /// character read from the [Reader] is the `terminator` itself.
+    pub fn stop_at(mut self, terminator:char) -> Self {
+        self.terminator = Some(terminator);
+        self
+    }
+
+    //mp next_char
+    /// Return the next logical character with the [StreamSpan] of the
+    /// source text that produced it; or [NoData](Char::NoData) if the
+    /// stream has no data first, or [Eof](Char::Eof) at the end of the
+    /// stream or the terminator (with an empty span at its position)
+    ///
+    /// # Errors
+    ///
+    /// Malformed escapes are reported at their exact position: an
+    /// [Error::Expected] at a character that is not a required digit
+    /// or delimiter (with the escape consumed up to that character),
+    /// or an [Error::InvalidToken] at the backslash of an unknown
+    /// escape, a lone surrogate or an invalid code point (with the
+    /// escape consumed). An escape that is cut short by malformed
+    /// UTF-8 (or an IO error) is invalid: it is consumed up to the
+    /// fault, and an [Error::InvalidToken] is returned at its
+    /// position, followed by the malformed UTF-8 error itself on the
+    /// next call. Malformed UTF-8 outside an escape is returned
+    /// without consuming anything.
+    pub fn next_char(&mut self) -> Result<(Char, StreamSpan)> {
+        loop {
+            let pos = *self.reader.borrow_pos();
+            let scanned = {
+                match peek(self.reader, 0) {
+                    Ok((pos, None)) => { return Ok((Char::Eof, StreamSpan::new(pos, pos))); }
+                    Ok((pos, Some(ch))) if Some(ch) == self.terminator => {
+                        return Ok((Char::Eof, StreamSpan::new(pos, pos)));
+                    }
+                    Ok((_, Some('\\'))) => self.scan_escape(pos),
+                    Ok((_, Some(ch)))   => Ok((1, Some(ch))),
+                    Err(e) => Err(e),
+                }
+            };
+            match scanned {
+                Ok((n, Some(ch))) => {
+                    let (_, span) = self.reader.take(n);
+                    return Ok((Char::Char(ch), span));
+                }
+                Ok((n, None)) => { self.reader.take(n); }
+                Err(Stop::NoData) => { return Ok((Char::NoData, StreamSpan::new(pos, pos))); }
+                Err(Stop::Invalid(n, e)) => {
+                    self.reader.take(n);
+                    return Err(e);
+                }
+                Err(Stop::Failed(e)) => { return Err(e); }
+            }
+        }
+    }
+
+    //mi scan_escape
+    /// Scan the escape at the front of the stream, which is at `pos`,
+    /// returning its length in characters and the character (or
+    /// `None` for an escape that produces no character)
+    fn scan_escape(&mut self, pos:StreamPosition) -> core::result::Result<(usize, Option<char>), Stop> {
+        let (esc_pos, esc) = peek(self.reader, 1)?;
+        let esc = {
+            match esc {
+                None => {
+                    return Err(Stop::Invalid(1, expected(self.reader, 1, esc_pos, "escape character", None)));
+                }
+                Some(esc) => esc,
+            }
+        };
+        let simple = {
+            match (self.dialect, esc) {
+                (_, '\\') | (_, '"')                   => Some(esc),
+                (_, 'n')                               => Some('\n'),
+                (_, 'r')                               => Some('\r'),
+                (_, 't')                               => Some('\t'),
+                (EscapeDialect::Json, '/')             => Some('/'),
+                (EscapeDialect::Json, 'b')             => Some('\u{8}'),
+                (EscapeDialect::Json, 'f')             => Some('\u{c}'),
+                (EscapeDialect::Rust, '\'')            => Some('\''),
+                (EscapeDialect::Rust, '0')             => Some('\0'),
+                (EscapeDialect::C, '\'') | (EscapeDialect::C, '?') => Some(esc),
+                (EscapeDialect::C, 'a')                => Some('\u{7}'),
+                (EscapeDialect::C, 'b')                => Some('\u{8}'),
+                (EscapeDialect::C, 'f')                => Some('\u{c}'),
+                (EscapeDialect::C, 'v')                => Some('\u{b}'),
+                _ => None,
+            }
+        };
+        if simple.is_some() {
+            return Ok((2, simple));
+        }
+        match (self.dialect, esc) {
+            (EscapeDialect::Json, 'u') => self.json_unicode(pos),
+            (EscapeDialect::Rust, 'x') => {
+                let (end, value) = hex_digits(self.reader, 2, 2, 2)?;
+                if value > 0x7f {
+                    return Err(Stop::Invalid(end, Error::InvalidToken(pos, "\\x escape above 0x7f".into())));
+                }
+                Ok((end, Some(value as u8 as char)))
+            }
+            (EscapeDialect::Rust, 'u') => {
+                let (brace_pos, brace) = peek(self.reader, 2)?;
+                if brace != Some('{') {
+                    return Err(Stop::Invalid(2, expected(self.reader, 2, brace_pos, "'{'", brace)));
+                }
+                let (end, value) = hex_digits(self.reader, 3, 1, 6)?;
+                let (brace_pos, brace) = peek(self.reader, end)?;
+                if brace != Some('}') {
+                    return Err(Stop::Invalid(end, expected(self.reader, end, brace_pos, "'}'", brace)));
+                }
+                Ok((end+1, Some(code_point(value, pos, end+1)?)))
+            }
+            (EscapeDialect::Rust, '\n') => {
+                let (end, _) = collect_while(self.reader, 2, |c| matches!(c, ' ' | '\t' | '\n' | '\r'))?;
+                Ok((end, None))
+            }
+            (EscapeDialect::C, '0'..='7') => {
+                // One to three octal digits; no more are peeked
+                let mut value = 0;
+                let mut end = 1;
+                while end < 4 {
+                    match peek(self.reader, end)?.1.and_then(|c| c.to_digit(8)) {
+                        Some(d) => { value = (value << 3) | d; end += 1; }
+                        None => { break; }
+                    }
+                }
+                Ok((end, Some(code_point(value, pos, end)?)))
+            }
+            (EscapeDialect::C, 'x') => {
+                let (end, value) = hex_digits(self.reader, 2, 1, 8)?;
+                Ok((end, Some(code_point(value, pos, end)?)))
+            }
+            (EscapeDialect::C, 'u') => {
+                let (end, value) = hex_digits(self.reader, 2, 4, 4)?;
+                Ok((end, Some(code_point(value, pos, end)?)))
+            }
+            (EscapeDialect::C, 'U') => {
+                let (end, value) = hex_digits(self.reader, 2, 8, 8)?;
+                Ok((end, Some(code_point(value, pos, end)?)))
+            }
+            _ => Err(Stop::Invalid(2, Error::InvalidToken(pos, format!("unknown escape \\{}", esc.escape_debug())))),
+        }
+    }
+
+    //mi json_unicode
+    /// Scan a JSON `\u` escape at the front of the stream, which is at
+    /// `pos`, combining a surrogate pair
+    fn json_unicode(&mut self, pos:StreamPosition) -> core::result::Result<(usize, Option<char>), Stop> {
+        let (end, value) = hex_digits(self.reader, 2, 4, 4)?;
+        if !(0xd800..=0xdbff).contains(&value) {
+            return Ok((end, Some(code_point(value, pos, end)?)));
+        }
+        // A high surrogate must be followed by a low surrogate
+        if peek(self.reader, end)?.1 == Some('\\') && peek(self.reader, end+1)?.1 == Some('u') {
+            let (low_end, low) = hex_digits(self.reader, end+2, 4, 4)?;
+            if (0xdc00..=0xdfff).contains(&low) {
+                let value = 0x10000 + ((value - 0xd800) << 10) + (low - 0xdc00);
+                return Ok((low_end, Some(code_point(value, pos, low_end)?)));
+            }
+        }
+        Err(Stop::Invalid(end, Error::InvalidToken(pos, format!("lone surrogate U+{:04X}", value))))
+    }
+}
+
+//ip Iterator for Unescape
+impl <R:ByteSource, O:Observer> Iterator for Unescape<'_, R, O> {
+    type Item = Result<(char, StreamSpan)>;
+
+    //mp next - return the next logical character and its span, or None
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_char() {
+            Ok((Char::Char(ch), span)) => Some(Ok((ch, span))),
+            Ok(_)                      => None,
+            Err(x)                     => Some(Err(x)),
+        }
+    }
+}
+
+//ip Reader - unescaping
+impl <R:ByteSource, O:Observer> Reader<R, O> {
+    //mp unescape
+    /// Create an [Unescape] adapter, which decodes the escape
+    /// sequences of `dialect` in the characters of the stream,
+    /// returning each logical character with the [StreamSpan] of the
+    /// source text that produced it
+    ///
+    /// As with iterating over the [Reader] itself, iterating over the
+    /// adapter ends when the [Reader] returns [Eof](Char::Eof) or
+    /// [NoData](Char::NoData) (or the terminator is reached).
+    ///
+    /// # Example
+    ///
+    /// ```
+    ///     use utf8_read::{Error, EscapeDialect, Reader};
+    ///     let mut reader = Reader::new(r"tab\there\u{1F600}\u{d800}".as_bytes());
+    ///     let mut unescape = reader.unescape(EscapeDialect::Rust);
+    ///     let text : String = (&mut unescape).map_while(|r| r.ok()).map(|(ch, _)| ch).collect();
+    ///     assert_eq!(text, "tab\there\u{1f600}");
+    ///     // The invalid escape ended the iteration, and is consumed
+    ///     assert!(unescape.next().is_none());
+    ///     let mut reader = Reader::new(r"ok\uD800".as_bytes());
+    ///     match reader.unescape(EscapeDialect::Json).nth(2) {
+    ///         Some(Err(Error::InvalidToken(pos, _))) => { assert_eq!(pos.byte(), 2); }
+    ///         x => { panic!("unexpected {:?}", x); }
+    ///     }
+    /// ```
+    pub fn unescape(&mut self, dialect:EscapeDialect) -> Unescape<'_, R, O> {
+        Unescape::new(self, dialect)
+    }
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Decoder, Error, Reader};
+mod utils;
+use utils::StopStart;
+
+//a Reference decoding
+//tp Decoded
+/// A character or the bytes of a malformed encoding
+#[derive(Debug, PartialEq)]
+enum Decoded {
+    Char(char),
+    Malformed(Vec<u8>),
+}
+
+/// Generate pseudo-random bytes with a xorshift generator; a bias
+/// toward lead and continuation bytes gives many near-valid encodings
+fn random_bytes(seed:u64, n:usize) -> Vec<u8> {
+    let mut x = seed;
+    (0..n).map(|_| {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        match (x >> 8) % 4 {
+            0 => (x as u8) & 0x7f,
+            1 => 0x80 | ((x as u8) & 0x3f),
+            _ => x as u8,
+        }
+    }).collect()
+}
+
+/// Decode bytes with the standard library, splitting malformed
+/// encodings as given by `error_len`
+fn std_decode(mut bytes:&[u8]) -> Vec<Decoded> {
+    let mut result = Vec::new();
+    while !bytes.is_empty() {
+        let (valid, n) = {
+            match std::str::from_utf8(bytes) {
+                Ok(s) => (s, bytes.len()),
+                Err(e) => {
+                    let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap();
+                    (valid, e.valid_up_to() + e.error_len().unwrap_or(bytes.len()-e.valid_up_to()))
+                }
+            }
+        };
+        result.extend(valid.chars().map(Decoded::Char));
+        if n > valid.len() {
+            result.push(Decoded::Malformed(bytes[valid.len()..n].into()));
+        }
+        bytes = &bytes[n..];
+    }
+    result
+}
+
+/// Convert a character result to [Decoded]
+fn to_decoded(r:Result<char, Error>) -> Decoded {
+    match r {
+        Ok(ch) => Decoded::Char(ch),
+        Err(Error::MalformedUtf8(_, _, bytes)) => Decoded::Malformed(bytes),
+        Err(e) => panic!("Unexpected error {}", e),
+    }
+}
+
+//a Tests
+#[test]
+fn test_reader_matches_std() {
+    for seed in 1..20_u64 {
+        let bytes = random_bytes(seed.wrapping_mul(0x9e3779b97f4a7c15), 10_000);
+        let mut buf_bytes = &bytes[..];
+        let mut reader = Reader::new(StopStart::new(&mut buf_bytes, 97)).set_eof_on_no_data(false);
+        let mut result = Vec::new();
+        loop {
+            match reader.next_char() {
+                Ok(Char::Char(ch)) => { result.push(Decoded::Char(ch)); }
+                Ok(Char::Eof) => { break; }
+                Ok(Char::NoData) => {
+                    reader.borrow_mut().kick();
+                    if reader.borrow().is_eof() {
+                        reader.set_eof(true);
+                    }
+                }
+                Err(e) => { result.push(to_decoded(Err(e))); }
+            }
+        }
+        assert_eq!(result, std_decode(&bytes), "Mismatch for seed {}", seed);
+        assert_eq!(reader.borrow_pos().byte(), bytes.len());
+    }
+}
+
+#[test]
+fn test_decoder_matches_std() {
+    for seed in 1..20_u64 {
+        let bytes = random_bytes(seed.wrapping_mul(0x2545f4914f6cdd1d), 10_000);
+        let mut decoder = Decoder::new();
+        let mut result = Vec::new();
+        for chunk in bytes.chunks(13) {
+            decoder.feed(chunk);
+            result.extend((&mut decoder).map(to_decoded));
+        }
+        if let Err(e) = decoder.finish() {
+            result.push(to_decoded(Err(e)));
+        }
+        result.extend((&mut decoder).map(to_decoded));
+        assert_eq!(result, std_decode(&bytes), "Mismatch for seed {}", seed);
+    }
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Error, Reader, StreamPosition};
+mod utils;
+use utils::StopStart;
+
+/// A mixed text with long ASCII runs, multi-byte characters and newlines
+fn mixed_text() -> String {
+    let mut text = String::new();
+    for i in 0..300 {
+        text += &format!("{} a plain ASCII line of text\n", i);
+        if i % 7 == 0 {
+            text += "caf\u{e9} \u{2764}\u{1f600} ";
+        }
+        if i % 11 == 0 {
+            text += "\n\n";
+        }
+    }
+    text
+}
+
+/// Find the position after reading `text` one character at a time
+fn char_by_char_end(text:&str) -> StreamPosition {
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(&mut buf_bytes);
+    for _ in reader.into_iter() {}
+    *reader.borrow_pos()
+}
+
+#[test]
+fn test_read_to_string() {
+    let text = mixed_text();
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(&mut buf_bytes);
+    let mut s = String::new();
+    assert_eq!(reader.read_to_string(&mut s).unwrap(), text.len());
+    assert_eq!(s, text);
+    assert_eq!(*reader.borrow_pos(), char_by_char_end(&text));
+    assert_eq!(reader.read_to_string(&mut s).unwrap(), 0);
+}
+
+#[test]
+fn test_positions_of_substrings() {
+    // Check position tracking against next_char for every prefix
+    // length, so that the word-at-a-time counting is exercised at
+    // every alignment
+    let text = "ab\u{e9}\ncd\u{1f600}ef\u{2764}gh\r\nijklmnopqrstuvwxyz\n\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}xyz";
+    for (i, _) in text.char_indices() {
+        let prefix = &text[..i];
+        let mut buf_bytes = prefix.as_bytes();
+        let mut reader = Reader::new(&mut buf_bytes);
+        let mut s = String::new();
+        reader.read_to_string(&mut s).unwrap();
+        assert_eq!(*reader.borrow_pos(), char_by_char_end(prefix), "Mismatch for prefix {:?}", prefix);
+    }
+}
+
+#[test]
+fn test_read_line_stop_start() {
+    let text = mixed_text();
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(StopStart::new(&mut buf_bytes, 37)).set_eof_on_no_data(false);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    loop {
+        reader.read_line(&mut line).unwrap();
+        if line.ends_with('\n') {
+            lines.push(std::mem::take(&mut line));
+        } else if reader.eof() {
+            break;
+        } else {
+            reader.borrow_mut().kick();
+            if reader.borrow().is_eof() {
+                reader.set_eof(true);
+            }
+        }
+    }
+    assert_eq!(line, "");
+    let expected : Vec<String> = text.split_inclusive('\n').map(|s| s.into()).collect();
+    assert_eq!(lines, expected);
+    assert_eq!(*reader.borrow_pos(), char_by_char_end(&text));
+}
+
+#[test]
+fn test_read_to_string_errors() {
+    let mut buf_bytes : &[u8] = b"abc\ndef\xffgh\n\xe2\x9d";
+    let mut reader = Reader::new(&mut buf_bytes);
+    let mut s = String::new();
+    assert!(matches!(reader.read_to_string(&mut s),
+                     Err(Error::MalformedUtf8(p, _, _)) if p == StreamPosition::of_blc(7,2,4)));
+    assert_eq!(s, "abc\ndef");
+    assert!(matches!(reader.read_to_string(&mut s),
+                     Err(Error::MalformedUtf8(p, _, _)) if p == StreamPosition::of_blc(11,3,1)));
+    assert_eq!(s, "abc\ndefgh\n");
+    assert_eq!(reader.read_to_string(&mut s).unwrap(), 0);
+}
+
+#[test]
+fn test_bulk_capture() {
+    let text : String = (0..10).map(|i| format!("{} a plain ASCII line of text\n", i)).collect();
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(&mut buf_bytes).keep_lines(1);
+    let mut s = String::new();
+    reader.read_line(&mut s).unwrap();
+    reader.begin_capture();
+    reader.read_line(&mut s).unwrap();
+    reader.read_line(&mut s).unwrap();
+    let (captured, span) = reader.end_capture();
+    assert_eq!(captured, "1 a plain ASCII line of text\n2 a plain ASCII line of text\n");
+    assert_eq!(span.start().line_position(), (2, 1));
+    assert_eq!(span.end().line_position(), (4, 1));
+    let pos = StreamPosition::of_blc(0, 3, 3);
+    assert_eq!(reader.render_diagnostic_at(pos, "here"),
+               "error: here\n --> <input>:3:3\n  |\n3 | 2 a plain ASCII line of text\n  |   ^\n");
+}
@@ -0,0 +1,48 @@
+//a Imports
+use utf8_read::{ByteSource, Char, Error, IoError, IoErrorKind, Reader};
+
+//a Uart
+/// A simulated UART, which is not a [std::io::Read], that returns
+/// the scripted results of reads in turn
+struct Uart {
+    script : Vec<Result<&'static [u8], IoErrorKind>>,
+}
+
+impl ByteSource for Uart {
+    fn read(&mut self, buf:&mut [u8]) -> Result<usize, IoError> {
+        if self.script.is_empty() {
+            return Ok(0);
+        }
+        match self.script.remove(0) {
+            Ok(bytes) => {
+                buf[..bytes.len()].copy_from_slice(bytes);
+                Ok(bytes.len())
+            }
+            Err(kind) => Err(kind.into()),
+        }
+    }
+}
+
+#[test]
+fn test_uart() {
+    let uart = Uart { script:vec![Ok(b"a\xc3"),
+                                  Err(IoErrorKind::Interrupted),
+                                  Ok(b"\xa9"),
+                                  Err(IoErrorKind::WouldBlock),
+                                  Ok(b"b"),
+                                  Err(IoErrorKind::Other),
+                                  Ok(b"\n"),
+    ] };
+    let mut reader = Reader::new(uart);
+    assert_eq!(reader.next_char().unwrap(), Char::Char('a'));
+    assert_eq!(reader.next_char().unwrap(), Char::Char('\u{e9}'));
+    assert_eq!(reader.next_char().unwrap(), Char::NoData);
+    assert_eq!(reader.next_char().unwrap(), Char::Char('b'));
+    match reader.next_char() {
+        Err(Error::IoError(e)) => { assert_eq!(e.kind(), IoErrorKind::Other); }
+        x => { panic!("Expected an IO error, got {:?}", x); }
+    }
+    assert_eq!(reader.next_char().unwrap(), Char::Char('\n'));
+    assert_eq!(reader.next_char().unwrap(), Char::Eof);
+    assert_eq!(reader.borrow_pos().line_position(), (2, 1));
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Reader, StreamPosition, StreamSpan};
+mod utils;
+use utils::StopStart;
+
+#[test]
+fn test_span_display() {
+    let span = StreamSpan::new(StreamPosition::of_blc(10,3,5), StreamPosition::of_blc(14,3,9));
+    assert_eq!(format!("{}", span), "line 3 char 5 to line 3 char 9");
+    assert_eq!(span.byte_len(), 4);
+}
+
+#[test]
+fn test_capture_token() {
+    let mut buf_bytes = "fn main\u{1f600}() {}\nx".as_bytes();
+    let mut reader = Reader::new(&mut buf_bytes);
+    assert_eq!(reader.end_capture(), (String::new(), StreamSpan::new(StreamPosition::new(), StreamPosition::new())));
+    for _ in 0..3 { reader.next_char().unwrap(); }
+    reader.begin_capture();
+    for _ in 0..5 { reader.next_char().unwrap(); }
+    assert!(reader.is_capturing());
+    let (text, span) = reader.end_capture();
+    assert!(!reader.is_capturing());
+    assert_eq!(text, "main\u{1f600}");
+    assert_eq!(span.start(), StreamPosition::of_blc(3,1,4));
+    assert_eq!(span.end(),   StreamPosition::of_blc(11,1,9));
+    assert_eq!(span.byte_len(), text.len());
+}
+
+#[test]
+fn test_capture_across_refills() {
+    let mut text = String::new();
+    for i in 0..2000 {
+        text += &format!("{} \u{2764}\n", i);
+    }
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(StopStart::new(&mut buf_bytes, 17)).set_eof_on_no_data(false);
+    reader.begin_capture();
+    loop {
+        match reader.next_char().unwrap() {
+            Char::NoData => {
+                reader.borrow_mut().kick();
+                if reader.borrow().is_eof() {
+                    reader.set_eof(true);
+                }
+            }
+            Char::Eof => { break; }
+            _ => (),
+        }
+    }
+    let (captured, span) = reader.end_capture();
+    assert_eq!(captured, text);
+    assert_eq!(span.byte_len(), text.len());
+    assert_eq!(span.end().line_position(), (2001, 1));
+}
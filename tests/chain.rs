@@ -0,0 +1,102 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{ChainReader, Char, Error, StreamPosition, Utf8ErrorKind};
+mod utils;
+use utils::StopStart;
+
+#[test]
+fn test_positions_restart() {
+    let a : &[u8] = b"ab\ncd";
+    let b : &[u8] = b"";
+    let c : &[u8] = b"e\n\xc0f";
+    let mut reader = ChainReader::new()
+        .with_source("a.txt", a)
+        .with_source("b.txt", b)
+        .with_source("c.txt", c);
+    assert_eq!(format!("{}", reader.source_position()), "a.txt line 1 char 1");
+    let mut text = String::new();
+    let mut errors = Vec::new();
+    loop {
+        match reader.next_char() {
+            Ok(Char::Char(ch)) => {
+                let pos = reader.char_position();
+                text.push(ch);
+                if ch == 'e' {
+                    assert_eq!(reader.source_index(), 2);
+                    assert_eq!(pos.name(), "c.txt");
+                    assert_eq!(pos.position(), StreamPosition::of_blc(0,1,1));
+                }
+            }
+            Ok(_) => { break; }
+            Err(e) => { errors.push(e); }
+        }
+    }
+    assert_eq!(text, "ab\ncde\nf");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(format!("{}", errors[0]),
+               "in c.txt: malformed UTF-8 at line 2 char 1: overlong encoding (bytes c0)");
+    assert_eq!(errors[0].position(), Some(StreamPosition::of_blc(2,2,1)));
+    assert_eq!(format!("{}", reader.source_position()), "c.txt line 2 char 2");
+}
+
+#[test]
+fn test_no_decode_across_sources() {
+    // The first source ends with the first two bytes of U+2764, and
+    // the second starts with the last byte
+    let a : &[u8] = b"x\xe2\x9d";
+    let b : &[u8] = b"\xa4y";
+    let mut reader = ChainReader::new()
+        .with_source("a", a)
+        .with_source("b", b);
+    assert_eq!(reader.next_char().unwrap(), Char::Char('x'));
+    match reader.next_char() {
+        Err(Error::InSource(name, e)) => {
+            assert_eq!(&*name, "a");
+            match *e {
+                Error::MalformedUtf8(pos, Utf8ErrorKind::TruncatedAtEof, bytes) => {
+                    assert_eq!(pos, StreamPosition::of_blc(1,1,2));
+                    assert_eq!(bytes, vec![0xe2, 0x9d]);
+                }
+                e => panic!("Unexpected error {:?}", e),
+            }
+        }
+        x => panic!("Expected truncated error, got {:?}", x),
+    }
+    match reader.next_char() {
+        Err(Error::InSource(name, e)) => {
+            assert_eq!(&*name, "b");
+            assert!(matches!(*e, Error::MalformedUtf8(_, Utf8ErrorKind::UnexpectedContinuation, _)));
+        }
+        x => panic!("Expected continuation error, got {:?}", x),
+    }
+    assert_eq!(reader.next_char().unwrap(), Char::Char('y'));
+    assert_eq!(reader.next_char().unwrap(), Char::Eof);
+}
+
+#[test]
+fn test_stop_start_sources() {
+    let text_a = "Alpha \u{1f600}\n".repeat(50);
+    let text_b = "Beta \u{2764}\n".repeat(50);
+    let mut bytes_a = text_a.as_bytes();
+    let mut bytes_b = text_b.as_bytes();
+    let mut reader = ChainReader::new()
+        .set_eof_on_no_data(false)
+        .with_source("a", StopStart::new(&mut bytes_a, 13))
+        .with_source("b", StopStart::new(&mut bytes_b, 13));
+    let mut text = String::new();
+    loop {
+        match reader.next_char().unwrap() {
+            Char::Char(ch) => { text.push(ch); }
+            Char::NoData => {
+                let stream = reader.borrow_reader_mut().unwrap().borrow_mut();
+                stream.kick();
+                if stream.is_eof() {
+                    reader.set_eof(true);
+                }
+            }
+            Char::Eof => { break; }
+        }
+    }
+    assert_eq!(text, text_a.clone() + &text_b);
+    assert_eq!(format!("{}", reader.source_position()), "b line 51 char 1");
+}
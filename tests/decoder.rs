@@ -0,0 +1,162 @@
+//a Imports
+use utf8_read::{Char, Decoder, Error, Reader, StreamPosition, Utf8ErrorKind};
+
+/// Decode all the characters currently available, returning them and any errors
+fn drain(decoder:&mut Decoder, text:&mut String, errors:&mut Vec<Error>) {
+    loop {
+        match decoder.next_char() {
+            Ok(Char::Char(ch)) => { text.push(ch); }
+            Ok(_) => { break; }
+            Err(e) => { errors.push(e); }
+        }
+    }
+}
+
+#[test]
+fn test_byte_at_a_time() {
+    let src = "Hello \u{2764}\nand \u{1f600}\n".repeat(20);
+    let mut decoder = Decoder::new();
+    let mut text = String::new();
+    let mut errors = Vec::new();
+    for b in src.as_bytes() {
+        decoder.feed(&[*b]);
+        drain(&mut decoder, &mut text, &mut errors);
+        assert!(decoder.borrow_buffer().len() < 4);
+    }
+    decoder.finish().unwrap();
+    assert_eq!(decoder.next_char().unwrap(), Char::Eof);
+    assert_eq!(text, src);
+    assert!(errors.is_empty());
+    assert_eq!(*decoder.borrow_pos(), StreamPosition::of_blc(src.len(), 41, 1));
+}
+
+#[test]
+fn test_errors_and_truncation() {
+    let mut decoder = Decoder::new().set_position(StreamPosition::of_blc(100, 5, 1));
+    let mut text = String::new();
+    let mut errors = Vec::new();
+    decoder.feed(b"a\x80b\xe2");
+    drain(&mut decoder, &mut text, &mut errors);
+    decoder.feed(b"\x9d");
+    drain(&mut decoder, &mut text, &mut errors);
+    assert_eq!(text, "ab");
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], Error::MalformedUtf8(p, Utf8ErrorKind::UnexpectedContinuation, _) if p == StreamPosition::of_blc(101,5,2)));
+    match decoder.finish() {
+        Err(Error::MalformedUtf8(pos, Utf8ErrorKind::TruncatedAtEof, bytes)) => {
+            assert_eq!(pos, StreamPosition::of_blc(103,5,3));
+            assert_eq!(bytes, vec![0xe2, 0x9d]);
+        }
+        x => panic!("Expected truncated error, got {:?}", x),
+    }
+    assert!(decoder.eof());
+    assert_eq!(decoder.next_char().unwrap(), Char::Eof);
+}
+
+#[test]
+fn test_finish_with_pending_chars() {
+    let mut decoder = Decoder::new();
+    decoder.feed(b"xy\xf0\x9f");
+    decoder.finish().unwrap();
+    let text : Vec<_> = decoder.into_iter().collect();
+    assert_eq!(text.len(), 3);
+    assert_eq!(*text[0].as_ref().unwrap(), 'x');
+    assert_eq!(*text[1].as_ref().unwrap(), 'y');
+    assert!(matches!(text[2], Err(Error::MalformedUtf8(_, Utf8ErrorKind::TruncatedAtEof, _))));
+    assert_eq!(decoder.next_char().unwrap(), Char::Eof);
+}
+
+#[test]
+fn test_lone_lead_byte_at_eof() {
+    // A Latin-1 letter at the end of the stream is not a truncated
+    // sequence, whether the end is known before or after it is read
+    let mut decoder = Decoder::new();
+    let text : String = decoder.feed(b"caf\xe9").map(|r| r.unwrap()).collect();
+    assert_eq!(text, "caf");
+    match decoder.finish() {
+        Err(Error::MalformedUtf8(pos, Utf8ErrorKind::Incomplete, bytes)) => {
+            assert_eq!((pos.byte(), bytes), (3, vec![0xe9]));
+        }
+        x => panic!("Expected incomplete error, got {:?}", x),
+    }
+
+    let mut decoder = Decoder::new();
+    decoder.feed(b"caf\xe9");
+    decoder.finish().unwrap();
+    let text : Vec<_> = (&mut decoder).collect();
+    assert_eq!(text.len(), 4);
+    assert!(matches!(&text[3], Err(Error::MalformedUtf8(_, Utf8ErrorKind::Incomplete, _))));
+    assert_eq!(decoder.next_char().unwrap(), Char::Eof);
+
+    let mut bytes : &[u8] = b"caf\xe9";
+    let mut reader = Reader::new(&mut bytes);
+    let results : Vec<_> = reader.into_iter().collect();
+    assert!(matches!(&results[3], Err(Error::MalformedUtf8(_, Utf8ErrorKind::Incomplete, bytes)) if bytes == &[0xe9]));
+    let mut bytes : &[u8] = b"caf\xe9";
+    let mut reader = Reader::new(&mut bytes).set_eof_on_no_data(false);
+    for _ in 0..3 {
+        reader.next_char().unwrap();
+    }
+    assert_eq!(reader.next_char().unwrap(), Char::NoData);
+    reader.set_eof(true);
+    let e = reader.next_char().unwrap_err();
+    assert_eq!(e.to_string(), "malformed UTF-8 at line 1 char 4: incomplete sequence (bytes e9)");
+}
+
+/// A decoded character or malformed UTF-8, with its position
+type Decoded = Result<(StreamPosition, char), (StreamPosition, Utf8ErrorKind, Vec<u8>)>;
+
+/// The characters and errors of `bytes` as decoded by a [Reader]
+fn expected(bytes:&[u8]) -> Vec<Decoded> {
+    let mut reader = Reader::new(bytes);
+    let mut results = Vec::new();
+    loop {
+        let pos = *reader.borrow_pos();
+        match reader.next_char() {
+            Ok(Char::Char(ch)) => { results.push(Ok((pos, ch))); }
+            Ok(_) => { break; }
+            Err(Error::MalformedUtf8(pos, kind, bytes)) => { results.push(Err((pos, kind, bytes))); }
+            Err(e) => { panic!("Unexpected error {}", e); }
+        }
+    }
+    results
+}
+
+#[test]
+fn test_feed_in_place() {
+    let bytes = b"ab\xe2\x9c\x93\xe2\x9c\n\xf0\x9f\x98\x80\x80x\xf4\x90\xc3\xa9\xe0\x80z\xf0\x9f\x98".repeat(3);
+    let expected = expected(&bytes);
+    for n in 1..8 {
+        let mut decoder = Decoder::new();
+        let mut results = Vec::new();
+        for chunk in bytes.chunks(n) {
+            let mut feed = decoder.feed(chunk);
+            loop {
+                let pos = *feed.borrow_pos();
+                match feed.next_char() {
+                    Ok(Char::Char(ch)) => { results.push(Ok((pos, ch))); }
+                    Ok(_) => { break; }
+                    Err(Error::MalformedUtf8(pos, kind, bytes)) => { results.push(Err((pos, kind, bytes))); }
+                    Err(e) => { panic!("Unexpected error {}", e); }
+                }
+            }
+            drop(feed);
+            assert!(decoder.borrow_buffer().len() < 4, "with {} bytes per feed", n);
+        }
+        if let Err(Error::MalformedUtf8(pos, kind, bytes)) = decoder.finish() {
+            results.push(Err((pos, kind, bytes)));
+        }
+        assert_eq!(decoder.next_char().unwrap(), Char::Eof);
+        assert_eq!(results, expected, "with {} bytes per feed", n);
+    }
+
+    // Bytes not decoded by a feed are kept for the next
+    let mut decoder = Decoder::new();
+    let mut feed = decoder.feed(b"xy\xc3");
+    assert_eq!(feed.next().unwrap().unwrap(), 'x');
+    drop(feed);
+    assert_eq!(decoder.borrow_buffer(), b"y\xc3");
+    let text : String = decoder.feed(b"\xa9!").map(|r| r.unwrap()).collect();
+    assert_eq!(text, "y\u{e9}!");
+    assert!(decoder.borrow_buffer().is_empty());
+}
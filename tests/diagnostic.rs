@@ -0,0 +1,98 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Error, Reader, StreamPosition};
+
+fn read_to_error<R:std::io::Read>(reader:&mut Reader<R>) -> Option<Error> {
+    loop {
+        match reader.next_char() {
+            Err(e) => { return Some(e); }
+            Ok(Char::Char(_)) => (),
+            _ => { return None; }
+        }
+    }
+}
+
+#[test]
+fn test_no_lines_kept() {
+    let mut buf_bytes : &[u8] = b"abc\n\x80";
+    let mut reader = Reader::new(&mut buf_bytes);
+    let e = read_to_error(&mut reader).unwrap();
+    assert_eq!(reader.render_diagnostic(&e),
+               "error: malformed UTF-8 at line 2 char 1: unexpected continuation byte (bytes 80)\n --> <input>:2:1\n");
+}
+
+#[test]
+fn test_previous_lines() {
+    let mut buf_bytes : &[u8] = b"line 1\nline 2\nline 3\r\n\tx=\xff;\nline 5\n";
+    let mut reader = Reader::new(&mut buf_bytes).keep_lines(2).set_source_name("a.cfg");
+    let e = read_to_error(&mut reader).unwrap();
+    assert_eq!(reader.render_diagnostic(&e),
+               "error: malformed UTF-8 at line 4 char 4: invalid lead byte (bytes ff)\n \
+                --> a.cfg:4:4\n  |\n\
+                2 | line 2\n\
+                3 | line 3\n\
+                4 | \tx=\u{fffd};\n  | \t  ^\n");
+}
+
+#[test]
+fn test_malformed_in_line() {
+    // Malformed UTF-8 is shown as U+FFFD; it does not advance the
+    // character position, so the caret is under it only for its error
+    let mut buf_bytes : &[u8] = b"ab\xffcd\xe2\x82ef\nxyz";
+    let mut reader = Reader::new(&mut buf_bytes).keep_lines(1);
+    let first = read_to_error(&mut reader).unwrap();
+    let c = *reader.borrow_pos();
+    assert_eq!(reader.render_diagnostic(&first),
+               "error: malformed UTF-8 at line 1 char 3: invalid lead byte (bytes ff)\n \
+                --> <input>:1:3\n  |\n\
+                1 | ab\u{fffd}cd\u{fffd}ef\n  |   ^\n");
+    let second = read_to_error(&mut reader).unwrap();
+    let e = *reader.borrow_pos();
+    assert_eq!(reader.render_diagnostic_at(c, "at c"),
+               "error: at c\n --> <input>:1:3\n  |\n1 | ab\u{fffd}cd\u{fffd}ef\n  |    ^\n");
+    assert_eq!(reader.render_diagnostic_at(e, "at e"),
+               "error: at e\n --> <input>:1:5\n  |\n1 | ab\u{fffd}cd\u{fffd}ef\n  |       ^\n");
+    while reader.next_char().unwrap() != Char::Char('x') {}
+    assert_eq!(reader.render_diagnostic(&second),
+               "error: malformed UTF-8 at line 1 char 5: incomplete sequence (bytes e2 82)\n \
+                --> <input>:1:5\n  |\n\
+                1 | ab\u{fffd}cd\u{fffd}ef\n  |      ^\n");
+
+    // Malformed UTF-8 that has not yet been read is also shown
+    let mut buf_bytes : &[u8] = b"ab\xffcd";
+    let mut reader = Reader::new(&mut buf_bytes).keep_lines(0);
+    reader.next_char().unwrap();
+    let pos = StreamPosition::of_blc(3, 1, 3);
+    assert_eq!(reader.render_diagnostic_at(pos, "at c"),
+               "error: at c\n --> <input>:1:3\n  |\n1 | ab\u{fffd}cd\n  |    ^\n");
+}
+
+#[test]
+fn test_user_error() {
+    let text = "0123456789\n".repeat(12);
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(&mut buf_bytes).keep_lines(0);
+    while reader.borrow_pos().line_position() != (10, 6) {
+        reader.next_char().unwrap();
+    }
+    let pos = *reader.borrow_pos();
+    assert_eq!(reader.render_diagnostic_at(pos, "unexpected '5'"),
+               "error: unexpected '5'\n  --> <input>:10:6\n   |\n10 | 0123456789\n   |      ^\n");
+
+    // Line 9 is no longer held, so only the position is shown
+    let pos = StreamPosition::of_blc(80,9,1);
+    assert_eq!(reader.render_diagnostic_at(pos, "too late"),
+               "error: too late\n --> <input>:9:1\n");
+
+    // A user-built position with character 0 puts the caret at the start
+    let pos = StreamPosition::of_blc(99,10,0);
+    assert_eq!(reader.render_diagnostic_at(pos, "odd"),
+               "error: odd\n  --> <input>:10:0\n   |\n10 | 0123456789\n   | ^\n");
+}
+
+#[test]
+fn test_io_error() {
+    let reader = Reader::new(std::io::empty());
+    let e = Error::IoError(std::io::Error::other("gone"));
+    assert_eq!(reader.render_diagnostic(&e), "error: IO error: gone\n");
+}
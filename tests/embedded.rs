@@ -0,0 +1,137 @@
+#![cfg(feature = "embedded-io")]
+//a Imports
+use utf8_read::{Char, EmbeddedIo, Error, IoErrorKind, Reader};
+
+//a Serial
+/// An error from the simulated serial port
+#[derive(Debug)]
+struct SerialError(embedded_io::ErrorKind);
+
+impl embedded_io::Error for SerialError {
+    fn kind(&self) -> embedded_io::ErrorKind {
+        self.0
+    }
+}
+
+/// A simulated serial port, that returns the scripted results of
+/// reads in turn, and then zero bytes as it has no more data
+struct Serial {
+    script : Vec<Result<&'static [u8], embedded_io::ErrorKind>>,
+}
+
+impl Serial {
+    fn read_script(&mut self, buf:&mut [u8]) -> Result<usize, SerialError> {
+        if self.script.is_empty() {
+            return Ok(0);
+        }
+        match self.script.remove(0) {
+            Ok(bytes) => {
+                buf[..bytes.len()].copy_from_slice(bytes);
+                Ok(bytes.len())
+            }
+            Err(kind) => Err(SerialError(kind)),
+        }
+    }
+}
+
+impl embedded_io::ErrorType for Serial {
+    type Error = SerialError;
+}
+
+impl embedded_io::Read for Serial {
+    fn read(&mut self, buf:&mut [u8]) -> Result<usize, SerialError> {
+        self.read_script(buf)
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl embedded_io_async::Read for Serial {
+    async fn read(&mut self, buf:&mut [u8]) -> Result<usize, SerialError> {
+        self.read_script(buf)
+    }
+}
+
+/// The script for a serial port, with the characters and results
+/// expected from it
+fn script() -> Vec<Result<&'static [u8], embedded_io::ErrorKind>> {
+    vec![Ok(b"a\xe2\x9c"),
+         Err(embedded_io::ErrorKind::Interrupted),
+         Ok(b"\x93"),
+         Err(embedded_io::ErrorKind::TimedOut),
+         Ok(b"b"),
+         Err(embedded_io::ErrorKind::BrokenPipe),
+         Ok(b"c"),
+    ]
+}
+
+/// Check the result of reading a character from the script
+fn check(r:utf8_read::Result<Char>, expected:Option<Char>) {
+    match (r, expected) {
+        (Ok(c), Some(e)) => { assert_eq!(c, e); }
+        (Err(Error::IoError(e)), None) => { assert_eq!(e.kind(), IoErrorKind::BrokenPipe); }
+        (r, e) => { panic!("Expected {:?}, got {:?}", e, r); }
+    }
+}
+
+/// Expected results from the script, with `None` for an IO error
+const EXPECTED : [Option<Char>; 7] = [Some(Char::Char('a')),
+                                      Some(Char::Char('\u{2713}')),
+                                      Some(Char::NoData),
+                                      Some(Char::Char('b')),
+                                      None,
+                                      Some(Char::Char('c')),
+                                      Some(Char::NoData),
+];
+
+#[test]
+fn test_embedded_io() {
+    let mut reader = Reader::new(EmbeddedIo::new(Serial { script:script() })).set_eof_on_no_data(false);
+    for e in EXPECTED {
+        check(reader.next_char(), e);
+    }
+    reader.borrow_mut().borrow_mut().script.push(Ok(b"\n"));
+    assert_eq!(reader.next_char().unwrap(), Char::Char('\n'));
+    reader.set_eof(true);
+    assert_eq!(reader.next_char().unwrap(), Char::Eof);
+}
+
+#[cfg(feature = "embedded-io-async")]
+mod asynchronous {
+    use super::*;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use utf8_read::EmbeddedIoAsync;
+
+    /// A waker that does nothing, as every future is ready
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self:Arc<Self>) {}
+    }
+
+    /// Run a future that is always ready to completion
+    fn block_on<F:Future>(f:F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut f = Box::pin(f);
+        loop {
+            if let Poll::Ready(x) = f.as_mut().poll(&mut cx) {
+                return x;
+            }
+        }
+    }
+
+    #[test]
+    fn test_embedded_io_async() {
+        let mut reader = Reader::new(EmbeddedIoAsync::new(Serial { script:script() })).set_eof_on_no_data(false);
+        assert_eq!(reader.next_char().unwrap(), Char::NoData);
+        for e in EXPECTED {
+            check(block_on(reader.next_char_async()), e);
+        }
+
+        let mut reader = Reader::new(EmbeddedIoAsync::new(Serial { script:vec![Ok(b"x\xe2"), Ok(b"\x9c")] }));
+        assert_eq!(block_on(reader.next_char_async()).unwrap(), Char::Char('x'));
+        assert!(matches!(block_on(reader.next_char_async()), Err(Error::MalformedUtf8(_, utf8_read::Utf8ErrorKind::TruncatedAtEof, _))));
+        assert_eq!(block_on(reader.next_char_async()).unwrap(), Char::Eof);
+    }
+}
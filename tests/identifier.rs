@@ -0,0 +1,76 @@
+#![cfg(feature = "unicode-ident")]
+//a Imports
+use utf8_read::{is_pattern_syntax, is_pattern_white_space, is_xid_continue, is_xid_start};
+use utf8_read::{Char, Error, Reader};
+mod utils;
+use utils::StopStart;
+
+#[test]
+fn test_identifiers_across_stalls() {
+    let text = "_x1 \u{65e5}\u{672c}\u{8a9e}\u{30ab}\u{30ca} \u{d55c}\u{ad6d}\u{c5b4}\u{6f22} p\u{430}yp\u{430}l \u{212b}ngstr\u{f6}m Stra\u{df}e x\u{663} ";
+    let expected = [
+        ("_x1", None, false),
+        ("\u{65e5}\u{672c}\u{8a9e}\u{30ab}\u{30ca}", None, false),
+        ("\u{d55c}\u{ad6d}\u{c5b4}\u{6f22}", None, false),
+        ("p\u{430}yp\u{430}l", None, true),
+        ("\u{212b}ngstr\u{f6}m", Some("\u{c5}ngstr\u{f6}m"), false),
+        ("Stra\u{df}e", None, false),
+        ("x\u{663}", None, true),
+    ];
+    for n in 1..6 {
+        let mut bytes = text.as_bytes();
+        let mut reader = Reader::new(StopStart::new(&mut bytes, n)).set_eof_on_no_data(false);
+        let mut found = Vec::new();
+        while found.len() < expected.len() {
+            match reader.read_identifier().unwrap() {
+                Some(ident) => {
+                    assert_eq!(ident.span().end().byte() - ident.span().start().byte(), ident.text().len());
+                    found.push((ident.text().to_string(), ident.nfc().map(String::from), ident.is_mixed_script()));
+                    while reader.skip_while(char::is_whitespace).unwrap().is_none() {
+                        reader.borrow_mut().kick();
+                        if reader.borrow().is_eof() {
+                            reader.set_eof(true);
+                        }
+                    }
+                }
+                None => { reader.borrow_mut().kick(); }
+            }
+        }
+        let expected : Vec<_> = expected.iter().map(|(t, n, m)| (t.to_string(), n.map(String::from), *m)).collect();
+        assert_eq!(found, expected, "with {} bytes per read", n);
+    }
+}
+
+#[test]
+fn test_not_identifier() {
+    let mut reader = Reader::new("1abc".as_bytes());
+    match reader.read_identifier() {
+        Err(Error::Expected(pos, _, Char::Char('1'))) => { assert_eq!(pos.byte(), 0); }
+        x => { panic!("unexpected {:?}", x); }
+    }
+    let mut reader = Reader::new("a-b".as_bytes());
+    let ident = reader.read_identifier().unwrap().unwrap();
+    assert_eq!((ident.text(), ident.normalized()), ("a", "a"));
+    assert!(matches!(reader.read_identifier(), Err(Error::Expected(_, _, Char::Char('-')))));
+    assert_eq!(reader.next_char().unwrap(), Char::Char('-'));
+    assert_eq!(reader.read_identifier().unwrap().unwrap().text(), "b");
+    assert!(matches!(reader.read_identifier(), Err(Error::Expected(_, _, Char::Eof))));
+}
+
+#[test]
+fn test_pattern_properties() {
+    let chars = || (0..=0x10ffff).filter_map(char::from_u32);
+    assert_eq!(chars().filter(|c| is_pattern_white_space(*c)).count(), 11);
+    assert_eq!(chars().filter(|c| is_pattern_syntax(*c)).count(), 2760);
+    for ch in chars() {
+        if is_pattern_white_space(ch) {
+            assert!(ch.is_whitespace() || ch == '\u{200e}' || ch == '\u{200f}');
+        }
+        if is_pattern_syntax(ch) || is_pattern_white_space(ch) {
+            assert!(!is_xid_continue(ch), "{:?}", ch);
+        }
+        if is_xid_start(ch) {
+            assert!(is_xid_continue(ch), "{:?}", ch);
+        }
+    }
+}
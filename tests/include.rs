@@ -0,0 +1,75 @@
+//a Imports
+use utf8_read::{Char, Error, IncludeReader, StreamPosition};
+
+/// Read a configuration with 'include name;' directives, returning the text
+fn read_config(reader:&mut IncludeReader<&[u8]>, files:&[(&str, &'static [u8])]) -> Result<String, Error> {
+    let mut text = String::new();
+    loop {
+        match reader.next_char()? {
+            Char::Char('@') => {
+                let mut name = String::new();
+                loop {
+                    match reader.next_char()? {
+                        Char::Char(';') => { break; }
+                        Char::Char(ch)  => { name.push(ch); }
+                        _ => { panic!("Unterminated include"); }
+                    }
+                }
+                let (_, data) = files.iter().find(|(n,_)| *n == name).unwrap();
+                reader.push_source(&name, data)?;
+            }
+            Char::Char(ch) => { text.push(ch); }
+            _ => { break; }
+        }
+    }
+    Ok(text)
+}
+
+#[test]
+fn test_nested_includes() {
+    let files : &[(&str, &'static [u8])] = &[
+        ("b.cfg", b"b1\n@c.cfg;b2\n"),
+        ("c.cfg", b"c1\n"),
+    ];
+    let mut reader = IncludeReader::new("a.cfg", &b"a1\n@b.cfg;a2\n@c.cfg;"[..]);
+    let text = read_config(&mut reader, files).unwrap();
+    assert_eq!(text, "a1\nb1\nc1\nb2\na2\nc1\n");
+    assert_eq!(reader.depth(), 0);
+    assert_eq!(format!("{}", reader.source_position()), "a.cfg line 3 char 8");
+}
+
+#[test]
+fn test_error_chain() {
+    let files : &[(&str, &'static [u8])] = &[
+        ("b.cfg", b"b1\nb2\nb3\n@c.cfg;"),
+        ("c.cfg", b"c1\nc2\n\xffc3"),
+    ];
+    let mut reader = IncludeReader::new("a.cfg", &b"a1\n@b.cfg;a2\n"[..]);
+    let e = read_config(&mut reader, files).unwrap_err();
+    assert_eq!(format!("{}", e),
+               "in c.cfg: malformed UTF-8 at line 3 char 1: invalid lead byte (bytes ff), \
+                included from b.cfg line 4 char 8, included from a.cfg line 2 char 8");
+    assert_eq!(e.position(), Some(StreamPosition::of_blc(6,3,1)));
+    assert_eq!(reader.depth(), 2);
+    let chain : Vec<String> = reader.include_chain().iter().map(|p| format!("{}", p)).collect();
+    assert_eq!(chain, vec!["c.cfg line 3 char 1", "b.cfg line 4 char 8", "a.cfg line 2 char 8"]);
+
+    // Reading continues after the error, popping back to the parents
+    let text = read_config(&mut reader, files).unwrap();
+    assert_eq!(text, "c3a2\n");
+}
+
+#[test]
+fn test_include_depth() {
+    let files : &[(&str, &'static [u8])] = &[
+        ("loop.cfg", b"x@loop.cfg;"),
+    ];
+    let mut reader = IncludeReader::new("loop.cfg", &b"x@loop.cfg;"[..]).set_max_depth(2);
+    let e = read_config(&mut reader, files).unwrap_err();
+    assert_eq!(format!("{}", e),
+               "in loop.cfg: include depth limit of 2 exceeded at line 1 char 12, \
+                included from loop.cfg line 1 char 12, included from loop.cfg line 1 char 12");
+    assert_eq!(reader.depth(), 2);
+    let e = IncludeReader::new("a", &b""[..]).set_max_depth(0).push_source("b", &b""[..]).unwrap_err();
+    assert!(matches!(e, Error::InSource(_, ref e) if matches!(**e, Error::IncludeDepth(_, 0))));
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Error, Reader, StreamPosition};
+mod utils;
+use utils::StopStart;
+
+#[test]
+fn test_positions_match_char_indices() {
+    let text = "Line one \u{2764}\nLine \u{1f600} two\n\nend";
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(&mut buf_bytes);
+    let positions : Vec<(StreamPosition, char)> = reader.positions().map(|x| x.unwrap()).collect();
+    let expected : Vec<(usize, char)> = text.char_indices().collect();
+    assert_eq!(positions.len(), expected.len());
+    for ((pos, ch), (byte, ex_ch)) in positions.iter().zip(expected.iter()) {
+        assert_eq!(pos.byte(), *byte);
+        assert_eq!(ch, ex_ch);
+    }
+    assert_eq!(positions[20].0.line_position(), (2, 10));
+    assert_eq!(positions[23].0.line_position(), (4, 1));
+}
+
+#[test]
+fn test_positions_with_errors() {
+    let mut buf_bytes : &[u8] = b"a\xffb";
+    let mut reader = Reader::new(&mut buf_bytes);
+    let mut iter = reader.positions();
+    assert_eq!(iter.next().unwrap().unwrap(), (StreamPosition::of_blc(0,1,1), 'a'));
+    assert!(matches!(iter.next(), Some(Err(Error::MalformedUtf8(_,_,_)))));
+    assert_eq!(iter.next().unwrap().unwrap(), (StreamPosition::of_blc(2,1,2), 'b'));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn test_chars_raw_stop_start() {
+    let text = "Stop \u{2764} start ".repeat(10);
+    let mut buf_bytes = text.as_bytes();
+    let mut reader = Reader::new(StopStart::new(&mut buf_bytes, 7)).set_eof_on_no_data(false);
+    let mut result = String::new();
+    let mut no_data = 0;
+    // Use the reader in a single loop, rather than an outer loop
+    // around a 'for' loop as is required with a normal iterator
+    let mut iter = reader.chars_raw();
+    while let Some(c) = iter.next() {
+        match c.unwrap() {
+            Char::Char(ch) => { result.push(ch); }
+            Char::NoData => {
+                no_data += 1;
+                reader.borrow_mut().kick();
+                if reader.borrow().is_eof() {
+                    reader.set_eof(true);
+                }
+                iter = reader.chars_raw();
+            }
+            Char::Eof => { panic!("Eof should end the iteration"); }
+        }
+    }
+    assert_eq!(result, text);
+    assert!(no_data > 10);
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "aho-corasick")]
+//a Imports
+use utf8_read::{KeywordMatcher, KeywordSet, Reader};
+mod utils;
+use utils::StopStart;
+
+/// All occurrences of `keywords` in `text` as (keyword, start byte,
+/// end byte), comparing characters after mapping them with `f`, in
+/// the order that a [KeywordMatcher] returns them
+fn reference<F:Fn(char) -> char>(keywords:&[&str], text:&str, f:F) -> Vec<(usize, usize, usize)> {
+    let chars : Vec<(usize, char)> = text.char_indices().map(|(i, c)| (i, f(c))).collect();
+    let mut found = Vec::new();
+    for end in 1..=chars.len() {
+        let end_byte = chars.get(end).map(|(i, _)| *i).unwrap_or(text.len());
+        let mut at_end = Vec::new();
+        for (id, k) in keywords.iter().enumerate() {
+            let k : Vec<char> = k.chars().map(&f).collect();
+            if !k.is_empty() && k.len() <= end && chars[end-k.len()..end].iter().map(|(_, c)| *c).eq(k.iter().copied()) {
+                at_end.push((id, chars[end-k.len()].0, end_byte));
+            }
+        }
+        at_end.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+        found.extend(at_end);
+    }
+    found
+}
+
+/// All occurrences found by a [KeywordMatcher] reading `text` from a
+/// stream that stalls every `n` bytes
+fn stream_matches(keywords:&KeywordSet, text:&str, n:usize) -> Vec<(usize, usize, usize)> {
+    let mut bytes = text.as_bytes();
+    let mut reader = Reader::new(StopStart::new(&mut bytes, n)).set_eof_on_no_data(false);
+    let mut matcher = KeywordMatcher::new(keywords);
+    let mut found = Vec::new();
+    loop {
+        match matcher.next_match(&mut reader).unwrap() {
+            Some((id, span)) => { found.push((id, span.start().byte(), span.end().byte())); }
+            None if reader.eof() => { return found; }
+            None => {
+                reader.borrow_mut().kick();
+                if reader.borrow().is_eof() {
+                    reader.set_eof(true);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_overlapping_across_refills() {
+    let keywords = ["panic", "pan", "an", "ic", "\u{1f600}!", "\u{e9}t\u{e9}", "", "nicpan"];
+    let set = KeywordSet::new(keywords).unwrap();
+    let mut text = String::new();
+    for i in 0..400 {
+        text += &"x".repeat(i % 13);
+        text += ["panicpanic ", "\u{e9}t\u{e9}\u{1f600}! ", "Panic pa nic\n"][i % 3];
+    }
+    assert!(text.len() > 3 * 2048);
+    let expected = reference(&keywords, &text, |c| c);
+    assert_eq!(expected.len(), 134 * 9 + 133 * 2 + 133 * 3);
+    for n in [1, 3, 7, 5000] {
+        assert_eq!(stream_matches(&set, &text, n), expected, "with {} bytes per read", n);
+    }
+    let mut reader = Reader::new(text.as_bytes());
+    let found : Vec<_> = reader.find_keywords(&set)
+        .map(|m| m.unwrap())
+        .map(|(id, span)| (id, span.start().byte(), span.end().byte()))
+        .collect();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn test_case_insensitive() {
+    let keywords = ["kelvin", "\u{3a3}\u{3a3}", "stra\u{df}e", "sMi\u{3bc}"];
+    let set = KeywordSet::case_insensitive(keywords).unwrap();
+    let text = "\u{212a}ELVIN kelvin \u{3c3}\u{3c2} \u{3c2}\u{3a3} STRASSE Stra\u{df}e \u{17f}mi\u{b5} SMI\u{39c}";
+    let found = stream_matches(&set, text, 2);
+    let ids : Vec<usize> = found.iter().map(|(id, _, _)| *id).collect();
+    assert_eq!(ids, [0, 0, 1, 1, 2, 3, 3]);
+    assert_eq!(found[0], (0, 0, 8));
+    assert_eq!(stream_matches(&KeywordSet::new(keywords).unwrap(), text, 2), [(0, 9, 15)]);
+}
+
+#[test]
+fn test_case_insensitive_lowercase_pairs() {
+    // Lowercase characters that are equal under simple case folding
+    let keywords = ["\u{390}x", "\u{3b0}x", "\u{fb06}x"];
+    let set = KeywordSet::case_insensitive(keywords).unwrap();
+    let text = "\u{1fd3}X \u{1fe3}X \u{fb05}X \u{390}x \u{3b0}x \u{fb06}x";
+    let ids : Vec<usize> = stream_matches(&set, text, 3).iter().map(|(id, _, _)| *id).collect();
+    assert_eq!(ids, [0, 1, 2, 0, 1, 2]);
+    let set = KeywordSet::case_insensitive(["\u{1fd3}", "\u{1fe3}", "\u{fb05}"]).unwrap();
+    assert_eq!(stream_matches(&set, "\u{390}\u{3b0}\u{fb06}", 1).len(), 3);
+}
+
+#[test]
+fn test_positions_and_errors() {
+    let set = KeywordSet::new(["ab", "b\ncd"]).unwrap();
+    let mut reader = Reader::new(&b"xab\ncd a\xffb ab"[..]);
+    let mut matcher = KeywordMatcher::new(&set);
+    let (id, span) = matcher.next_match(&mut reader).unwrap().unwrap();
+    assert_eq!((id, span.start().line_position(), span.end().line_position()), (0, (1, 2), (1, 4)));
+    let (id, span) = matcher.next_match(&mut reader).unwrap().unwrap();
+    assert_eq!((id, span.start().line_position(), span.end().line_position()), (1, (1, 3), (2, 3)));
+    assert!(matcher.next_match(&mut reader).is_err());
+    let (id, span) = matcher.next_match(&mut reader).unwrap().unwrap();
+    assert_eq!((id, span.start().byte()), (0, 11));
+    assert!(matcher.next_match(&mut reader).unwrap().is_none());
+}
@@ -0,0 +1,148 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Error, Escapes, Lexed, Lexer, Reader, Recognizer, StringRules, Token};
+mod utils;
+use utils::StopStart;
+
+fn lexer() -> Lexer {
+    Lexer::new()
+        .add_recognizer(Recognizer::Whitespace)
+        .add_recognizer(Recognizer::LineComment("//".into()))
+        .add_recognizer(Recognizer::BlockComment("/*".into(), "*/".into()))
+        .add_recognizer(Recognizer::Hex)
+        .add_recognizer(Recognizer::Float)
+        .add_recognizer(Recognizer::Decimal)
+        .add_recognizer(Recognizer::Identifier)
+        .add_recognizer(Recognizer::Str(StringRules::new('"', Escapes::Backslash)))
+        .add_recognizer(Recognizer::Str(StringRules::new('\'', Escapes::DoubledQuote).set_multiline(true)))
+}
+
+#[test]
+fn test_tokens_across_stalls() {
+    let text = "let \u{e9}t\u{e9} = 0xFF + 12.5e-1 /* note\n */ \"a\\\"\\u{1f600}\\n\" 'x''\ny' 7e3 1.x // end";
+    let expected = [
+        (Token::Identifier("let".into()), (1, 1)),
+        (Token::Identifier("\u{e9}t\u{e9}".into()), (1, 5)),
+        (Token::Hex(255), (1, 11)),
+        (Token::Float(1.25), (1, 18)),
+        (Token::BlockComment(" note\n ".into()), (1, 26)),
+        (Token::Str("a\"\u{1f600}\n".into()), (2, 5)),
+        (Token::Str("x'\ny".into()), (2, 22)),
+        (Token::Float(7000.0), (3, 4)),
+        (Token::Decimal(1), (3, 8)),
+        (Token::LineComment(" end".into()), (3, 12)),
+    ];
+    let lexer = lexer();
+    for n in [1, 2, 3, 5, 100] {
+        let mut bytes = text.as_bytes();
+        let mut reader = Reader::new(StopStart::new(&mut bytes, n)).set_eof_on_no_data(false);
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        loop {
+            match lexer.next_token(&mut reader) {
+                Ok(Lexed::Token(Token::Whitespace, _)) => (),
+                Ok(Lexed::Token(token, span)) => { tokens.push((token, span.start().line_position())); }
+                Ok(Lexed::NoData) => {
+                    reader.borrow_mut().kick();
+                    if reader.borrow().is_eof() {
+                        reader.set_eof(true);
+                    }
+                }
+                Ok(Lexed::Eof) => { break; }
+                Err(Error::Expected(pos, _, _)) => { errors.push(pos.line_position()); }
+                Err(e) => { panic!("unexpected error {}", e); }
+            }
+        }
+        // '=', '+' and the '.' of "1.x" are not tokens
+        assert_eq!(errors, [(1, 9), (1, 16), (3, 9)]);
+        let identifier_x = tokens.remove(9);
+        assert_eq!(identifier_x, (Token::Identifier("x".into()), (3, 10)));
+        assert_eq!(tokens, expected, "with {} bytes per read", n);
+    }
+}
+
+/// Lex `text`, returning the error positions as (line, char) and the
+/// tokens as their debug strings
+fn lex_errors(text:&str) -> (Vec<(usize, usize)>, Vec<String>) {
+    let lexer = lexer();
+    let mut reader = Reader::new(text.as_bytes());
+    let mut errors = Vec::new();
+    let mut tokens = Vec::new();
+    loop {
+        match lexer.next_token(&mut reader) {
+            Ok(Lexed::Token(Token::Whitespace, _)) => (),
+            Ok(Lexed::Token(token, _)) => { tokens.push(format!("{:?}", token)); }
+            Ok(_) => { return (errors, tokens); }
+            Err(e) => { errors.push(e.position().unwrap().line_position()); }
+        }
+    }
+}
+
+#[test]
+fn test_error_positions() {
+    let (errors, tokens) = lex_errors("a \"abc\ndef\" b");
+    assert_eq!(errors, [(1, 3), (2, 4)]);
+    // The second quote opens a string that is unterminated at EOF
+    assert_eq!(tokens, ["Identifier(\"a\")", "Identifier(\"def\")"]);
+    let (errors, tokens) = lex_errors("x \"ok\\q\\z\" y");
+    assert_eq!(errors, [(1, 6)]);
+    assert_eq!(tokens, ["Identifier(\"x\")", "Identifier(\"y\")"]);
+    let (errors, _) = lex_errors("\"\\u{d800}\" \"\\u{110000}\" \"\\u12\"");
+    assert_eq!(errors, [(1, 2), (1, 13), (1, 26)]);
+    let (errors, tokens) = lex_errors("0x 99999999999999999999 0x1ffffffffffffffff 1");
+    assert_eq!(errors, [(1, 3), (1, 4), (1, 25)]);
+    assert_eq!(tokens, ["Decimal(1)"]);
+    let (errors, tokens) = lex_errors("a /* never\nclosed");
+    assert_eq!(errors, [(1, 3)]);
+    assert_eq!(tokens, ["Identifier(\"a\")"]);
+    let (errors, _) = lex_errors("'it''s");
+    assert_eq!(errors, [(1, 1)]);
+}
+
+#[test]
+fn test_error_display() {
+    let mut reader = Reader::new("\"\\x\"".as_bytes());
+    let err = Recognizer::Str(StringRules::new('"', Escapes::Backslash)).recognize(&mut reader).unwrap_err();
+    assert_eq!(format!("{}", err), "invalid escape \\x at line 1 char 2");
+    let mut reader = Reader::new("'abc".as_bytes());
+    let err = Recognizer::Str(StringRules::new('\'', Escapes::None)).recognize(&mut reader).unwrap_err();
+    assert_eq!(format!("{}", err), "unterminated string starting at line 1 char 1");
+}
+
+#[test]
+fn test_malformed_in_token() {
+    // Malformed UTF-8 within a string is reported in stream order,
+    // and its bytes are never dropped from the string
+    let string = Recognizer::Str(StringRules::new('"', Escapes::Backslash));
+    let mut bytes : &[u8] = b"\"ab\xffcd\" x";
+    let mut reader = Reader::new(&mut bytes);
+    let err = string.recognize(&mut reader).unwrap_err();
+    assert_eq!(format!("{}", err), "malformed UTF-8 in string at line 1 char 4");
+    match string.recognize(&mut reader) {
+        Err(Error::MalformedUtf8(pos, _, bytes)) => { assert_eq!((pos.byte(), bytes), (3, vec![0xff])); }
+        x => { panic!("unexpected {:?}", x); }
+    }
+    assert!(string.recognize(&mut reader).unwrap().is_none());
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "cd\" x");
+
+    // A token that may end at the malformed UTF-8 does so
+    let mut bytes : &[u8] = b"abc\xff1";
+    let mut reader = Reader::new(&mut bytes);
+    let lexer = lexer();
+    assert!(matches!(lexer.next_token(&mut reader), Ok(Lexed::Token(Token::Identifier(s), _)) if s == "abc"));
+    assert!(matches!(lexer.next_token(&mut reader), Err(Error::MalformedUtf8(pos, _, _)) if pos.byte() == 3));
+    assert!(matches!(lexer.next_token(&mut reader), Ok(Lexed::Token(Token::Decimal(1), _))));
+
+    let mut bytes : &[u8] = b"0x\xff /* \xfe";
+    let mut reader = Reader::new(&mut bytes);
+    let err = lexer.next_token(&mut reader).unwrap_err();
+    assert_eq!(format!("{}", err), "expected hexadecimal digit, found malformed UTF-8 at line 1 char 3");
+    assert!(matches!(lexer.next_token(&mut reader), Err(Error::MalformedUtf8(pos, _, _)) if pos.byte() == 2));
+    assert!(matches!(lexer.next_token(&mut reader), Ok(Lexed::Token(Token::Whitespace, _))));
+    let err = lexer.next_token(&mut reader).unwrap_err();
+    assert_eq!(format!("{}", err), "malformed UTF-8 in block comment at line 1 char 7");
+    assert!(matches!(lexer.next_token(&mut reader), Err(Error::MalformedUtf8(pos, _, _)) if pos.byte() == 7));
+    assert_eq!(lexer.next_token(&mut reader).unwrap(), Lexed::Eof);
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Error, Reader};
+mod utils;
+use utils::StopStart;
+
+/// Create a reader of `bytes` with `n` bytes per read
+fn stop_start(bytes:&[u8], n:usize) -> Reader<StopStart<&[u8]>> {
+    Reader::new(StopStart::new(bytes, n)).set_eof_on_no_data(false)
+}
+
+/// Read one character at a time, returning the text read and the
+/// first error other than malformed UTF-8
+fn read_chars(mut reader:Reader<StopStart<&[u8]>>) -> (String, Error) {
+    let mut text = String::new();
+    loop {
+        match reader.next_char() {
+            Ok(Char::Char(ch)) => { text.push(ch); }
+            Ok(Char::NoData) => {
+                reader.borrow_mut().kick();
+                if reader.borrow().is_eof() {
+                    reader.set_eof(true);
+                }
+            }
+            Ok(Char::Eof) => { panic!("no limit was exceeded"); }
+            Err(Error::MalformedUtf8(..)) => (),
+            Err(e) => {
+                // The limit error is returned again
+                assert_eq!(format!("{}", reader.next_char().unwrap_err()), format!("{}", e));
+                return (text, e);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_byte_limit() {
+    let text = "caf\u{e9} \u{1f600} more";
+    for n in 1..6 {
+        let (read, e) = read_chars(stop_start(text.as_bytes(), n).set_max_bytes(9));
+        assert_eq!(read, "caf\u{e9} ");
+        assert!(matches!(e, Error::ByteLimit(pos, 9) if pos.byte() == 6), "{:?}", e);
+        let (read, _) = read_chars(stop_start(text.as_bytes(), n).set_max_bytes(10));
+        assert_eq!(read, "caf\u{e9} \u{1f600}");
+    }
+    let mut reader = Reader::new(text.as_bytes()).set_max_bytes(9);
+    let mut s = String::new();
+    assert!(matches!(reader.read_to_string(&mut s), Err(Error::ByteLimit(_, 9))));
+    assert_eq!(s, "caf\u{e9} ");
+    assert_eq!(format!("{}", reader.next_char().unwrap_err()), "limit of 9 bytes exceeded at line 1 char 6");
+}
+
+#[test]
+fn test_byte_limit_malformed() {
+    // Malformed UTF-8 counts towards the byte limit
+    let bytes = vec![0xff; 100_000];
+    for n in 1..4 {
+        let (read, e) = read_chars(stop_start(&bytes, n).set_max_bytes(10));
+        assert_eq!(read, "");
+        assert!(matches!(e, Error::ByteLimit(pos, 10) if pos.byte() == 10), "{:?}", e);
+    }
+    let mut reader = Reader::new(&bytes[..]).set_max_bytes(10);
+    for _ in 0..10 {
+        assert!(matches!(reader.next_char(), Err(Error::MalformedUtf8(..))));
+    }
+    assert!(matches!(reader.next_char(), Err(Error::ByteLimit(_, 10))));
+    assert!(matches!(reader.next_char(), Err(Error::ByteLimit(_, 10))));
+}
+
+#[test]
+fn test_line_limit() {
+    let text = "short\nexactly 10\r\nthis line is too long\nok\n";
+    let (read, e) = read_chars(stop_start(text.as_bytes(), 3).set_max_line_chars(11));
+    assert_eq!(read, "short\nexactly 10\r\nthis line i");
+    assert_eq!(e.position().unwrap().line_position(), (3, 12));
+    assert_eq!(format!("{}", e), "line length limit of 11 characters exceeded at line 3 char 12");
+
+    let mut reader = Reader::new(text.as_bytes()).set_max_line_chars(11);
+    let mut s = String::new();
+    assert_eq!(reader.read_line(&mut s).unwrap(), 6);
+    assert_eq!(reader.read_line(&mut s).unwrap(), 12);
+    assert!(matches!(reader.read_line(&mut s), Err(Error::LineLimit(_, 11))));
+    assert_eq!(s, "short\nexactly 10\r\nthis line i");
+
+    // Peeking beyond the limit returns the characters before it first
+    let mut reader = Reader::new(text.as_bytes()).set_max_line_chars(4);
+    assert_eq!(reader.take_while(|c| c != '\n').unwrap().unwrap().0, "shor");
+    assert!(matches!(reader.take_while(|c| c != '\n'), Err(Error::LineLimit(_, 4))));
+    assert!(matches!(reader.next_char(), Err(Error::LineLimit(_, 4))));
+}
+
+#[test]
+fn test_malformed_limit() {
+    let bytes = b"a\xffb\xfec\xfdd";
+    for n in 1..4 {
+        let (read, e) = read_chars(stop_start(bytes, n).set_max_malformed(2));
+        assert_eq!(read, "abc");
+        assert!(matches!(e, Error::MalformedLimit(pos, 2) if pos.byte() == 5), "{:?}", e);
+    }
+    let (read, e) = read_chars(stop_start(bytes, 2).set_max_malformed(0));
+    assert_eq!(read, "a");
+    assert_eq!(format!("{}", e), "limit of 0 malformed UTF-8 sequences exceeded at line 1 char 2");
+}
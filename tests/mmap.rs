@@ -0,0 +1,126 @@
+#![cfg(feature = "mmap")]
+//a Imports
+use std::io::Write;
+use utf8_read::{Char, MmapReader, Reader, StreamPosition};
+
+/// Write `bytes` to a new temporary file with the given name
+fn temp_file(name:&str, bytes:&[u8]) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("utf8_read_{}_{}", std::process::id(), name));
+    std::fs::File::create(&path).unwrap().write_all(bytes).unwrap();
+    path
+}
+
+/// Read every character and error position
+fn all_chars<I:Iterator<Item = utf8_read::Result<(StreamPosition, char)>>>(iter:I) -> Vec<Result<(StreamPosition, char), StreamPosition>> {
+    iter.map(|r| r.map_err(|e| e.position().unwrap())).collect()
+}
+
+#[test]
+fn test_mapped_matches_reader() {
+    let mut bytes = Vec::new();
+    for i in 0..20_000 {
+        bytes.extend_from_slice(format!("{} caf\u{e9} \u{1f600}\n", i).as_bytes());
+        if i % 1000 == 0 {
+            bytes.extend_from_slice(b"bad \xe2\x9d\xff\x80\n");
+        }
+    }
+    bytes.extend_from_slice(b"\xf0\x9f\x98");
+    let path = temp_file("match", &bytes);
+
+    let mut mmap_reader = MmapReader::open(&path).unwrap();
+    assert!(mmap_reader.borrow().is_mapped());
+    let mut buf_bytes = &bytes[..];
+    let mut reader = Reader::new(&mut buf_bytes);
+    assert_eq!(all_chars(mmap_reader.positions()), all_chars(reader.positions()));
+    assert_eq!(mmap_reader.next_char().unwrap(), Char::Eof);
+    assert_eq!(mmap_reader.borrow_pos(), reader.borrow_pos());
+
+    let mut mmap_reader = MmapReader::open(&path).unwrap();
+    let mut lines = Vec::new();
+    let mut errors = 0;
+    loop {
+        let mut line = String::new();
+        match mmap_reader.read_line(&mut line) {
+            Ok(0) => { break; }
+            Ok(_) => { lines.push(line); }
+            Err(_) => { errors += 1; }
+        }
+    }
+    assert_eq!(errors, 3 * 20 + 1);
+    assert_eq!(lines.len(), 20_000 + 20);
+    assert_eq!(lines[1], "\n");
+    assert_eq!(lines[2], "1 caf\u{e9} \u{1f600}\n");
+    assert_eq!(mmap_reader.borrow_pos(), reader.borrow_pos());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_read_to_string() {
+    let text = "line one\nline two \u{2764}\n";
+    let path = temp_file("to_string", text.as_bytes());
+    let mut reader = MmapReader::open(&path).unwrap();
+    let mut s = String::new();
+    assert_eq!(reader.read_to_string(&mut s).unwrap(), text.len());
+    assert_eq!(s, text);
+    assert_eq!(reader.borrow_pos().line_position(), (3, 1));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_fallback() {
+    let path = temp_file("empty", b"");
+    let mut reader = MmapReader::open(&path).unwrap();
+    assert!(!reader.borrow().is_mapped());
+    assert_eq!(reader.next_char().unwrap(), Char::Eof);
+    std::fs::remove_file(&path).unwrap();
+
+    // /proc entries report a length of zero, but have contents
+    if let Ok(mut reader) = MmapReader::open("/proc/self/status") {
+        assert!(!reader.borrow().is_mapped());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with("Name:"));
+    }
+}
+
+#[test]
+fn test_reader_api() {
+    let path = temp_file("api", b"key = value\n# caf\xc3\xa9\n");
+    let mut reader = MmapReader::open(&path).unwrap().keep_lines(1);
+    assert!(reader.borrow().is_mapped());
+    assert_eq!(reader.length_hint(), Some(20));
+    reader.expect_str("key").unwrap();
+    reader.skip_whitespace().unwrap();
+    assert!(reader.expect_char(':').is_err());
+    let (text, span) = reader.read_until('\n').unwrap().unwrap();
+    assert_eq!(text, "= value");
+    assert_eq!(span.start().line_position(), (1, 5));
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "# caf\u{e9}\n");
+    assert_eq!(reader.next_char().unwrap(), Char::Eof);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_decoded_in_place() {
+    let text = "caf\u{e9} \u{1f600}\n".repeat(20_000);
+    let path = temp_file("in_place", text.as_bytes());
+    let mut reader = MmapReader::open(&path).unwrap().collect_stats();
+    assert_eq!(reader.next_char().unwrap(), Char::Char('c'));
+    // The buffer of the reader is the mapping itself, a window of
+    // which is far larger than the internal buffer
+    let map = reader.borrow().mapping().unwrap().as_ptr_range();
+    let buffer = reader.borrow_buffer().as_ptr_range();
+    assert_eq!(buffer.start, map.start.wrapping_add(1));
+    assert!(buffer.end > map.start.wrapping_add(4096) && buffer.end <= map.end);
+    assert_eq!(reader.peek_char().unwrap(), Char::Char('a'));
+
+    let mut s = String::from("c");
+    reader.read_to_string(&mut s).unwrap();
+    assert_eq!(s, text);
+    assert_eq!(reader.text_stats().unwrap().lines(), 20_000);
+    assert_eq!(*reader.borrow_pos(), StreamPosition::of_blc(text.len(), 20_001, 1));
+    assert!(reader.borrow_buffer().is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
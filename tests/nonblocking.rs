@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+//a Imports
+use std::io::ErrorKind;
+use utf8_read::{Char, Error, Reader};
+
+/// A stream that returns its data a few bytes at a time, interspersed
+/// with errors as a non-blocking stream might
+struct Flaky<'a> {
+    data   : &'a [u8],
+    errors : Vec<ErrorKind>,
+    step   : usize,
+}
+
+impl <'a> Flaky<'a> {
+    fn new(data:&'a [u8], errors:&[ErrorKind]) -> Self {
+        Self { data, errors:errors.into(), step:0 }
+    }
+}
+
+impl std::io::Read for Flaky<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.step += 1;
+        if self.step % 2 == 1 {
+            let kind = self.errors[(self.step / 2) % self.errors.len()];
+            return Err(std::io::Error::new(kind, "flaky"));
+        }
+        let n = self.data.len().min(buf.len()).min(2);
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_would_block() {
+    let text = "a\u{1f600}b\u{2764}c";
+    let mut reader = Reader::new(Flaky::new(text.as_bytes(), &[ErrorKind::WouldBlock, ErrorKind::TimedOut]));
+    let mut result = String::new();
+    let mut no_data = 0;
+    loop {
+        match reader.next_char().unwrap() {
+            Char::Char(ch) => { result.push(ch); }
+            Char::NoData   => { no_data += 1; }
+            Char::Eof      => { break; }
+        }
+        assert!(no_data < 100, "Too many NoData results");
+    }
+    assert_eq!(result, text);
+    assert!(no_data > 5);
+    assert!(reader.eof());
+}
+
+#[test]
+fn test_interrupted() {
+    let text = "a\u{1f600}b\u{2764}c";
+    let mut reader = Reader::new(Flaky::new(text.as_bytes(), &[ErrorKind::Interrupted]));
+    let result : String = reader.into_iter().map(|c| c.unwrap()).collect();
+    assert_eq!(result, text);
+}
+
+#[test]
+fn test_fatal_error() {
+    let mut reader = Reader::new(Flaky::new(b"abc", &[ErrorKind::ConnectionReset]));
+    match reader.next_char() {
+        Err(Error::IoError(e)) => { assert_eq!(e.kind(), ErrorKind::ConnectionReset); }
+        x => panic!("Expected IO error, got {:?}", x),
+    }
+    assert!(!reader.eof());
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Error, Lexed, Lexer, Observer, Reader, Recognizer, StreamPosition, Token};
+mod utils;
+use utils::StopStart;
+
+/// An observer that records everything it is told
+#[derive(Default)]
+struct Recorder {
+    chars     : Vec<(StreamPosition, char)>,
+    line_ends : Vec<StreamPosition>,
+    errors    : Vec<String>,
+    fills     : Vec<usize>,
+}
+
+impl Observer for Recorder {
+    fn on_char(&mut self, pos:StreamPosition, ch:char) {
+        self.chars.push((pos, ch));
+    }
+    fn on_line_end(&mut self, pos:StreamPosition) {
+        self.line_ends.push(pos);
+    }
+    fn on_error(&mut self, err:&Error) {
+        self.errors.push(format!("{}", err));
+    }
+    fn on_fill(&mut self, bytes_read:usize) {
+        self.fills.push(bytes_read);
+    }
+}
+
+/// The characters, their positions and the positions of newlines of `text`
+fn expected(text:&str) -> (Vec<(StreamPosition, char)>, Vec<StreamPosition>) {
+    let mut reader = Reader::new(text.as_bytes());
+    let chars : Vec<_> = reader.positions().map(|r| r.unwrap()).collect();
+    let line_ends = chars.iter().filter(|(_, ch)| *ch == '\n').map(|(pos, _)| *pos).collect();
+    (chars, line_ends)
+}
+
+#[test]
+fn test_observe_chars() {
+    let text = "caf\u{e9}\n\u{1f600} line two\n\nend".repeat(200);
+    let (chars, line_ends) = expected(&text);
+    for n in [1, 3, 4000] {
+        let mut bytes = text.as_bytes();
+        let mut reader = Reader::new(StopStart::new(&mut bytes, n))
+            .set_eof_on_no_data(false)
+            .set_observer(Recorder::default());
+        loop {
+            match reader.next_char().unwrap() {
+                Char::Char(_) => (),
+                Char::NoData => {
+                    reader.borrow_mut().kick();
+                    if reader.borrow().is_eof() {
+                        reader.set_eof(true);
+                    }
+                }
+                Char::Eof => { break; }
+            }
+        }
+        let recorder = reader.borrow_observer();
+        assert_eq!(recorder.chars, chars, "with {} bytes per read", n);
+        assert_eq!(recorder.line_ends, line_ends, "with {} bytes per read", n);
+        assert!(recorder.errors.is_empty());
+        assert_eq!(recorder.fills.iter().sum::<usize>(), text.len());
+        assert!(recorder.fills.iter().all(|f| *f <= n));
+    }
+
+    // The bulk read methods are observed as if read one character at a time
+    let mut recorder = Recorder::default();
+    let mut reader = Reader::new(text.as_bytes()).set_observer(&mut recorder);
+    let mut s = String::new();
+    while reader.read_line(&mut s).unwrap() > 0 {}
+    assert_eq!(s, text);
+    assert_eq!(recorder.chars, chars);
+    assert_eq!(recorder.line_ends, line_ends);
+    assert_eq!(*recorder.fills.last().unwrap(), 0);
+}
+
+#[test]
+fn test_observe_errors() {
+    let mut reader = Reader::new(&b"ab\xffcd\xe2\x9c"[..]).set_observer(Recorder::default());
+    // An error found while peeking is observed once, when it is returned
+    assert!(reader.expect_str("ab?").is_err());
+    assert!(reader.borrow_observer().errors.is_empty());
+    assert!(reader.borrow_observer().chars.is_empty());
+    assert_eq!(reader.next_char().unwrap(), Char::Char('a'));
+    assert_eq!(reader.next_char().unwrap(), Char::Char('b'));
+    assert!(reader.take_while(|_| true).is_err());
+    assert_eq!(reader.borrow_observer().errors.len(), 1);
+    assert_eq!(reader.next_char().unwrap(), Char::Char('c'));
+    assert_eq!(reader.next_char().unwrap(), Char::Char('d'));
+    assert!(reader.next_char().is_err());
+    assert_eq!(reader.next_char().unwrap(), Char::Eof);
+    let recorder = reader.borrow_observer();
+    assert_eq!(recorder.chars.iter().map(|(_, ch)| *ch).collect::<String>(), "abcd");
+    assert_eq!(recorder.errors,
+               ["malformed UTF-8 at line 1 char 3: invalid lead byte (bytes ff)",
+                "malformed UTF-8 at line 1 char 5: sequence truncated at end of stream (bytes e2 9c)"]);
+}
+
+#[test]
+fn test_observed_lexer() {
+    let lexer = Lexer::new()
+        .add_recognizer(Recognizer::Whitespace)
+        .add_recognizer(Recognizer::Identifier)
+        .add_recognizer(Recognizer::Decimal);
+    let mut recorder = Recorder::default();
+    let mut reader = Reader::new("let x\n  = 12".as_bytes()).set_observer(&mut recorder);
+    let mut tokens = Vec::new();
+    loop {
+        match lexer.next_token(&mut reader) {
+            Ok(Lexed::Token(Token::Whitespace, _)) => (),
+            Ok(Lexed::Token(token, _)) => { tokens.push(token); }
+            Ok(_) => { break; }
+            Err(_) => (),
+        }
+    }
+    assert_eq!(tokens, [Token::Identifier("let".into()), Token::Identifier("x".into()), Token::Decimal(12)]);
+    assert_eq!(recorder.chars.len(), 12);
+    assert_eq!(recorder.line_ends, [StreamPosition::of_blc(5, 1, 6)]);
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "rayon")]
+//a Imports
+use rayon::prelude::*;
+use utf8_read::{Char, Error, Reader, StreamPosition};
+
+/// A mixed text with multi-byte characters, blank lines and some
+/// malformed UTF-8 (including an encoding truncated at the end)
+fn mixed_bytes() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for i in 0..200 {
+        bytes.extend_from_slice(format!("{} line of text\n", i).as_bytes());
+        if i % 7 == 0 {
+            bytes.extend_from_slice("caf\u{e9} \u{2764}\u{1f600}".as_bytes());
+        }
+        if i % 13 == 0 {
+            bytes.extend_from_slice(b"bad \xe2\x9d\xff \x80\x80\x80\x80\x80 \xf0\x9f\n\n");
+        }
+    }
+    bytes.extend_from_slice(b"end \xf0\x9f\x98");
+    bytes
+}
+
+/// Decode with a [Reader], returning each character with its position
+/// and the description of each error
+fn reader_decode(mut bytes:&[u8]) -> Vec<Result<(StreamPosition, char), String>> {
+    let mut reader = Reader::new(&mut bytes);
+    let mut result = Vec::new();
+    loop {
+        let pos = *reader.borrow_pos();
+        match reader.next_char() {
+            Ok(Char::Char(ch)) => { result.push(Ok((pos, ch))); }
+            Ok(_) => { break; }
+            Err(e) => { result.push(Err(e.to_string())); }
+        }
+    }
+    result
+}
+
+/// Expand the text slices from a parallel decode into characters with
+/// their positions, and errors into their descriptions
+fn expand(results:Vec<Result<(StreamPosition, &str), Error>>) -> Vec<Result<(StreamPosition, char), String>> {
+    let mut expanded = Vec::new();
+    for r in results {
+        match r {
+            Ok((pos, s)) => {
+                let (mut byte, (mut line, mut ofs)) = (pos.byte(), pos.line_position());
+                for ch in s.chars() {
+                    expanded.push(Ok((StreamPosition::of_blc(byte, line, ofs), ch)));
+                    byte += ch.len_utf8();
+                    if ch == '\n' { line += 1; ofs = 1; } else { ofs += 1; }
+                }
+            }
+            Err(e) => { expanded.push(Err(e.to_string())); }
+        }
+    }
+    expanded
+}
+
+#[test]
+fn test_par_chunks_match_reader() {
+    let bytes = mixed_bytes();
+    let expected = reader_decode(&bytes);
+    for chunk_size in [1, 2, 3, 5, 17, 64, 1000, 100_000] {
+        let results : Vec<_> = utf8_read::par_chunks(&bytes, chunk_size).collect();
+        assert_eq!(expand(results), expected, "Mismatch for chunk size {}", chunk_size);
+    }
+}
+
+#[test]
+fn test_par_lines_match_reader() {
+    let bytes = mixed_bytes();
+    let expected = reader_decode(&bytes);
+    for chunk_size in [1, 7, 64, 100_000] {
+        let results : Vec<_> = utf8_read::par_lines(&bytes, chunk_size).collect();
+        for (pos, line) in results.iter().filter_map(|r| r.as_ref().ok()) {
+            assert!(!line[..line.len()-1].contains('\n'), "Line {:?} at {} contains a newline", line, pos);
+        }
+        assert_eq!(expand(results), expected, "Mismatch for chunk size {}", chunk_size);
+    }
+}
+
+#[test]
+fn test_par_lines() {
+    let text = "one\n\ntwo \u{1f600}\nthree";
+    let lines : Vec<_> = utf8_read::par_lines(text.as_bytes(), 2)
+        .map(|r| r.unwrap())
+        .map(|(pos, line)| (pos.byte(), pos.line_position(), line))
+        .collect();
+    assert_eq!(lines, vec![(0, (1, 1), "one\n"),
+                           (4, (2, 1), "\n"),
+                           (5, (3, 1), "two \u{1f600}\n"),
+                           (14, (4, 1), "three")]);
+    assert_eq!(utf8_read::par_lines(b"", 2).count(), 0);
+}
+
+#[test]
+fn test_incomplete_at_chunk_end() {
+    let bytes = [b'a', 0xf0, 0x90, 0x80, b'A', 0xe2, 0x9c];
+    let expected = reader_decode(&bytes);
+    for chunk_size in [1, 2, 3, 4] {
+        let results : Vec<_> = utf8_read::par_chunks(&bytes, chunk_size).collect();
+        assert_eq!(expand(results), expected, "Mismatch for chunk size {}", chunk_size);
+    }
+}
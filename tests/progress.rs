@@ -0,0 +1,141 @@
+#![cfg(feature = "std")]
+//a Imports
+use std::io::{Seek, Write};
+use std::sync::{Arc, Mutex};
+use utf8_read::{Char, Observer, Reader, StreamPosition};
+mod utils;
+use utils::StopStart;
+
+/// The reports made to a progress callback
+type Reports = Arc<Mutex<Vec<(StreamPosition, Option<f64>)>>>;
+
+/// Create a progress callback that records its reports
+fn recorder() -> (Reports, impl FnMut(StreamPosition, Option<f64>) + Send + Sync + 'static) {
+    let reports = Reports::default();
+    let r = reports.clone();
+    (reports, move |pos, fraction| r.lock().unwrap().push((pos, fraction)))
+}
+
+#[test]
+fn test_reports_across_stalls() {
+    let text = "caf\u{e9} \u{1f600}\n".repeat(500);
+    for n in [1, 7, 100, 5000] {
+        let (reports, callback) = recorder();
+        let mut bytes = text.as_bytes();
+        let mut reader = Reader::new(StopStart::new(&mut bytes, n))
+            .set_eof_on_no_data(false)
+            .set_progress(1000, callback);
+        loop {
+            match reader.next_char().unwrap() {
+                Char::Char(_) => (),
+                Char::NoData => {
+                    reader.borrow_mut().kick();
+                    if reader.borrow().is_eof() {
+                        reader.set_eof(true);
+                    }
+                }
+                Char::Eof => { break; }
+            }
+        }
+        let reports = reports.lock().unwrap();
+        // No length is known, so no fraction is reported
+        assert!(reports.iter().all(|(_, fraction)| fraction.is_none()));
+        let bytes : Vec<_> = reports.iter().map(|(pos, _)| pos.byte()).collect();
+        assert!(bytes.windows(2).all(|w| w[1] / 1000 > w[0] / 1000), "{:?} with {} bytes per read", bytes, n);
+        assert!(bytes.len() >= text.len() / 2048, "{:?} with {} bytes per read", bytes, n);
+    }
+}
+
+#[test]
+fn test_fraction_from_file() {
+    let text = "0123456789abcdef\n".repeat(1000);
+    let path = std::env::temp_dir().join(format!("utf8_read_{}_progress", std::process::id()));
+    std::fs::File::create(&path).unwrap().write_all(text.as_bytes()).unwrap();
+    let file = std::fs::File::open(&path).unwrap();
+    let (reports, callback) = recorder();
+    let mut reader = Reader::new(&file).length_from_source().set_progress(0, callback);
+    assert_eq!(reader.length_hint(), Some(text.len() as u64));
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    assert_eq!(s, text);
+    std::fs::remove_file(&path).unwrap();
+    let reports = reports.lock().unwrap();
+    let fractions : Vec<f64> = reports.iter().map(|(_, f)| f.unwrap()).collect();
+    assert!(fractions.windows(2).all(|w| w[1] > w[0]), "{:?}", fractions);
+    assert_eq!(*fractions.last().unwrap(), 1.0);
+    assert_eq!(reports.last().unwrap().0.line_position(), (1001, 1));
+}
+
+#[test]
+fn test_length_from_source() {
+    let mut cursor = std::io::Cursor::new(b"skip this".to_vec());
+    cursor.seek(std::io::SeekFrom::Start(5)).unwrap();
+    let mut reader = Reader::new(cursor).length_from_source();
+    assert_eq!(reader.length_hint(), Some(9));
+    assert_eq!(reader.next_char().unwrap(), Char::Char('t'));
+    assert_eq!(Reader::new("text".as_bytes()).length_hint(), None);
+}
+
+#[test]
+fn test_reader_is_send_and_sync() {
+    fn send_and_sync<T:Send + Sync>(_:&T) {}
+    let (_, callback) = recorder();
+    let reader = Reader::new("text".as_bytes()).set_progress(1000, callback);
+    send_and_sync(&reader);
+}
+
+#[test]
+fn test_borrowed_callback() {
+    // The callback may borrow local state, and need not be Send
+    let text = "a line of text\n".repeat(1000);
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    let mut last = None;
+    let mut reader = Reader::new(text.as_bytes()).set_progress(4096, |pos, _| {
+        count.set(count.get() + 1);
+        last = Some(pos.byte());
+    });
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    drop(reader);
+    assert_eq!(count.get(), 4);
+    assert_eq!(last, Some(text.len()));
+}
+
+#[test]
+fn test_observer_progress() {
+    /// An observer that counts lines and records the progress
+    #[derive(Default)]
+    struct Lines { lines : usize, reports : Vec<usize> }
+    impl Observer for Lines {
+        fn on_line_end(&mut self, _pos:StreamPosition) { self.lines += 1; }
+        fn on_progress(&mut self, pos:StreamPosition, _fraction:Option<f64>) { self.reports.push(pos.byte()); }
+    }
+    let text = "a line of text\n".repeat(1000);
+    let mut reader = Reader::new(text.as_bytes()).set_observer(Lines::default()).set_progress_interval(5000);
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    assert_eq!(reader.borrow_observer().lines, 1000);
+    let reports = &reader.borrow_observer().reports;
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports.last(), Some(&text.len()));
+}
+
+#[test]
+fn test_reports_after_resume() {
+    // Leftover bytes beyond the internal buffer are reported as they
+    // are read
+    let text = "caf\u{e9} \u{1f600}\n".repeat(2000);
+    let (reports, callback) = recorder();
+    let mut empty : &[u8] = &[];
+    let mut reader = Reader::resume(&mut empty, StreamPosition::new(), text.as_bytes())
+        .set_length_hint(text.len() as u64)
+        .set_progress(1000, callback);
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    assert_eq!(s, text);
+    let reports = reports.lock().unwrap();
+    assert!(reports.len() >= text.len() / 2048, "{:?}", reports);
+    let fractions : Vec<f64> = reports.iter().map(|(_, f)| f.unwrap()).collect();
+    assert!(fractions.windows(2).all(|w| w[1] > w[0]), "{:?}", fractions);
+    assert_eq!(*fractions.last().unwrap(), 1.0);
+}
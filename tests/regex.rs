@@ -0,0 +1,97 @@
+#![cfg(feature = "regex")]
+//a Imports
+use utf8_read::{Reader, RegexMatcher, StreamRegex};
+mod utils;
+use utils::StopStart;
+
+/// The matches of `pattern` in `text` as byte ranges, as found by the
+/// `regex-automata` crate on the whole text
+fn reference(pattern:&str, text:&str) -> Vec<(usize, usize)> {
+    let re = regex_automata::dfa::regex::Regex::new(pattern).unwrap();
+    re.find_iter(text).map(|m| (m.start(), m.end())).collect()
+}
+
+/// The matches of `pattern` in `text` as byte ranges, as found by a
+/// [RegexMatcher] on a stream that stalls every `n` bytes; the text
+/// of each match is checked against its span
+fn stream_matches(pattern:&str, text:&str, n:usize) -> Vec<(usize, usize)> {
+    let regex = StreamRegex::new(pattern).unwrap();
+    let mut bytes = text.as_bytes();
+    let mut reader = Reader::new(StopStart::new(&mut bytes, n)).set_eof_on_no_data(false);
+    let mut matcher = RegexMatcher::new(&regex);
+    let mut matches = Vec::new();
+    loop {
+        match matcher.next_match(&mut reader).unwrap() {
+            Some((found, span)) => {
+                let range = (span.start().byte(), span.end().byte());
+                assert_eq!(found, &text[range.0..range.1]);
+                matches.push(range);
+            }
+            None if reader.eof() => { return matches; }
+            None => {
+                reader.borrow_mut().kick();
+                if reader.borrow().is_eof() {
+                    reader.set_eof(true);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_matches_as_whole_text() {
+    let text = "foo bar123 \u{e9}t\u{e9} 42\nbaz \u{1f600}\u{1f600} quux 7\nfoofoo\n";
+    let patterns = [r"[0-9]+", r"[a-z\u{e9}]+", r"foo|foofoo", r"(?m)^[a-z]+", r"(?m)[a-z]+$",
+                    r"(?-u:\b)[a-z]+(?-u:\b)", r"[\u{1f600}-\u{1f64f}]+", r"a*", r"", r"x?",
+                    r"[^\n]*\n", r"o[ \t]*b", r"[0-9]+ +[a-z]"];
+    for pattern in patterns {
+        let expected = reference(pattern, text);
+        for n in [1, 2, 3, 7, 100] {
+            assert_eq!(stream_matches(pattern, text, n), expected, "pattern {:?} with {} bytes per read", pattern, n);
+        }
+    }
+}
+
+#[test]
+fn test_find_iter_positions() {
+    let regex = StreamRegex::new(r"\d+").unwrap();
+    let mut reader = Reader::new("a1\n\u{e9}22 333\n".as_bytes());
+    let found : Vec<_> = reader.find_iter(&regex)
+        .map(|m| m.unwrap())
+        .map(|(text, span)| (text, span.start().line_position(), span.end().line_position()))
+        .collect();
+    assert_eq!(found, [("1".to_string(),   (1, 2), (1, 3)),
+                       ("22".to_string(),  (2, 2), (2, 4)),
+                       ("333".to_string(), (2, 5), (2, 8))]);
+}
+
+#[test]
+fn test_window_is_bounded() {
+    let regex = StreamRegex::new(r"ab+c").unwrap();
+    let text = "xyz ab abbb abbc ".repeat(10_000);
+    let mut reader = Reader::new(text.as_bytes());
+    let mut matcher = RegexMatcher::new(&regex);
+    let mut count = 0;
+    let mut max_window = 0;
+    while let Some((found, _)) = matcher.next_match(&mut reader).unwrap() {
+        assert_eq!(found, "abbc");
+        max_window = max_window.max(matcher.window_len());
+        count += 1;
+    }
+    assert_eq!(count, 10_000);
+    assert!(max_window < 8, "window held {} bytes", max_window);
+}
+
+#[test]
+fn test_malformed_utf8() {
+    let regex = StreamRegex::new(r"[a-z]+").unwrap();
+    let mut reader = Reader::new(&b"ab\xffcd ef"[..]);
+    let mut matcher = RegexMatcher::new(&regex);
+    let (found, _) = matcher.next_match(&mut reader).unwrap().unwrap();
+    assert_eq!(found, "ab");
+    assert!(matcher.next_match(&mut reader).is_err());
+    let (found, span) = matcher.next_match(&mut reader).unwrap().unwrap();
+    assert_eq!((found.as_str(), span.start().byte()), ("cd", 3));
+    let (found, _) = matcher.next_match(&mut reader).unwrap().unwrap();
+    assert_eq!(found, "ef");
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Reader, ReaderState, StreamPosition};
+
+fn test_text() -> String {
+    let mut text = String::new();
+    for i in 0..500 {
+        text += &format!("{} \u{1f600} \u{2764}\n", i);
+    }
+    text
+}
+
+/// Read up to `n` characters, returning them
+fn read_chars<R:std::io::Read>(reader:&mut Reader<R>, n:usize) -> String {
+    let mut s = String::new();
+    while s.chars().count() < n {
+        match reader.next_char().unwrap() {
+            Char::Char(ch) => { s.push(ch); }
+            _ => { break; }
+        }
+    }
+    s
+}
+
+#[test]
+fn test_resume_after_complete() {
+    let text = test_text();
+    let bytes = text.as_bytes();
+    let mut result = String::new();
+    // Split the stream at awkward byte offsets, mid-UTF-8 encoding
+    let mut pos = StreamPosition::new();
+    let mut leftover = Vec::new();
+    let mut ofs = 0;
+    while ofs < bytes.len() {
+        let end = (ofs + 1001).min(bytes.len());
+        let mut chunk = &bytes[ofs..end];
+        let mut reader = Reader::resume(&mut chunk, pos, &leftover).set_eof_on_no_data(false);
+        result += &read_chars(&mut reader, usize::MAX);
+        let (_, p, l) = reader.complete();
+        pos = p;
+        leftover = l;
+        ofs = end;
+    }
+    assert_eq!(result, text);
+    assert!(leftover.is_empty());
+    assert_eq!(pos.byte(), bytes.len());
+    assert_eq!(pos.line_position(), (501, 1));
+}
+
+#[test]
+fn test_checkpoint_and_restart() {
+    let text = test_text();
+    let bytes = text.as_bytes();
+    let mut stream = bytes;
+    let mut reader = Reader::new(&mut stream);
+    let mut result = read_chars(&mut reader, 1234);
+    let state = reader.state();
+    assert_eq!(state.position(), *reader.borrow_pos());
+    assert_eq!(state.stream_offset(), state.position().byte() + state.leftover().len());
+
+    // Restart reading from the checkpoint as if from a reopened file
+    let mut stream = &bytes[state.stream_offset()..];
+    let mut reader = Reader::from_state(&mut stream, &state);
+    result += &read_chars(&mut reader, usize::MAX);
+    assert_eq!(result, text);
+    assert_eq!(*reader.borrow_pos(), StreamPosition::of_blc(bytes.len(), 501, 1));
+    assert_eq!(reader.state(), ReaderState::new(*reader.borrow_pos(), &[]));
+}
+
+#[test]
+fn test_resume_after_long_peek() {
+    let text = test_text();
+    let bytes = text.as_bytes();
+    // A scan that stalls peeks every character of the stream, which
+    // is far more than the internal buffer holds
+    let mut stream = bytes;
+    let mut reader = Reader::new(&mut stream).set_eof_on_no_data(false);
+    let start = read_chars(&mut reader, 10);
+    assert_eq!(start, "0 \u{1f600} \u{2764}\n1 \u{1f600} ");
+    assert!(reader.read_until_str("NOPE").unwrap().is_none());
+    let state = reader.state();
+    let (_, pos, leftover) = reader.complete();
+    assert_eq!(leftover.len(), bytes.len() - start.len());
+    assert_eq!(state, ReaderState::new(pos, &leftover));
+
+    let mut empty : &[u8] = &[];
+    let mut reader = Reader::resume(&mut empty, pos, &leftover);
+    assert!(!reader.buffer_is_empty());
+    assert_eq!(reader.state(), state);
+    assert_eq!(read_chars(&mut reader, usize::MAX), &text[start.len()..]);
+    assert_eq!(*reader.borrow_pos(), StreamPosition::of_blc(bytes.len(), 501, 1));
+
+    let mut empty : &[u8] = &[];
+    let mut reader = Reader::from_state(&mut empty, &state);
+    assert_eq!(read_chars(&mut reader, 100), text[start.len()..].chars().take(100).collect::<String>());
+    reader.drop_buffer();
+    assert!(reader.buffer_is_empty());
+    assert_eq!(reader.borrow_pos().byte(), bytes.len());
+
+    // Malformed UTF-8 that has been peeked is left over too
+    let mut stream : &[u8] = b"ab\xffcd";
+    let mut reader = Reader::new(&mut stream);
+    assert!(reader.read_until('d').is_err());
+    let (_, pos, leftover) = reader.complete();
+    assert_eq!((pos.byte(), leftover), (0, b"ab\xffcd".to_vec()));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_state() {
+    let state = ReaderState::new(StreamPosition::of_blc(10, 2, 3), &[0xe2, 0x9d]);
+    let json = serde_json::to_string(&state).unwrap();
+    let restored : ReaderState = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, state);
+}
@@ -0,0 +1,157 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Error, Reader, Result, StreamPosition};
+mod utils;
+use utils::StopStart;
+
+type StallingReader<'a> = Reader<StopStart<&'a mut &'a [u8]>>;
+
+/// Repeat a scan until it completes, as a client would when the
+/// stream has more data, setting EOF once the stream is complete
+fn retry<'a, T, F:FnMut(&mut StallingReader<'a>) -> Result<Option<T>>>(reader:&mut StallingReader<'a>, mut f:F) -> Result<T> {
+    loop {
+        if let Some(t) = f(reader)? {
+            return Ok(t);
+        }
+        reader.borrow_mut().kick();
+        if reader.borrow().is_eof() {
+            reader.set_eof(true);
+        }
+    }
+}
+
+#[test]
+fn test_scan_across_stalls() {
+    let text = "  keyword ident\u{e9}_42 <!-- comment - -- -> \u{1f600} -->rest\n";
+    for n in 1..8 {
+        let mut bytes = text.as_bytes();
+        let mut reader : StallingReader = Reader::new(StopStart::new(&mut bytes, n)).set_eof_on_no_data(false);
+        assert_eq!(retry(&mut reader, |r| r.skip_whitespace()).unwrap(), 2);
+        let span = retry(&mut reader, |r| r.expect_str("keyword")).unwrap();
+        assert_eq!((span.start().byte(), span.end().byte()), (2, 9));
+        retry(&mut reader, |r| r.expect_char(' ')).unwrap();
+        let (ident, span) = retry(&mut reader, |r| r.take_while(|c| c.is_alphanumeric() || c == '_')).unwrap();
+        assert_eq!(ident, "ident\u{e9}_42");
+        assert_eq!(span.start().line_position(), (1, 11));
+        assert_eq!(span.end().line_position(), (1, 20));
+        retry(&mut reader, |r| r.skip_whitespace()).unwrap();
+        retry(&mut reader, |r| r.expect_str("<!--")).unwrap();
+        let (comment, _) = retry(&mut reader, |r| r.read_until_str("-->")).unwrap();
+        assert_eq!(comment, " comment - -- -> \u{1f600} ");
+        let (rest, span) = retry(&mut reader, |r| r.read_until('\n')).unwrap();
+        assert_eq!(rest, "rest");
+        assert_eq!(span.end().line_position(), (1, 51));
+        assert_eq!(*reader.borrow_pos(), StreamPosition::of_blc(text.len(), 2, 1));
+        assert_eq!(retry(&mut reader, |r| r.skip_whitespace()).unwrap(), 0);
+        assert_eq!(reader.next_char().unwrap(), Char::Eof);
+    }
+}
+
+#[test]
+fn test_expectation_errors() {
+    let mut bytes = "let x".as_bytes();
+    let mut reader = Reader::new(&mut bytes);
+    match reader.expect_str("lex") {
+        Err(Error::Expected(pos, expected, Char::Char('t'))) => {
+            assert_eq!(pos.line_position(), (1, 3));
+            assert_eq!(expected, "\"lex\"");
+        }
+        x => { panic!("Expected an Expected error, got {:?}", x); }
+    }
+    // Nothing is consumed by a failed expectation
+    assert_eq!(reader.borrow_pos().byte(), 0);
+    reader.expect_str("let").unwrap().unwrap();
+    match reader.read_until(';') {
+        Err(e) => {
+            assert_eq!(e.to_string(), "expected ';' at line 1 char 6, found end of stream");
+            assert_eq!(e.position(), Some(StreamPosition::of_blc(5, 1, 6)));
+        }
+        x => { panic!("Expected an Expected error, got {:?}", x); }
+    }
+    assert_eq!(reader.take_while(|_| true).unwrap().unwrap().0, " x");
+    assert!(matches!(reader.expect_char('x'), Err(Error::Expected(_, _, Char::Eof))));
+}
+
+#[test]
+fn test_malformed_during_scan() {
+    // Errors are returned in stream order, after the characters before them
+    let mut bytes : &[u8] = b"ab\xffcd efg";
+    let mut reader = Reader::new(&mut bytes);
+    let (word, span) = reader.take_while(|c| c.is_alphabetic()).unwrap().unwrap();
+    assert_eq!(word, "ab");
+    assert_eq!(span.byte_len(), 2);
+    match reader.take_while(|c| c.is_alphabetic()) {
+        Err(Error::MalformedUtf8(pos, _, _)) => { assert_eq!(pos.byte(), 2); }
+        x => { panic!("Expected a malformed UTF-8 error, got {:?}", x); }
+    }
+    assert_eq!(reader.take_while(|c| c.is_alphabetic()).unwrap().unwrap().0, "cd");
+
+    // A scan for a terminator or an expected string fails with the
+    // error, which is returned again after the characters before it
+    let mut bytes : &[u8] = b"<a\xffb>";
+    let mut reader = Reader::new(&mut bytes);
+    match reader.read_until('>') {
+        Err(e @ Error::MalformedUtf8(..)) => {
+            assert_eq!(e.position().unwrap().byte(), 2);
+            assert_eq!(e.to_string(), "malformed UTF-8 at line 1 char 3: invalid lead byte (bytes ff)");
+        }
+        x => { panic!("Expected a malformed UTF-8 error, got {:?}", x); }
+    }
+    assert!(matches!(reader.expect_str("<a>"), Err(Error::MalformedUtf8(pos, _, _)) if pos.byte() == 2));
+    reader.expect_str("<a").unwrap().unwrap();
+    assert!(matches!(reader.read_until('>'), Err(Error::MalformedUtf8(..))));
+    assert_eq!(reader.read_until('>').unwrap().unwrap().0, "b");
+}
+
+#[test]
+fn test_long_scan_across_stalls() {
+    // Each retry continues the scan, rather than peeking from the start
+    let text = format!("{}-->x", "- ->-".repeat(4000));
+    let mut bytes = text.as_bytes();
+    let mut reader : StallingReader = Reader::new(StopStart::new(&mut bytes, 7)).set_eof_on_no_data(false);
+    let mut retries = 0;
+    let (comment, span) = retry(&mut reader, |r| { retries += 1; r.read_until_str("-->") }).unwrap();
+    assert!(retries > 2000);
+    assert_eq!(comment.len(), 20_000);
+    assert_eq!(span.end().byte(), 20_000);
+    assert_eq!(reader.next_char().unwrap(), Char::Char('x'));
+
+    // A scan of a predicate is also continued, invoking it once for
+    // each character
+    let text = format!("{}.", "abc".repeat(4000));
+    let mut bytes = text.as_bytes();
+    let mut reader : StallingReader = Reader::new(StopStart::new(&mut bytes, 7)).set_eof_on_no_data(false);
+    let calls = std::cell::Cell::new(0);
+    let (word, _) = retry(&mut reader, |r| r.take_while(|c| { calls.set(calls.get() + 1); c.is_alphabetic() })).unwrap();
+    assert_eq!(word.len(), 12_000);
+    assert_eq!(calls.get(), 12_001);
+    assert_eq!(reader.next_char().unwrap(), Char::Char('.'));
+
+    // An incomplete scan leaves its characters to be returned by complete
+    let mut bytes : &[u8] = &[b'a'; 5000];
+    let mut reader = Reader::new(&mut bytes);
+    assert!(matches!(reader.read_until_str("NOPE"), Err(Error::Expected(_, _, Char::Eof))));
+    let (_, pos, leftover) = reader.complete();
+    assert_eq!(pos.byte(), 0);
+    assert_eq!(leftover.len(), 5000);
+}
+
+#[test]
+fn test_peek_state_and_capture() {
+    let mut bytes = "x\u{2764}yz".as_bytes();
+    let mut reader = Reader::new(&mut bytes);
+    reader.begin_capture();
+    assert_eq!(reader.next_char().unwrap(), Char::Char('x'));
+    assert!(reader.expect_str("\u{2764}yq").is_err());
+    assert_eq!(reader.peek_char().unwrap(), Char::Char('\u{2764}'));
+    let state = reader.state();
+    assert_eq!(state.position().byte(), 1);
+    assert_eq!(state.leftover(), "\u{2764}yz".as_bytes());
+    assert_eq!(reader.next_char().unwrap(), Char::Char('\u{2764}'));
+    let (text, span) = reader.end_capture();
+    assert_eq!(text, "x\u{2764}");
+    assert_eq!(span.end().byte(), 4);
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    assert_eq!(s, "yz");
+}
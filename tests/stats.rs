@@ -0,0 +1,90 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, LineEndings, Reader, StreamPosition, TextStats};
+mod utils;
+use utils::StopStart;
+
+/// Collect the statistics of `text` read one character at a time
+/// with `n` bytes per read
+fn stats_by_char(text:&str, n:usize) -> TextStats {
+    let mut bytes = text.as_bytes();
+    let mut reader = Reader::new(StopStart::new(&mut bytes, n)).set_eof_on_no_data(false).collect_stats();
+    loop {
+        match reader.next_char().unwrap() {
+            Char::Char(_) => (),
+            Char::NoData => {
+                reader.borrow_mut().kick();
+                if reader.borrow().is_eof() {
+                    reader.set_eof(true);
+                }
+            }
+            Char::Eof => { break; }
+        }
+    }
+    reader.text_stats().unwrap().clone()
+}
+
+/// Collect the statistics of `text` read with
+/// [read_to_string](Reader::read_to_string)
+fn stats_in_bulk(text:&str) -> TextStats {
+    let mut reader = Reader::new(text.as_bytes()).collect_stats();
+    let mut s = String::new();
+    reader.read_to_string(&mut s).unwrap();
+    reader.text_stats().unwrap().clone()
+}
+
+#[test]
+fn test_counts() {
+    let text = "first line\r\n\u{e9}t\u{e9}\r\n\tthe longest line \u{1f600}\r\nx\u{1b}[0m\n\r\nend";
+    let stats = stats_in_bulk(text);
+    assert_eq!(stats.bytes(), text.len());
+    assert_eq!(stats.chars(), text.chars().count());
+    assert_eq!((stats.newlines(), stats.lines()), (5, 6));
+    assert_eq!(stats.end(), StreamPosition::of_blc(text.len(), 6, 4));
+    assert_eq!(stats.longest_line(), (19, StreamPosition::of_blc(19, 3, 1)));
+    assert_eq!(stats.non_ascii(), (3, Some(StreamPosition::of_blc(12, 2, 1))));
+    assert_eq!(stats.control(), (1, Some(StreamPosition::of_blc(44, 4, 2))));
+    assert_eq!(stats.whitespace(), 14);
+    assert_eq!(stats.line_endings(), (LineEndings::Mixed, Some(StreamPosition::of_blc(48, 4, 6))));
+    assert!(!stats.has_trailing_newline());
+    for n in 1..6 {
+        assert_eq!(stats_by_char(text, n), stats, "with {} bytes per read", n);
+    }
+}
+
+#[test]
+fn test_line_endings() {
+    let cases = [
+        ("", LineEndings::None, None, false, 0),
+        ("no ending", LineEndings::None, None, false, 1),
+        ("a\nb\n", LineEndings::Lf, None, true, 2),
+        ("a\r\nb\r\n", LineEndings::CrLf, None, true, 2),
+        ("a\rb\r", LineEndings::Cr, None, true, 1),
+        ("a\r\nb\rc", LineEndings::Mixed, Some(4), false, 2),
+        ("a\nb\r", LineEndings::Mixed, Some(3), true, 2),
+    ];
+    for (text, endings, mixed, trailing, lines) in cases {
+        let stats = stats_in_bulk(text);
+        assert_eq!(stats.line_endings().0, endings, "{:?}", text);
+        assert_eq!(stats.line_endings().1.map(|p| p.byte()), mixed, "{:?}", text);
+        assert_eq!(stats.has_trailing_newline(), trailing, "{:?}", text);
+        assert_eq!(stats.lines(), lines, "{:?}", text);
+        assert_eq!(stats_by_char(text, 1), stats, "{:?}", text);
+    }
+}
+
+#[test]
+fn test_stats_from_position() {
+    let mut reader = Reader::new("skip\nab\u{0}c".as_bytes());
+    let mut s = String::new();
+    reader.read_line(&mut s).unwrap();
+    let (source, pos, leftover) = reader.complete();
+    let mut reader = Reader::resume(source, pos, &leftover).collect_stats();
+    assert_eq!(reader.text_stats().unwrap().chars(), 0);
+    reader.read_to_string(&mut s).unwrap();
+    let stats = reader.text_stats().unwrap();
+    assert_eq!(stats.start(), StreamPosition::of_blc(5, 2, 1));
+    assert_eq!((stats.bytes(), stats.chars(), stats.lines()), (4, 4, 1));
+    assert_eq!(stats.control(), (1, Some(StreamPosition::of_blc(7, 2, 3))));
+    assert!(Reader::new("".as_bytes()).text_stats().is_none());
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "std")]
 //a Imports
 use utf8_read::{Reader};
 mod utils;
@@ -40,6 +41,7 @@ fn test_string_stop_start(buf:&str) {
     assert_eq!(n, char_list.len(), "Must have consumed the whole string");
 }
 
+#[allow(clippy::assertions_on_constants)]
 fn test_string_stop_start2(buf:&str) {
     let char_list : Vec<char> = buf.chars().collect();
 
@@ -1,6 +1,7 @@
 //a Imports
-use utf8_read::{Error, StreamPosition, Reader};
+use utf8_read::{Char, Error, StreamPosition, Reader, Utf8ErrorKind};
 
+#[allow(clippy::assertions_on_constants)]
 fn test_buf_exp(buf_bytes : &[u8], expectation :&[Result<char, Error>]) {
     let mut reader = Reader::new(buf_bytes);
     let ch : Vec<Result<char, Error>> = reader.into_iter().collect();
@@ -8,9 +9,10 @@ fn test_buf_exp(buf_bytes : &[u8], expectation :&[Result<char, Error>]) {
     for (n,(a,b)) in ch.iter().zip(expectation).enumerate() {
         match (a, b) {
             (Ok(x), Ok(y))  => {assert_eq!(x,y, "Mismatch in character for {}", n);}
-            ( Err(Error::MalformedUtf8(p0,n0)), Err(Error::MalformedUtf8(p1,n1)) ) => {
+            ( Err(Error::MalformedUtf8(p0,k0,b0)), Err(Error::MalformedUtf8(p1,k1,b1)) ) => {
                 assert_eq!(p0,p1,"Mismatch in stream positions for errors for {}",n);
-                assert_eq!(n0,n1,"Mismatch in byte length for errors for {}",n);
+                assert_eq!(k0,k1,"Mismatch in error kind for errors for {}",n);
+                assert_eq!(b0,b1,"Mismatch in bytes for errors for {}",n);
             },
             _ => {
                 assert!(false, "Mismatch in expectation for {} : {:?} != {:?}", n, a, b);
@@ -25,17 +27,17 @@ fn test_buf_exp(buf_bytes : &[u8], expectation :&[Result<char, Error>]) {
 fn test_me() {
     test_buf_exp("a".as_bytes(), &[Ok('a')]);
     test_buf_exp(b"\x80  ", &[
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
         Ok(' '), Ok(' ')
             ]);
     test_buf_exp(b" \x80", &[
         Ok(' '),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
             ]);
 
     test_buf_exp(b" \xc0", &[
         Ok(' '),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),Utf8ErrorKind::Overlong,vec![0xc0])),
     ]);
 
     test_buf_exp(b"1\xc2\x80345\x80", &[
@@ -44,7 +46,7 @@ fn test_me() {
         Ok('3'),
         Ok('4'),
         Ok('5'),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(6,1,6),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(6,1,6),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
     ]);
 
     // 0xc0 0x80 is an overlong encoding
@@ -52,18 +54,18 @@ fn test_me() {
     // Then the 0x80 will be a single byte UTF8 error too
     test_buf_exp(b" \xc0\x80", &[
         Ok(' '),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),1)),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,2),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),Utf8ErrorKind::Overlong,vec![0xc0])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,2),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
             ]);
 
-    // 0xe0 0x80 expects another 0x8_ - it is a short encoding
+    // 0xe0 0x80 is the start of an overlong encoding
     // Rust utf8 indicates the 0xe0 is a single byte UTF8 error
     // Then the 0x80 will be a single byte UTF8 error too
     // The following byte is not an error - it is a space
     test_buf_exp(b" \xe0\x80\x20", &[
         Ok(' '),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),1)),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,2),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,2),Utf8ErrorKind::Overlong,vec![0xe0])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,2),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
         Ok(' '),
             ]);
 
@@ -71,14 +73,95 @@ fn test_me() {
         Ok('H'),Ok('e'),Ok('l'),Ok('l'),Ok('o'),Ok('\n'),
         Ok('F'),Ok('u'),Ok('n'),Ok('!'),Ok('\n'),
         Ok('1'),Ok('2'),Ok('3'),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(14,3,4),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(14,3,4),Utf8ErrorKind::Overlong,vec![0xc0])),
     ]);
 
     test_buf_exp(b"Hello\nFun!\n123\xc0", &[
         Ok('H'),Ok('e'),Ok('l'),Ok('l'),Ok('o'),Ok('\n'),
         Ok('F'),Ok('u'),Ok('n'),Ok('!'),Ok('\n'),
         Ok('1'),Ok('2'),Ok('3'),
-        Err(Error::MalformedUtf8(StreamPosition::of_blc(14,3,4),1)),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(14,3,4),Utf8ErrorKind::Overlong,vec![0xc0])),
     ]);
 
 }
+
+#[test]
+fn test_error_kinds() {
+    // 0xed 0xa0 0x80 would be the surrogate U+D800
+    test_buf_exp(b"\xed\xa0\x80a", &[
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),Utf8ErrorKind::Surrogate,vec![0xed])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0xa0])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
+        Ok('a'),
+    ]);
+
+    // 0xf4 0x90 would start a code point of U+110000 or above
+    test_buf_exp(b"\xf4\x90\x80\x80", &[
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),Utf8ErrorKind::OutOfRange,vec![0xf4])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x90])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(3,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
+    ]);
+    test_buf_exp(b"\xf5", &[
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),Utf8ErrorKind::OutOfRange,vec![0xf5])),
+    ]);
+
+    // 0xf0 0x80 is the start of an overlong encoding
+    test_buf_exp(b"\xf0\x80\x80\x80", &[
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),Utf8ErrorKind::Overlong,vec![0xf0])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(3,1,1),Utf8ErrorKind::UnexpectedContinuation,vec![0x80])),
+    ]);
+
+    // A three-byte sequence interrupted after two bytes
+    test_buf_exp(b"\xe2\x9d!", &[
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),Utf8ErrorKind::Incomplete,vec![0xe2,0x9d])),
+        Ok('!'),
+    ]);
+
+    test_buf_exp(b"\xff\xfe", &[
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(0,1,1),Utf8ErrorKind::InvalidLeadByte,vec![0xff])),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(1,1,1),Utf8ErrorKind::InvalidLeadByte,vec![0xfe])),
+    ]);
+
+    // Latin-1 text such as 'caf\xe9 ' is an incomplete sequence
+    test_buf_exp(b"caf\xe9 ", &[
+        Ok('c'),Ok('a'),Ok('f'),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(3,1,4),Utf8ErrorKind::Incomplete,vec![0xe9])),
+        Ok(' '),
+    ]);
+
+    // A sequence truncated by the end of the stream
+    test_buf_exp(b"ab\xf0\x9f\x98", &[
+        Ok('a'),Ok('b'),
+        Err(Error::MalformedUtf8(StreamPosition::of_blc(2,1,3),Utf8ErrorKind::TruncatedAtEof,vec![0xf0,0x9f,0x98])),
+    ]);
+}
+
+#[test]
+fn test_error_display() {
+    let e = Error::MalformedUtf8(StreamPosition::of_blc(2,1,3),Utf8ErrorKind::TruncatedAtEof,vec![0xf0,0x9f,0x98]);
+    assert_eq!(format!("{}", e), "malformed UTF-8 at line 1 char 3: sequence truncated at end of stream (bytes f0 9f 98)");
+}
+
+#[test]
+fn test_set_eof_truncated() {
+    let mut buf_bytes : &[u8] = b"a\xe2\x9d";
+    let mut reader = Reader::new(&mut buf_bytes).set_eof_on_no_data(false);
+    assert_eq!(reader.next_char().unwrap(), Char::Char('a'));
+    assert_eq!(reader.next_char().unwrap(), Char::NoData);
+    reader.set_eof(true);
+    match reader.next_char() {
+        Err(Error::MalformedUtf8(pos, Utf8ErrorKind::TruncatedAtEof, bytes)) => {
+            assert_eq!(pos, StreamPosition::of_blc(1,1,2));
+            assert_eq!(bytes, vec![0xe2, 0x9d]);
+        }
+        x => panic!("Expected truncated UTF-8 error, got {:?}", x),
+    }
+    assert_eq!(reader.next_char().unwrap(), Char::Eof);
+    let (_, pos, rest) = reader.complete();
+    assert_eq!(pos.byte(), 3);
+    assert!(rest.is_empty());
+}
+
@@ -0,0 +1,134 @@
+#![cfg(feature = "std")]
+//a Imports
+use utf8_read::{Char, Error, EscapeDialect, Reader};
+mod utils;
+use utils::StopStart;
+
+/// Characters with the byte ranges of their spans
+type Spanned = Vec<(char, (usize, usize))>;
+
+/// Unescape all of `text`, returning the characters with the byte
+/// ranges of their spans, and the byte positions of any errors
+fn unescape_all(text:&str, dialect:EscapeDialect) -> (Spanned, Vec<usize>) {
+    let mut reader = Reader::new(text.as_bytes());
+    let mut unescape = reader.unescape(dialect);
+    let mut chars = Vec::new();
+    let mut errors = Vec::new();
+    loop {
+        match unescape.next_char() {
+            Ok((Char::Char(ch), span)) => { chars.push((ch, (span.start().byte(), span.end().byte()))); }
+            Ok(_) => { return (chars, errors); }
+            Err(e) => { errors.push(e.position().unwrap().byte()); }
+        }
+    }
+}
+
+#[test]
+fn test_dialects() {
+    let (chars, errors) = unescape_all(r#"\"\/\b\u00e9\ud83d\ude00x"#, EscapeDialect::Json);
+    assert!(errors.is_empty());
+    assert_eq!(chars, [('"', (0, 2)), ('/', (2, 4)), ('\u{8}', (4, 6)), ('\u{e9}', (6, 12)),
+                       ('\u{1f600}', (12, 24)), ('x', (24, 25))]);
+    let (chars, errors) = unescape_all("\\0\\x41\\u{1F600}\\'a\\\n  \t b", EscapeDialect::Rust);
+    assert!(errors.is_empty());
+    assert_eq!(chars, [('\0', (0, 2)), ('A', (2, 6)), ('\u{1f600}', (6, 15)), ('\'', (15, 17)),
+                       ('a', (17, 18)), ('b', (24, 25))]);
+    let (chars, errors) = unescape_all(r"\a\v\?\101\0\1234\x263aé\U0001F600", EscapeDialect::C);
+    assert!(errors.is_empty());
+    let text : String = chars.iter().map(|(c, _)| *c).collect();
+    assert_eq!(text, "\u{7}\u{b}?A\0S4\u{263a}\u{e9}\u{1f600}");
+    assert_eq!(chars[5], ('S', (12, 16)));
+    assert_eq!(chars[7], ('\u{263a}', (17, 23)));
+}
+
+#[test]
+fn test_across_stalls() {
+    let text = "\u{e9}\\u00e9\\ud83d\\ude00\\n\\\"end\" after";
+    let expected = unescape_all(text, EscapeDialect::Json).0;
+    for n in 1..8 {
+        let mut bytes = text.as_bytes();
+        let mut reader = Reader::new(StopStart::new(&mut bytes, n)).set_eof_on_no_data(false);
+        let mut chars = Vec::new();
+        loop {
+            match reader.unescape(EscapeDialect::Json).stop_at('"').next_char().unwrap() {
+                (Char::Char(ch), span) => { chars.push((ch, (span.start().byte(), span.end().byte()))); }
+                (Char::NoData, _) => { reader.borrow_mut().kick(); }
+                (_, span) => {
+                    assert!(!reader.borrow().is_eof());
+                    assert_eq!(span.start().byte(), 27);
+                    break;
+                }
+            }
+        }
+        assert_eq!(chars, &expected[..chars.len()], "with {} bytes per read", n);
+        assert_eq!(chars.len(), 8);
+        assert_eq!(reader.next_char().unwrap(), Char::Char('"'));
+    }
+
+    // A C octal escape is complete after three digits, without
+    // waiting for the character after it
+    let mut bytes : &[u8] = b"\\1014";
+    let mut reader = Reader::new(StopStart::new(&mut bytes, 4)).set_eof_on_no_data(false);
+    let mut unescape = reader.unescape(EscapeDialect::C);
+    let (ch, span) = unescape.next_char().unwrap();
+    assert_eq!((ch, span.end().byte()), (Char::Char('A'), 4));
+    assert_eq!(unescape.next_char().unwrap().0, Char::NoData);
+}
+
+#[test]
+fn test_error_positions() {
+    // A bad hex digit is reported at the digit, which is not consumed
+    let (chars, errors) = unescape_all(r"ab\u12G4", EscapeDialect::Json);
+    assert_eq!(errors, [6]);
+    assert_eq!(chars.iter().map(|(c, _)| *c).collect::<String>(), "abG4");
+    // Lone surrogates are reported at their backslash, and consumed
+    let (chars, errors) = unescape_all(r"\ud800x\udc00\ud83dA", EscapeDialect::Json);
+    assert_eq!(errors, [0, 7, 13]);
+    assert_eq!(chars, [('x', (6, 7)), ('A', (19, 20))]);
+    let (_, errors) = unescape_all(r"\q\x80\u{110000}\u{d800}\u12", EscapeDialect::Rust);
+    assert_eq!(errors, [0, 2, 6, 16, 26]);
+    let (_, errors) = unescape_all(r"\U0000D800\xffffffff\8", EscapeDialect::C);
+    assert_eq!(errors, [0, 10, 20]);
+
+    let mut reader = Reader::new(r"x\".as_bytes());
+    let mut unescape = reader.unescape(EscapeDialect::C);
+    assert_eq!(unescape.next().unwrap().unwrap().0, 'x');
+    match unescape.next() {
+        Some(Err(Error::Expected(pos, _, Char::Eof))) => { assert_eq!(pos.byte(), 2); }
+        x => { panic!("unexpected {:?}", x); }
+    }
+    assert!(unescape.next().is_none());
+
+    let mut reader = Reader::new(r"\uD83D".as_bytes());
+    let err = reader.unescape(EscapeDialect::Json).next_char().unwrap_err();
+    assert_eq!(format!("{}", err), "lone surrogate U+D83D at line 1 char 1");
+}
+
+#[test]
+fn test_malformed_in_escape() {
+    // A malformed byte within an escape makes the escape invalid at
+    // that byte, and is never skipped to complete the escape
+    let mut bytes : &[u8] = b"\\u00\xff41z";
+    let mut reader = Reader::new(&mut bytes);
+    let mut unescape = reader.unescape(EscapeDialect::Json);
+    let err = unescape.next_char().unwrap_err();
+    assert_eq!(format!("{}", err), "expected hexadecimal digit, found malformed UTF-8 at line 1 char 5");
+    match unescape.next_char() {
+        Err(Error::MalformedUtf8(pos, _, bytes)) => { assert_eq!((pos.byte(), bytes), (4, vec![0xff])); }
+        x => { panic!("unexpected {:?}", x); }
+    }
+    let rest : Vec<_> = unescape.map(|r| r.unwrap()).map(|(c, span)| (c, span.start().byte())).collect();
+    assert_eq!(rest, [('4', 5), ('1', 6), ('z', 7)]);
+
+    let mut bytes : &[u8] = b"\\\xffa\\x41\xfe";
+    let mut reader = Reader::new(&mut bytes);
+    let mut unescape = reader.unescape(EscapeDialect::C);
+    let err = unescape.next_char().unwrap_err();
+    assert_eq!(format!("{}", err), "expected escape character, found malformed UTF-8 at line 1 char 2");
+    assert!(matches!(unescape.next_char(), Err(Error::MalformedUtf8(pos, _, _)) if pos.byte() == 1));
+    assert_eq!(unescape.next_char().unwrap().0, Char::Char('a'));
+    // An escape that may end at the malformed byte does so
+    assert_eq!(unescape.next_char().unwrap().0, Char::Char('A'));
+    assert!(matches!(unescape.next_char(), Err(Error::MalformedUtf8(pos, _, _)) if pos.byte() == 7));
+    assert_eq!(unescape.next_char().unwrap().0, Char::Eof);
+}